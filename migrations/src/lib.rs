@@ -3,6 +3,18 @@ pub use sea_orm_migration::prelude::*;
 mod m22062024_000001_create_podcast_table;
 mod m22062024_000001_create_episode_table;
 mod m26102024_000001_create_episode_state;
+mod m18102026_000001_create_podcast_url_history;
+mod m18102026_000002_add_podcast_subscription_fields;
+mod m18102026_000003_create_tag_tables;
+mod m18102026_000004_create_smart_playlists;
+mod m18102026_000005_add_episode_inbox_fields;
+mod m18102026_000006_create_listening_session;
+mod m18102026_000007_add_artwork_urls;
+mod m18102026_000008_create_bookmark;
+mod m18102026_000009_add_podcast_gain;
+mod m18102026_000010_add_session_trimmed_seconds;
+mod m18102026_000011_add_episode_enclosure_type;
+mod m18102026_000012_add_alternate_enclosures;
 
 pub struct Migrator;
 
@@ -12,7 +24,19 @@ impl MigratorTrait for Migrator {
         vec![
             Box::new(m22062024_000001_create_podcast_table::Migration),
             Box::new(m22062024_000001_create_episode_table::Migration),
-            Box::new(m26102024_000001_create_episode_state::Migration)
+            Box::new(m26102024_000001_create_episode_state::Migration),
+            Box::new(m18102026_000001_create_podcast_url_history::Migration),
+            Box::new(m18102026_000002_add_podcast_subscription_fields::Migration),
+            Box::new(m18102026_000003_create_tag_tables::Migration),
+            Box::new(m18102026_000004_create_smart_playlists::Migration),
+            Box::new(m18102026_000005_add_episode_inbox_fields::Migration),
+            Box::new(m18102026_000006_create_listening_session::Migration),
+            Box::new(m18102026_000007_add_artwork_urls::Migration),
+            Box::new(m18102026_000008_create_bookmark::Migration),
+            Box::new(m18102026_000009_add_podcast_gain::Migration),
+            Box::new(m18102026_000010_add_session_trimmed_seconds::Migration),
+            Box::new(m18102026_000011_add_episode_enclosure_type::Migration),
+            Box::new(m18102026_000012_add_alternate_enclosures::Migration)
         ]
     }
 }
@@ -0,0 +1,50 @@
+use async_trait::async_trait;
+use sea_orm_migration::prelude::*;
+
+use crate::m22062024_000001_create_podcast_table::Podcast;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(PodcastUrlHistory::Table)
+                        .if_not_exists()
+                            .col(ColumnDef::new(PodcastUrlHistory::Id).integer().not_null().auto_increment().primary_key())
+                            .col(ColumnDef::new(PodcastUrlHistory::PodcastId).integer().not_null())
+                            .col(ColumnDef::new(PodcastUrlHistory::Url).string().not_null())
+                            .col(ColumnDef::new(PodcastUrlHistory::ReplacedBy).string().not_null())
+                            .col(ColumnDef::new(PodcastUrlHistory::Reason).string().not_null())
+                            .col(ColumnDef::new(PodcastUrlHistory::ChangedAt).big_integer().not_null())
+                            .foreign_key(
+                                ForeignKey::create()
+                                    .name("fk-podcast-url-history-podcast-id")
+                                    .from(PodcastUrlHistory::Table, PodcastUrlHistory::PodcastId)
+                                    .to(Podcast::Table, Podcast::Id)
+                                    .on_delete(ForeignKeyAction::Cascade)
+                            )
+                            .to_owned()
+            ).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(PodcastUrlHistory::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum PodcastUrlHistory {
+    Table,
+    Id,
+    PodcastId,
+    Url,
+    ReplacedBy,
+    Reason,
+    ChangedAt
+}
@@ -0,0 +1,59 @@
+use async_trait::async_trait;
+use sea_orm_migration::prelude::*;
+
+use crate::m22062024_000001_create_podcast_table::Podcast;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // SQLite only takes one column per ALTER TABLE
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Podcast::Table)
+                    .add_column(ColumnDef::new(PodcastSubscription::TitleOverride).string())
+                    .to_owned()
+            ).await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Podcast::Table)
+                    .add_column(ColumnDef::new(PodcastSubscription::SortOrder).integer())
+                    .to_owned()
+            ).await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Podcast::Table)
+                    .add_column(ColumnDef::new(PodcastSubscription::UnsubscribedAt).big_integer())
+                    .to_owned()
+            ).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for column in [
+            PodcastSubscription::TitleOverride,
+            PodcastSubscription::SortOrder,
+            PodcastSubscription::UnsubscribedAt,
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Podcast::Table)
+                        .drop_column(column)
+                        .to_owned()
+                ).await?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+pub enum PodcastSubscription {
+    TitleOverride,
+    SortOrder,
+    UnsubscribedAt,
+}
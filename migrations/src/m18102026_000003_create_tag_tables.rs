@@ -0,0 +1,74 @@
+use async_trait::async_trait;
+use sea_orm_migration::prelude::*;
+
+use crate::m22062024_000001_create_podcast_table::Podcast;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Tag::Table)
+                        .if_not_exists()
+                            .col(ColumnDef::new(Tag::Id).integer().not_null().auto_increment().primary_key())
+                            .col(ColumnDef::new(Tag::Name).string().not_null().unique_key())
+                            .to_owned()
+            ).await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(PodcastTag::Table)
+                        .if_not_exists()
+                            .col(ColumnDef::new(PodcastTag::PodcastId).integer().not_null())
+                            .col(ColumnDef::new(PodcastTag::TagId).integer().not_null())
+                            .primary_key(
+                                Index::create()
+                                    .col(PodcastTag::PodcastId)
+                                    .col(PodcastTag::TagId)
+                            )
+                            .foreign_key(
+                                ForeignKey::create()
+                                    .name("fk-podcast-tag-podcast-id")
+                                    .from(PodcastTag::Table, PodcastTag::PodcastId)
+                                    .to(Podcast::Table, Podcast::Id)
+                                    .on_delete(ForeignKeyAction::Cascade)
+                            )
+                            .foreign_key(
+                                ForeignKey::create()
+                                    .name("fk-podcast-tag-tag-id")
+                                    .from(PodcastTag::Table, PodcastTag::TagId)
+                                    .to(Tag::Table, Tag::Id)
+                                    .on_delete(ForeignKeyAction::Cascade)
+                            )
+                            .to_owned()
+            ).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(PodcastTag::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Tag::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum Tag {
+    Table,
+    Id,
+    Name
+}
+
+#[derive(Iden)]
+pub enum PodcastTag {
+    Table,
+    PodcastId,
+    TagId
+}
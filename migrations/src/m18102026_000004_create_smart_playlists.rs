@@ -0,0 +1,75 @@
+use async_trait::async_trait;
+use sea_orm_migration::prelude::*;
+
+use crate::m22062024_000001_create_episode_table::Episode;
+use crate::m26102024_000001_create_episode_state::EpisodeState;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Episode::Table)
+                    .add_column(ColumnDef::new(PlaylistColumns::Duration).integer())
+                    .to_owned()
+            ).await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(EpisodeState::Table)
+                    .add_column(ColumnDef::new(PlaylistColumns::LastPlayed).big_integer())
+                    .to_owned()
+            ).await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(SmartPlaylist::Table)
+                        .if_not_exists()
+                            .col(ColumnDef::new(SmartPlaylist::Id).integer().not_null().auto_increment().primary_key())
+                            .col(ColumnDef::new(SmartPlaylist::Name).string().not_null())
+                            .col(ColumnDef::new(SmartPlaylist::Query).text().not_null())
+                            .to_owned()
+            ).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(SmartPlaylist::Table).to_owned())
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(EpisodeState::Table)
+                    .drop_column(PlaylistColumns::LastPlayed)
+                    .to_owned()
+            ).await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Episode::Table)
+                    .drop_column(PlaylistColumns::Duration)
+                    .to_owned()
+            ).await
+    }
+}
+
+/// Columns playlists filter and sort on: `episode.duration` in seconds and
+/// `episode_state.last_played` as a Unix timestamp
+#[derive(Iden)]
+pub enum PlaylistColumns {
+    Duration,
+    LastPlayed,
+}
+
+#[derive(Iden)]
+pub enum SmartPlaylist {
+    Table,
+    Id,
+    Name,
+    Query
+}
@@ -0,0 +1,47 @@
+use async_trait::async_trait;
+use sea_orm_migration::prelude::*;
+
+use crate::m22062024_000001_create_episode_table::Episode;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Episode::Table)
+                    .add_column(ColumnDef::new(EpisodeInbox::FirstSeen).big_integer())
+                    .to_owned()
+            ).await?;
+        // Episodes stored before the inbox existed aren't new anymore
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Episode::Table)
+                    .add_column(ColumnDef::new(EpisodeInbox::Triaged).boolean().not_null().default(true))
+                    .to_owned()
+            ).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for column in [EpisodeInbox::FirstSeen, EpisodeInbox::Triaged] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Episode::Table)
+                        .drop_column(column)
+                        .to_owned()
+                ).await?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+pub enum EpisodeInbox {
+    FirstSeen,
+    Triaged,
+}
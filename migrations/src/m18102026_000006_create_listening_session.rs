@@ -0,0 +1,65 @@
+use async_trait::async_trait;
+use sea_orm_migration::prelude::*;
+
+use crate::m22062024_000001_create_podcast_table::Podcast;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ListeningSession::Table)
+                        .if_not_exists()
+                            .col(ColumnDef::new(ListeningSession::Id).integer().not_null().auto_increment().primary_key())
+                            .col(ColumnDef::new(ListeningSession::PodcastId).integer().not_null())
+                            .col(ColumnDef::new(ListeningSession::EpLink).string().not_null())
+                            .col(ColumnDef::new(ListeningSession::EpisodeTitle).string())
+                            .col(ColumnDef::new(ListeningSession::StartPosition).double().not_null())
+                            .col(ColumnDef::new(ListeningSession::EndPosition).double().not_null())
+                            .col(ColumnDef::new(ListeningSession::StartedAt).big_integer().not_null())
+                            .col(ColumnDef::new(ListeningSession::EndedAt).big_integer().not_null())
+                            .col(ColumnDef::new(ListeningSession::Speed).double().not_null().default(1.0))
+                            .foreign_key(
+                                ForeignKey::create()
+                                    .name("fk-listening-session-podcast-id")
+                                    .from(ListeningSession::Table, ListeningSession::PodcastId)
+                                    .to(Podcast::Table, Podcast::Id)
+                                    .on_delete(ForeignKeyAction::Cascade)
+                            )
+                            .to_owned()
+            ).await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-listening-session-started-at")
+                    .table(ListeningSession::Table)
+                    .col(ListeningSession::StartedAt)
+                    .to_owned()
+            ).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ListeningSession::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum ListeningSession {
+    Table,
+    Id,
+    PodcastId,
+    EpLink,
+    EpisodeTitle,
+    StartPosition,
+    EndPosition,
+    StartedAt,
+    EndedAt,
+    Speed
+}
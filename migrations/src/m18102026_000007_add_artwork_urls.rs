@@ -0,0 +1,51 @@
+use async_trait::async_trait;
+use sea_orm_migration::prelude::*;
+
+use crate::m22062024_000001_create_episode_table::Episode;
+use crate::m22062024_000001_create_podcast_table::Podcast;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Podcast::Table)
+                    .add_column(ColumnDef::new(Artwork::ImageUrl).string())
+                    .to_owned()
+            ).await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Episode::Table)
+                    .add_column(ColumnDef::new(Artwork::ImageUrl).string())
+                    .to_owned()
+            ).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Episode::Table)
+                    .drop_column(Artwork::ImageUrl)
+                    .to_owned()
+            ).await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Podcast::Table)
+                    .drop_column(Artwork::ImageUrl)
+                    .to_owned()
+            ).await
+    }
+}
+
+/// Artwork URL from the feed, on both `podcast` and `episode`
+#[derive(Iden)]
+pub enum Artwork {
+    ImageUrl,
+}
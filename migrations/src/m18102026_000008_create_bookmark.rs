@@ -0,0 +1,63 @@
+use async_trait::async_trait;
+use sea_orm_migration::prelude::*;
+
+use crate::m22062024_000001_create_podcast_table::Podcast;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Bookmark::Table)
+                        .if_not_exists()
+                            .col(ColumnDef::new(Bookmark::Id).integer().not_null().auto_increment().primary_key())
+                            .col(ColumnDef::new(Bookmark::PodcastId).integer().not_null())
+                            .col(ColumnDef::new(Bookmark::EpLink).string().not_null())
+                            .col(ColumnDef::new(Bookmark::EpisodeTitle).string())
+                            .col(ColumnDef::new(Bookmark::Position).double().not_null())
+                            .col(ColumnDef::new(Bookmark::EndPosition).double())
+                            .col(ColumnDef::new(Bookmark::Note).text().not_null().default(""))
+                            .col(ColumnDef::new(Bookmark::CreatedAt).big_integer().not_null())
+                            .foreign_key(
+                                ForeignKey::create()
+                                    .name("fk-bookmark-podcast-id")
+                                    .from(Bookmark::Table, Bookmark::PodcastId)
+                                    .to(Podcast::Table, Podcast::Id)
+                                    .on_delete(ForeignKeyAction::Cascade)
+                            )
+                            .to_owned()
+            ).await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-bookmark-ep-link")
+                    .table(Bookmark::Table)
+                    .col(Bookmark::EpLink)
+                    .to_owned()
+            ).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Bookmark::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+pub enum Bookmark {
+    Table,
+    Id,
+    PodcastId,
+    EpLink,
+    EpisodeTitle,
+    Position,
+    EndPosition,
+    Note,
+    CreatedAt
+}
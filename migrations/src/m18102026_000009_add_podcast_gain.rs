@@ -0,0 +1,36 @@
+use async_trait::async_trait;
+use sea_orm_migration::prelude::*;
+
+use crate::m22062024_000001_create_podcast_table::Podcast;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Podcast::Table)
+                    .add_column(ColumnDef::new(Gain::GainDb).integer().not_null().default(0))
+                    .to_owned()
+            ).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Podcast::Table)
+                    .drop_column(Gain::GainDb)
+                    .to_owned()
+            ).await
+    }
+}
+
+/// Volume offset of a podcast in dB, for shows mastered louder or quieter than the rest
+#[derive(Iden)]
+pub enum Gain {
+    GainDb,
+}
@@ -0,0 +1,36 @@
+use async_trait::async_trait;
+use sea_orm_migration::prelude::*;
+
+use crate::m18102026_000006_create_listening_session::ListeningSession;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ListeningSession::Table)
+                    .add_column(ColumnDef::new(Trimmed::TrimmedSeconds).double().not_null().default(0.0))
+                    .to_owned()
+            ).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ListeningSession::Table)
+                    .drop_column(Trimmed::TrimmedSeconds)
+                    .to_owned()
+            ).await
+    }
+}
+
+/// Silence skipped during a listening session, in seconds of the episode
+#[derive(Iden)]
+pub enum Trimmed {
+    TrimmedSeconds,
+}
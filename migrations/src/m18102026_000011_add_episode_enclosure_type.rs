@@ -0,0 +1,36 @@
+use async_trait::async_trait;
+use sea_orm_migration::prelude::*;
+
+use crate::m22062024_000001_create_episode_table::Episode;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Episode::Table)
+                    .add_column(ColumnDef::new(Enclosure::EnclosureType).string())
+                    .to_owned()
+            ).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Episode::Table)
+                    .drop_column(Enclosure::EnclosureType)
+                    .to_owned()
+            ).await
+    }
+}
+
+/// MIME type the feed gives for the episode's enclosure
+#[derive(Iden)]
+pub enum Enclosure {
+    EnclosureType,
+}
@@ -0,0 +1,71 @@
+use async_trait::async_trait;
+use sea_orm_migration::prelude::*;
+
+use crate::m22062024_000001_create_episode_table::Episode;
+use crate::m22062024_000001_create_podcast_table::Podcast;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Episode::Table)
+                    .add_column(ColumnDef::new(Versions::Enclosures).text())
+                    .to_owned()
+            ).await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Podcast::Table)
+                    .add_column(ColumnDef::new(Versions::PreferredQuality).string())
+                    .to_owned()
+            ).await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Podcast::Table)
+                    .add_column(ColumnDef::new(Versions::PreferredFormat).string())
+                    .to_owned()
+            ).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Podcast::Table)
+                    .drop_column(Versions::PreferredFormat)
+                    .to_owned()
+            ).await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Podcast::Table)
+                    .drop_column(Versions::PreferredQuality)
+                    .to_owned()
+            ).await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Episode::Table)
+                    .drop_column(Versions::Enclosures)
+                    .to_owned()
+            ).await
+    }
+}
+
+/// The versions an episode comes in, as JSON, and which one a podcast's listener prefers
+#[derive(Iden)]
+pub enum Versions {
+    Enclosures,
+    PreferredQuality,
+    PreferredFormat,
+}
@@ -0,0 +1,91 @@
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use log::{info, warn};
+use sha1::{Digest, Sha1};
+use tokio_util::sync::CancellationToken;
+use crate::error::{DownloadError, RustcastError, RustcastResult};
+use crate::http::HttpClient;
+
+/// Largest side of a thumbnail; enough for the detail pane, which shows them biggest
+const THUMBNAIL_SIZE: u32 = 160;
+/// Thumbnails older than this are fetched again in case the artwork changed behind its URL
+const MAX_AGE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+fn url_hash(url: &str) -> String {
+    Sha1::digest(url.as_bytes()).iter().take(10).map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Where the thumbnail of the artwork at `url` is cached. Artwork that moves to a new URL gets
+/// a new file, so a changed URL never shows the old image.
+fn thumbnail_file(artwork_dir: &Path, url: &str) -> PathBuf {
+    artwork_dir.join(format!("{}.png", url_hash(url)))
+}
+
+/// Name egui knows the thumbnail by once its bytes are handed over
+pub fn thumbnail_uri(url: &str) -> String {
+    format!("bytes://artwork/{}.png", url_hash(url))
+}
+
+/// PNG thumbnail of the artwork at `url`, from the cache while it's fresh. A stale thumbnail
+/// is still better than none when the artwork can't be fetched again.
+pub async fn thumbnail(http: &HttpClient, cancel: &CancellationToken, artwork_dir: &Path, url: &str) -> RustcastResult<Vec<u8>> {
+    let file = thumbnail_file(artwork_dir, url);
+    let cached = tokio::fs::read(&file).await.ok();
+    let fresh = tokio::fs::metadata(&file).await
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| modified.elapsed().ok())
+        .is_some_and(|age| age < MAX_AGE);
+
+    if let (Some(bytes), true) = (&cached, fresh) {
+        return Ok(bytes.clone());
+    }
+    match fetch_thumbnail(http, cancel, artwork_dir, url, &file).await {
+        Ok(bytes) => Ok(bytes),
+        Err(e) => match cached {
+            Some(bytes) => {
+                warn!("Using the cached artwork of {}, it could not be fetched again: {}", url, e);
+                Ok(bytes)
+            }
+            None => Err(e),
+        },
+    }
+}
+
+async fn fetch_thumbnail(http: &HttpClient, cancel: &CancellationToken, artwork_dir: &Path, url: &str, file: &Path) -> RustcastResult<Vec<u8>> {
+    tokio::fs::create_dir_all(artwork_dir).await.map_err(artwork_io)?;
+    let original = file.with_extension("download");
+    http.download(url, None, cancel, &original, |_, _| {}).await?;
+
+    // Decoding and scaling are too slow for the async workers
+    let decoded = original.clone();
+    let thumbnail = tokio::task::spawn_blocking(move || make_thumbnail(&decoded)).await
+        .map_err(|e| RustcastError::Download(DownloadError::InvalidImage(e.to_string())));
+    let _ = tokio::fs::remove_file(&original).await;
+
+    let thumbnail = thumbnail??;
+    tokio::fs::write(file, &thumbnail).await.map_err(artwork_io)?;
+    info!("Cached artwork of {} in {}", url, file.display());
+    Ok(thumbnail)
+}
+
+fn make_thumbnail(original: &Path) -> RustcastResult<Vec<u8>> {
+    let invalid = |e: image::ImageError| RustcastError::Download(DownloadError::InvalidImage(e.to_string()));
+    let image = image::io::Reader::open(original)
+        .map_err(artwork_io)?
+        .with_guessed_format()
+        .map_err(artwork_io)?
+        .decode()
+        .map_err(invalid)?;
+
+    let mut png = Cursor::new(Vec::new());
+    image.thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE)
+        .write_to(&mut png, image::ImageOutputFormat::Png)
+        .map_err(invalid)?;
+    Ok(png.into_inner())
+}
+
+fn artwork_io(err: std::io::Error) -> RustcastError {
+    RustcastError::Download(DownloadError::Io(err.to_string()))
+}
@@ -0,0 +1,710 @@
+//! Plays streams by decoding them with symphonia and sending them to the default output
+//! device with cpal, through a `GainStage` in between
+
+use std::collections::VecDeque;
+use std::io::{Read, Seek, SeekFrom};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use log::{debug, info, warn};
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::{Decoder, DecoderOptions, CODEC_TYPE_NULL};
+use symphonia::core::errors::Error as DecodeError;
+use symphonia::core::formats::{FormatOptions, FormatReader, SeekMode, SeekTo};
+use symphonia::core::io::{MediaSource, MediaSourceStream};
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+use symphonia::core::units::Time;
+use crate::error::PlayerError;
+use crate::loudness::GainStage;
+use crate::player::PlayerBackend;
+
+/// Decoded audio kept queued ahead of the output device
+const QUEUE_SECONDS: f64 = 0.25;
+/// How long the decoding thread waits for room in the queue before it looks for commands again
+const WAIT_INTERVAL: Duration = Duration::from_millis(20);
+/// A stream that sends nothing for this long counts as failed
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Asked of the decoding thread. Opens and seeks carry the epoch they started.
+enum Command {
+    Open(String, u64),
+    Seek(f64, u64),
+    Volume(f32),
+    Loudness(f64, Option<f64>),
+}
+
+/// What the backend, the decoding thread and the output device share
+#[derive(Default)]
+struct Playback {
+    /// Interleaved samples in the output's rate and channel count, waiting to be played
+    queue: VecDeque<f32>,
+    playing: bool,
+    /// Seconds into the episode of the next sample to be played
+    position: f64,
+    duration: f64,
+    /// Opened, and nothing decoded yet
+    loading: bool,
+    /// Decoded to the end, so playback ends once the queue runs dry
+    decoded_all: bool,
+    /// The queue ran dry while playing
+    starved: bool,
+    error: Option<PlayerError>,
+    /// Counts opens and seeks, so audio decoded before the latest one is dropped
+    epoch: u64,
+}
+
+#[derive(Default)]
+struct Shared {
+    playback: Mutex<Playback>,
+    /// Signalled when the output takes samples off the queue
+    drained: Condvar,
+}
+
+impl Shared {
+    /// Reports a failure of what the epoch opened, unless something else has been opened since
+    fn fail(&self, epoch: u64, error: PlayerError) {
+        let mut playback = self.playback.lock().unwrap();
+        if playback.epoch == epoch {
+            warn!("{}", error);
+            playback.loading = false;
+            playback.error = Some(error);
+        }
+    }
+
+    /// Plays what's queued into `data`, and silence where there's nothing to play
+    fn fill<T: cpal::SizedSample + cpal::FromSample<f32>>(&self, data: &mut [T], spec: OutputSpec) {
+        let mut playback = self.playback.lock().unwrap();
+        let mut played = 0;
+        if playback.playing {
+            played = data.len().min(playback.queue.len());
+            played -= played % spec.channels;
+            for (out, sample) in data.iter_mut().zip(playback.queue.drain(..played)) {
+                *out = T::from_sample(sample);
+            }
+            playback.position += (played / spec.channels) as f64 / spec.rate as f64;
+            playback.starved = played < data.len() && !playback.loading && !playback.decoded_all;
+        }
+        for out in &mut data[played..] {
+            *out = T::EQUILIBRIUM;
+        }
+        drop(playback);
+        self.drained.notify_one();
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct OutputSpec {
+    rate: u32,
+    channels: usize,
+}
+
+/// The default output device, playing from the shared queue for as long as it's kept
+struct Output {
+    _stream: cpal::Stream,
+    spec: OutputSpec,
+}
+
+impl Output {
+    fn open(shared: &Arc<Shared>) -> Result<Self, String> {
+        let device = cpal::default_host().default_output_device()
+            .ok_or_else(|| "there is no audio output device".to_string())?;
+        let config = device.default_output_config().map_err(|e| e.to_string())?;
+        let spec = OutputSpec {
+            rate: config.sample_rate().0,
+            channels: config.channels().max(1) as usize,
+        };
+        let stream = match config.sample_format() {
+            cpal::SampleFormat::F32 => build_stream::<f32>(&device, &config.config(), spec, shared.clone()),
+            cpal::SampleFormat::I16 => build_stream::<i16>(&device, &config.config(), spec, shared.clone()),
+            cpal::SampleFormat::U16 => build_stream::<u16>(&device, &config.config(), spec, shared.clone()),
+            format => return Err(format!("the output device takes {:?} samples", format)),
+        }.map_err(|e| e.to_string())?;
+        stream.play().map_err(|e| e.to_string())?;
+
+        info!("Playing through {} at {} Hz", device.name().unwrap_or_default(), spec.rate);
+        Ok(Output { _stream: stream, spec })
+    }
+}
+
+fn build_stream<T: cpal::SizedSample + cpal::FromSample<f32>>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    spec: OutputSpec,
+    shared: Arc<Shared>,
+) -> Result<cpal::Stream, cpal::BuildStreamError> {
+    device.build_output_stream(
+        config,
+        move |data: &mut [T], _| shared.fill(data, spec),
+        |e| warn!("Audio output failed: {}", e),
+        None,
+    )
+}
+
+/// Turns decoded audio into the output's channel count and, by linear interpolation, its
+/// sample rate. Keeps the last frame of a packet to interpolate into the next one.
+struct Converter {
+    in_channels: usize,
+    out_channels: usize,
+    /// Input frames per output frame
+    step: f64,
+    /// Where the next output frame falls, in input frames after `last`
+    phase: f64,
+    last: Vec<f32>,
+}
+
+impl Converter {
+    fn new(in_rate: u32, in_channels: usize, output: OutputSpec) -> Self {
+        Converter {
+            in_channels: in_channels.max(1),
+            out_channels: output.channels,
+            step: in_rate as f64 / output.rate as f64,
+            phase: 0.0,
+            last: vec![0.0; output.channels],
+        }
+    }
+
+    /// Starts over, after a seek
+    fn reset(&mut self) {
+        self.phase = 0.0;
+        self.last.iter_mut().for_each(|sample| *sample = 0.0);
+    }
+
+    /// One input frame in the output's channels: mono goes to every channel, and a single
+    /// output channel gets the average
+    fn remix(&self, frame: &[f32], out: &mut Vec<f32>) {
+        if self.in_channels == self.out_channels {
+            out.extend_from_slice(frame);
+        } else if self.in_channels == 1 {
+            out.extend(std::iter::repeat_n(frame[0], self.out_channels));
+        } else if self.out_channels == 1 {
+            out.push(frame.iter().sum::<f32>() / frame.len() as f32);
+        } else {
+            out.extend((0..self.out_channels).map(|channel| frame.get(channel).copied().unwrap_or(0.0)));
+        }
+    }
+
+    /// Converts interleaved `input`, adding to `output`
+    fn convert(&mut self, input: &[f32], output: &mut Vec<f32>) {
+        if self.step == 1.0 {
+            for frame in input.chunks_exact(self.in_channels) {
+                self.remix(frame, output);
+            }
+            return;
+        }
+
+        let mut frames = Vec::with_capacity(input.len() / self.in_channels * self.out_channels + self.out_channels);
+        frames.extend_from_slice(&self.last);
+        for frame in input.chunks_exact(self.in_channels) {
+            self.remix(frame, &mut frames);
+        }
+        let count = frames.len() / self.out_channels;
+
+        while self.phase + 1.0 < count as f64 {
+            let index = self.phase as usize;
+            let fraction = (self.phase - index as f64) as f32;
+            let before = &frames[index * self.out_channels..(index + 1) * self.out_channels];
+            let after = &frames[(index + 1) * self.out_channels..(index + 2) * self.out_channels];
+            output.extend(before.iter().zip(after).map(|(before, after)| before + (after - before) * fraction));
+            self.phase += self.step;
+        }
+        self.phase -= (count - 1) as f64;
+        self.last.copy_from_slice(&frames[(count - 1) * self.out_channels..]);
+    }
+}
+
+/// Media behind a URL, read as it arrives and sought with range requests
+struct HttpSource {
+    agent: ureq::Agent,
+    url: String,
+    reader: Box<dyn Read + Send + Sync>,
+    position: u64,
+    length: Option<u64>,
+}
+
+impl HttpSource {
+    fn open(url: &str) -> Result<Self, String> {
+        let agent = ureq::AgentBuilder::new().timeout_read(READ_TIMEOUT).build();
+        let response = agent.get(url).call().map_err(|e| e.to_string())?;
+        let length = response.header("Content-Length").and_then(|length| length.parse().ok());
+        Ok(HttpSource {
+            agent,
+            url: url.to_string(),
+            reader: response.into_reader(),
+            position: 0,
+            length,
+        })
+    }
+}
+
+impl Read for HttpSource {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.reader.read(buf)?;
+        self.position += read as u64;
+        Ok(read)
+    }
+}
+
+impl Seek for HttpSource {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let target = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+            SeekFrom::End(offset) => self.length.and_then(|length| length.checked_add_signed(offset)),
+        }.ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidInput, "seek out of range"))?;
+        if target == self.position {
+            return Ok(target);
+        }
+
+        let response = self.agent.get(&self.url)
+            .set("Range", &format!("bytes={}-", target))
+            .call()
+            .map_err(|e| std::io::Error::other(e.to_string()))?;
+        if response.status() != 206 && target != 0 {
+            return Err(std::io::Error::new(std::io::ErrorKind::Unsupported, "the server can't seek in the stream"));
+        }
+        self.reader = response.into_reader();
+        self.position = target;
+        Ok(target)
+    }
+}
+
+impl MediaSource for HttpSource {
+    fn is_seekable(&self) -> bool {
+        self.length.is_some()
+    }
+
+    fn byte_len(&self) -> Option<u64> {
+        self.length
+    }
+}
+
+/// An opened stream being decoded
+struct Media {
+    format: Box<dyn FormatReader>,
+    decoder: Box<dyn Decoder>,
+    track_id: u32,
+    epoch: u64,
+    output: OutputSpec,
+    converter: Option<(u32, usize, Converter)>,
+    /// Packets that end before this timestamp are dropped, after an accurate seek
+    skip_until: u64,
+    /// Nothing has been read since opening
+    at_start: bool,
+    ended: bool,
+    /// Converted samples that didn't fit in the queue yet
+    pending: Vec<f32>,
+}
+
+impl Media {
+    /// Opens and probes `url`, returning the stream and its duration, 0 if unknown
+    fn open(url: &str, output: OutputSpec, epoch: u64) -> Result<(Self, f64), PlayerError> {
+        let source = HttpSource::open(url).map_err(PlayerError::OpenFailed)?;
+        let probed = symphonia::default::get_probe()
+            .format(
+                &Hint::new(),
+                MediaSourceStream::new(Box::new(source), Default::default()),
+                &FormatOptions { enable_gapless: true, ..Default::default() },
+                &MetadataOptions::default(),
+            )
+            .map_err(|e| PlayerError::UnsupportedFormat(e.to_string()))?;
+        let format = probed.format;
+
+        let track = format.tracks().iter()
+            .find(|track| track.codec_params.codec != CODEC_TYPE_NULL)
+            .ok_or_else(|| PlayerError::UnsupportedFormat("no audio track".to_string()))?;
+        let decoder = symphonia::default::get_codecs()
+            .make(&track.codec_params, &DecoderOptions::default())
+            .map_err(|e| PlayerError::UnsupportedFormat(e.to_string()))?;
+        let duration = match (track.codec_params.time_base, track.codec_params.n_frames) {
+            (Some(time_base), Some(frames)) => {
+                let time = time_base.calc_time(frames);
+                time.seconds as f64 + time.frac
+            }
+            _ => 0.0,
+        };
+        let track_id = track.id;
+
+        Ok((Media {
+            format,
+            decoder,
+            track_id,
+            epoch,
+            output,
+            converter: None,
+            skip_until: 0,
+            at_start: true,
+            ended: false,
+            pending: Vec::new(),
+        }, duration))
+    }
+
+    fn seek(&mut self, seconds: f64, epoch: u64) -> Result<(), PlayerError> {
+        self.epoch = epoch;
+        self.pending.clear();
+        self.ended = false;
+        if let Some((_, _, converter)) = &mut self.converter {
+            converter.reset();
+        }
+        if seconds <= 0.0 && self.at_start {
+            return Ok(());
+        }
+
+        let seeked = self.format
+            .seek(SeekMode::Accurate, SeekTo::Time { time: Time::from(seconds), track_id: Some(self.track_id) })
+            .map_err(|e| PlayerError::SeekFailed(e.to_string()))?;
+        self.skip_until = seeked.required_ts;
+        self.decoder.reset();
+        Ok(())
+    }
+
+    /// Queues what's waiting, or decodes the next packet
+    fn step(&mut self, shared: &Shared, gain: &mut GainStage) -> Result<(), PlayerError> {
+        if !self.pending.is_empty() {
+            self.flush(shared);
+            return Ok(());
+        }
+
+        self.at_start = false;
+        let packet = match self.format.next_packet() {
+            Ok(packet) => packet,
+            Err(DecodeError::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+                self.ended = true;
+                let mut playback = shared.playback.lock().unwrap();
+                if playback.epoch == self.epoch {
+                    playback.decoded_all = true;
+                    playback.loading = false;
+                }
+                return Ok(());
+            }
+            Err(DecodeError::ResetRequired) => {
+                self.decoder.reset();
+                return Ok(());
+            }
+            Err(e) => return Err(PlayerError::PlaybackFailed(e.to_string())),
+        };
+        if packet.track_id() != self.track_id || packet.ts() + packet.dur() < self.skip_until {
+            return Ok(());
+        }
+        let decoded = match self.decoder.decode(&packet) {
+            Ok(decoded) => decoded,
+            // A damaged packet is a moment of silence, not a failed stream
+            Err(DecodeError::DecodeError(e)) => {
+                debug!("Skipping a packet that didn't decode: {}", e);
+                return Ok(());
+            }
+            Err(e) => return Err(PlayerError::PlaybackFailed(e.to_string())),
+        };
+        if decoded.frames() == 0 {
+            return Ok(());
+        }
+
+        let spec = *decoded.spec();
+        let mut buffer = SampleBuffer::<f32>::new(decoded.capacity() as u64, spec);
+        buffer.copy_interleaved_ref(decoded);
+        let channels = spec.channels.count();
+        let converter = match &mut self.converter {
+            Some((rate, count, converter)) if *rate == spec.rate && *count == channels => converter,
+            converter => &mut converter.insert((spec.rate, channels, Converter::new(spec.rate, channels, self.output))).2,
+        };
+        converter.convert(buffer.samples(), &mut self.pending);
+        gain.process(&mut self.pending);
+        self.flush(shared);
+        Ok(())
+    }
+
+    /// Moves pending samples to the queue, waiting a moment if it's full
+    fn flush(&mut self, shared: &Shared) {
+        let mut playback = shared.playback.lock().unwrap();
+        if playback.epoch != self.epoch {
+            self.pending.clear();
+            return;
+        }
+        let capacity = (QUEUE_SECONDS * self.output.rate as f64) as usize * self.output.channels;
+        if !playback.queue.is_empty() && playback.queue.len() + self.pending.len() > capacity {
+            drop(shared.drained.wait_timeout(playback, WAIT_INTERVAL));
+            return;
+        }
+        playback.queue.extend(self.pending.drain(..));
+        playback.loading = false;
+    }
+}
+
+/// The decoding thread: opens what it's asked to, decodes it into the queue and keeps the
+/// output device open once it has been
+fn run(shared: Arc<Shared>, commands: Receiver<Command>) {
+    let mut output: Option<Output> = None;
+    let mut gain: Option<GainStage> = None;
+    let mut volume = 1.0;
+    let mut loudness = (0.0, None);
+    let mut media: Option<Media> = None;
+
+    loop {
+        let command = match &media {
+            Some(media) if !media.ended => match commands.try_recv() {
+                Ok(command) => Some(command),
+                Err(TryRecvError::Empty) => None,
+                Err(TryRecvError::Disconnected) => return,
+            },
+            _ => match commands.recv() {
+                Ok(command) => Some(command),
+                Err(_) => return,
+            },
+        };
+
+        match command {
+            Some(Command::Open(url, epoch)) => {
+                media = None;
+                if output.is_none() {
+                    match Output::open(&shared) {
+                        Ok(opened) => {
+                            let mut stage = GainStage::new(opened.spec.rate, opened.spec.channels);
+                            stage.set_volume(volume);
+                            stage.set_loudness(loudness.0, loudness.1);
+                            gain = Some(stage);
+                            output = Some(opened);
+                        }
+                        Err(e) => {
+                            shared.fail(epoch, PlayerError::OpenFailed(e));
+                            continue;
+                        }
+                    }
+                }
+                let (Some(output), Some(gain)) = (&output, &mut gain) else {
+                    continue;
+                };
+                match Media::open(&url, output.spec, epoch) {
+                    Ok((opened, duration)) => {
+                        shared.playback.lock().unwrap().duration = duration;
+                        gain.reset();
+                        media = Some(opened);
+                    }
+                    Err(e) => shared.fail(epoch, e),
+                }
+            }
+            Some(Command::Seek(seconds, epoch)) => {
+                if let Some(opened) = &mut media {
+                    if let Err(e) = opened.seek(seconds, epoch) {
+                        shared.fail(epoch, e);
+                    }
+                }
+            }
+            Some(Command::Volume(changed)) => {
+                volume = changed;
+                if let Some(gain) = &mut gain {
+                    gain.set_volume(volume);
+                }
+            }
+            Some(Command::Loudness(gain_db, target)) => {
+                loudness = (gain_db, target);
+                if let Some(gain) = &mut gain {
+                    gain.set_loudness(gain_db, target);
+                }
+            }
+            None => {}
+        }
+
+        if let (Some(opened), Some(gain)) = (&mut media, &mut gain) {
+            if opened.ended {
+                continue;
+            }
+            if let Err(e) = opened.step(&shared, gain) {
+                shared.fail(opened.epoch, e);
+                media = None;
+            }
+        }
+    }
+}
+
+/// Plays through symphonia and cpal on a thread of its own, with the volume, the podcast's
+/// offset and loudness normalization applied on the way to the output device
+pub struct SymphoniaBackend {
+    shared: Arc<Shared>,
+    commands: Sender<Command>,
+}
+
+impl SymphoniaBackend {
+    pub fn new() -> Self {
+        let shared = Arc::new(Shared::default());
+        let (commands, receiver) = mpsc::channel();
+        let thread_shared = shared.clone();
+        std::thread::spawn(move || run(thread_shared, receiver));
+        SymphoniaBackend { shared, commands }
+    }
+
+    /// Drops what's queued and starts a new epoch, for an open or a seek
+    fn restart(&self, update: impl FnOnce(&mut Playback)) -> u64 {
+        let mut playback = self.shared.playback.lock().unwrap();
+        playback.epoch += 1;
+        playback.queue.clear();
+        playback.starved = false;
+        playback.decoded_all = false;
+        update(&mut playback);
+        playback.epoch
+    }
+
+    fn send(&self, command: Command) {
+        if self.commands.send(command).is_err() {
+            warn!("The audio thread has stopped");
+        }
+    }
+}
+
+impl Default for SymphoniaBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PlayerBackend for SymphoniaBackend {
+    fn open(&mut self, url: &str) {
+        let epoch = self.restart(|playback| {
+            playback.position = 0.0;
+            playback.duration = 0.0;
+            playback.loading = true;
+            playback.error = None;
+        });
+        self.send(Command::Open(url.to_string(), epoch));
+    }
+
+    fn play(&mut self) {
+        self.shared.playback.lock().unwrap().playing = true;
+    }
+
+    fn pause(&mut self) {
+        self.shared.playback.lock().unwrap().playing = false;
+    }
+
+    fn seek(&mut self, seconds: f64) {
+        let seconds = seconds.max(0.0);
+        let epoch = self.restart(|playback| playback.position = seconds);
+        self.send(Command::Seek(seconds, epoch));
+    }
+
+    fn current_position(&self) -> f64 {
+        self.shared.playback.lock().unwrap().position
+    }
+
+    fn duration(&self) -> f64 {
+        self.shared.playback.lock().unwrap().duration
+    }
+
+    fn supports_volume(&self) -> bool {
+        true
+    }
+
+    fn set_volume(&mut self, volume: f32) {
+        self.send(Command::Volume(volume));
+    }
+
+    fn set_loudness(&mut self, podcast_gain_db: f64, target: Option<f64>) {
+        self.send(Command::Loudness(podcast_gain_db, target));
+    }
+
+    fn supports_speed(&self) -> bool {
+        false
+    }
+
+    fn set_speed(&mut self, _speed: f64) {}
+
+    fn is_finished(&self) -> bool {
+        let playback = self.shared.playback.lock().unwrap();
+        playback.decoded_all && playback.queue.is_empty()
+    }
+
+    fn is_loading(&self) -> bool {
+        let playback = self.shared.playback.lock().unwrap();
+        playback.loading && playback.error.is_none()
+    }
+
+    fn is_buffering(&self) -> bool {
+        let playback = self.shared.playback.lock().unwrap();
+        playback.playing && playback.starved
+    }
+
+    fn take_error(&mut self) -> Option<PlayerError> {
+        self.shared.playback.lock().unwrap().error.take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output(rate: u32, channels: usize) -> OutputSpec {
+        OutputSpec { rate, channels }
+    }
+
+    #[test]
+    fn remixes_channels() {
+        let mut converted = Vec::new();
+        Converter::new(48_000, 2, output(48_000, 1)).convert(&[0.2, 0.4, -0.5, 0.5], &mut converted);
+        assert_eq!(converted, [0.3, 0.0]);
+
+        let mut converted = Vec::new();
+        Converter::new(48_000, 1, output(48_000, 2)).convert(&[0.2, -0.5], &mut converted);
+        assert_eq!(converted, [0.2, 0.2, -0.5, -0.5]);
+
+        let mut converted = Vec::new();
+        Converter::new(48_000, 2, output(48_000, 4)).convert(&[0.2, 0.4], &mut converted);
+        assert_eq!(converted, [0.2, 0.4, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn resamples_between_packets() {
+        // Upsampling twice interpolates halfway between frames, starting from silence
+        let mut converter = Converter::new(24_000, 1, output(48_000, 1));
+        let mut converted = Vec::new();
+        converter.convert(&[1.0, 2.0], &mut converted);
+        converter.convert(&[3.0, 4.0], &mut converted);
+        assert_eq!(converted, [0.0, 0.5, 1.0, 1.5, 2.0, 2.5, 3.0, 3.5]);
+    }
+
+    #[test]
+    fn resampling_keeps_the_duration() {
+        let mut converter = Converter::new(44_100, 2, output(48_000, 2));
+        let mut converted = Vec::new();
+        for _ in 0..100 {
+            converter.convert(&[0.1; 1152 * 2], &mut converted);
+        }
+        let expected = 1152.0 * 100.0 * 48_000.0 / 44_100.0;
+        assert!((converted.len() as f64 / 2.0 - expected).abs() <= 1.0, "{} frames", converted.len() / 2);
+    }
+
+    #[test]
+    fn plays_the_queue_and_keeps_time() {
+        let shared = Shared::default();
+        let spec = output(4, 2);
+        shared.playback.lock().unwrap().queue.extend([0.5, -0.5, 0.25, -0.25, 1.0, 1.0]);
+
+        // Nothing plays while paused
+        let mut data = [1.0f32; 4];
+        shared.fill(&mut data, spec);
+        assert_eq!(data, [0.0; 4]);
+        assert_eq!(shared.playback.lock().unwrap().position, 0.0);
+
+        shared.playback.lock().unwrap().playing = true;
+        shared.fill(&mut data, spec);
+        assert_eq!(data, [0.5, -0.5, 0.25, -0.25]);
+        assert_eq!(shared.playback.lock().unwrap().position, 0.5);
+
+        // Running dry before the end is buffering
+        shared.fill(&mut data, spec);
+        assert_eq!(data, [1.0, 1.0, 0.0, 0.0]);
+        let playback = shared.playback.lock().unwrap();
+        assert_eq!(playback.position, 0.75);
+        assert!(playback.starved);
+    }
+
+    #[test]
+    fn failures_of_an_earlier_open_are_dropped() {
+        let shared = Shared::default();
+        shared.playback.lock().unwrap().epoch = 2;
+
+        shared.fail(1, PlayerError::OpenFailed("gone".to_string()));
+        assert!(shared.playback.lock().unwrap().error.is_none());
+        shared.fail(2, PlayerError::OpenFailed("gone".to_string()));
+        assert!(shared.playback.lock().unwrap().error.is_some());
+    }
+}
@@ -0,0 +1,135 @@
+use std::collections::HashMap;
+use sea_orm::ActiveValue;
+use crate::entity::bookmark;
+use crate::error::{RustcastError, RustcastResult, UiError};
+use crate::utils;
+
+/// A bookmark as the user wrote it, before it's stored
+#[derive(Debug, PartialEq, Clone)]
+pub struct NewBookmark {
+    pub podcast_id: i32,
+    pub ep_link: String,
+    pub episode_title: Option<String>,
+    pub position: f64,
+    /// Where the bookmarked part ends, for a range rather than a moment
+    pub end_position: Option<f64>,
+    pub note: String,
+}
+
+impl NewBookmark {
+    pub fn into_active_model(self) -> bookmark::ActiveModel {
+        bookmark::ActiveModel {
+            podcast_id: ActiveValue::Set(self.podcast_id),
+            ep_link: ActiveValue::Set(self.ep_link),
+            episode_title: ActiveValue::Set(self.episode_title),
+            position: ActiveValue::Set(self.position),
+            end_position: ActiveValue::Set(self.end_position),
+            note: ActiveValue::Set(self.note.trim().to_string()),
+            created_at: ActiveValue::Set(utils::unix_timestamp()),
+            ..Default::default()
+        }
+    }
+}
+
+pub fn validate_range(position: f64, end_position: Option<f64>) -> RustcastResult<()> {
+    let invalid = |msg: &str| Err(RustcastError::Ui(UiError::InvalidInput(msg.to_string())));
+    // Written so that NaN fails as well
+    if !(position.is_finite() && position >= 0.0) {
+        return invalid("A bookmark has to be at a position in the episode.");
+    }
+    if end_position.is_some_and(|end| !(end.is_finite() && end > position)) {
+        return invalid("A bookmark has to end after it starts.");
+    }
+    Ok(())
+}
+
+/// `42:10` or `42:10–43:05`
+pub fn format_range(position: f64, end_position: Option<f64>) -> String {
+    match end_position {
+        Some(end) => format!("{}–{}", utils::format_position(position), utils::format_position(end)),
+        None => utils::format_position(position),
+    }
+}
+
+/// Bookmarks as Markdown, a section per podcast and episode. Timestamps link into the
+/// episode with a media fragment, which players that support it start playing from.
+pub fn bookmarks_markdown(bookmarks: &[bookmark::Model], titles: &HashMap<i32, String>) -> String {
+    let mut markdown = String::from("# Bookmarks\n");
+    let mut podcast = None;
+    let mut episode = None;
+
+    for bookmark in bookmarks {
+        if podcast != Some(bookmark.podcast_id) {
+            podcast = Some(bookmark.podcast_id);
+            episode = None;
+            let title = titles.get(&bookmark.podcast_id).map_or("Unknown podcast", String::as_str);
+            markdown.push_str(&format!("\n## {}\n", escape(title)));
+        }
+        if episode != Some(&bookmark.ep_link) {
+            episode = Some(&bookmark.ep_link);
+            let title = bookmark.episode_title.as_deref().unwrap_or("Unknown episode");
+            markdown.push_str(&format!("\n### [{}](<{}>)\n\n", escape(title), bookmark.ep_link));
+        }
+
+        markdown.push_str(&format!(
+            "- [{}](<{}#t={}>)",
+            format_range(bookmark.position, bookmark.end_position),
+            bookmark.ep_link,
+            media_fragment(bookmark.position, bookmark.end_position),
+        ));
+        let mut lines = bookmark.note.lines();
+        if let Some(first) = lines.next() {
+            markdown.push_str(&format!(" {}", first));
+        }
+        for line in lines {
+            markdown.push_str(&format!("\n  {}", line));
+        }
+        markdown.push('\n');
+    }
+    markdown
+}
+
+fn media_fragment(position: f64, end_position: Option<f64>) -> String {
+    match end_position {
+        Some(end) => format!("{},{}", position.floor(), end.ceil()),
+        None => format!("{}", position.floor()),
+    }
+}
+
+/// Keeps titles from being read as Markdown syntax
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '[' | ']' | '*' | '_' | '`' | '#' | '<' | '>') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validates_ranges() {
+        assert!(validate_range(0.0, None).is_ok());
+        assert!(validate_range(42.5, Some(60.0)).is_ok());
+
+        let invalid = |position, end| matches!(validate_range(position, end), Err(RustcastError::Ui(UiError::InvalidInput(_))));
+        assert!(invalid(-1.0, None));
+        assert!(invalid(f64::NAN, None));
+        assert!(invalid(f64::INFINITY, None));
+        assert!(invalid(5.0, Some(5.0)));
+        assert!(invalid(5.0, Some(4.0)));
+        assert!(invalid(5.0, Some(f64::NAN)));
+        assert!(invalid(5.0, Some(f64::INFINITY)));
+    }
+
+    #[test]
+    fn formats_ranges() {
+        assert_eq!(format_range(2530.0, None), "42:10");
+        assert_eq!(format_range(2530.0, Some(3725.0)), "42:10–1:02:05");
+    }
+}
@@ -0,0 +1,308 @@
+use std::path::{Path, PathBuf};
+use tokio_util::sync::CancellationToken;
+use crate::bookmarks::format_range;
+use crate::decode;
+use crate::error::{ExportError, RustcastError, RustcastResult};
+use crate::loudness::{self, LoudnessMeter};
+
+/// Clips are for quotes, and are kept in memory while they're cut
+pub const MAX_CLIP_SECONDS: f64 = 600.0;
+
+/// Part of an episode to save as its own file
+#[derive(Debug, PartialEq, Clone)]
+pub struct ClipRequest {
+    pub podcast_id: i32,
+    pub ep_link: String,
+    pub podcast_title: Option<String>,
+    pub episode_title: Option<String>,
+    pub start: f64,
+    pub end: f64,
+    /// The podcast's volume offset
+    pub gain_db: f64,
+    /// Loudness to normalize the clip to, in LUFS
+    pub loudness_target: Option<f64>,
+    pub file: PathBuf,
+}
+
+impl ClipRequest {
+    /// `Episode title (12:00–12:30)`, the clip's title in its tags
+    fn title(&self) -> String {
+        format!(
+            "{} ({})",
+            self.episode_title.as_deref().unwrap_or("Unknown episode"),
+            format_range(self.start, Some(self.end)),
+        )
+    }
+}
+
+pub fn validate_range(start: f64, end: f64) -> RustcastResult<()> {
+    let invalid = |msg: &str| Err(RustcastError::Export(ExportError::InvalidRange(msg.to_string())));
+    // Written so that NaN fails as well
+    if !(start >= 0.0 && end > start) {
+        return invalid("A clip has to end after it starts.");
+    }
+    if end - start > MAX_CLIP_SECONDS {
+        return invalid("Clips can be at most 10 minutes long.");
+    }
+    Ok(())
+}
+
+/// Decodes the range of `source` and writes it to the request's file as 16-bit WAV, tagged
+/// with the podcast, episode and range. Returns the length of the clip in seconds.
+///
+/// Blocks while it decodes, so it belongs on a blocking thread.
+pub fn export_clip(source: &Path, request: &ClipRequest, cancel: &CancellationToken) -> RustcastResult<f64> {
+    validate_range(request.start, request.end)?;
+    let mut audio = decode_range(source, request.start, request.end, cancel)?;
+    if audio.samples.is_empty() {
+        return Err(RustcastError::Export(ExportError::Decode(
+            "the episode ends before the clip starts".to_string()
+        )));
+    }
+
+    let mut gain_db = request.gain_db;
+    if let Some(target) = request.loudness_target {
+        let mut meter = LoudnessMeter::new(audio.rate, audio.channels as usize);
+        let scaled: Vec<f32> = audio.samples.iter().map(|sample| *sample as f32 / 32768.0).collect();
+        meter.push(&scaled);
+        gain_db += loudness::normalization_gain_db(&meter, target);
+    }
+    loudness::apply_gain(&mut audio.samples, gain_db);
+
+    let seconds = audio.frames() as f64 / audio.rate as f64;
+    std::fs::write(&request.file, wav(&audio, request))
+        .map_err(|e| RustcastError::Export(ExportError::Io(e.to_string())))?;
+    Ok(seconds)
+}
+
+/// Interleaved samples
+struct Pcm {
+    samples: Vec<i16>,
+    rate: u32,
+    channels: u16,
+}
+
+impl Pcm {
+    fn frames(&self) -> usize {
+        self.samples.len() / self.channels.max(1) as usize
+    }
+}
+
+fn decode_range(source: &Path, start: f64, end: f64, cancel: &CancellationToken) -> RustcastResult<Pcm> {
+    let mut pcm: Option<Pcm> = None;
+    decode::decode(source, start, cancel, |time, samples, spec| {
+        if time >= end {
+            return Ok(false);
+        }
+        let pcm = pcm.get_or_insert_with(|| Pcm {
+            samples: Vec::new(),
+            rate: spec.rate,
+            channels: spec.channels,
+        });
+        if spec.rate != pcm.rate || spec.channels != pcm.channels {
+            return Err(RustcastError::Export(ExportError::Decode(
+                "the audio format changes within the clip".to_string()
+            )));
+        }
+
+        for (index, frame) in samples.chunks(pcm.channels as usize).enumerate() {
+            let time = time + index as f64 / spec.rate as f64;
+            if time >= start && time < end {
+                pcm.samples.extend_from_slice(frame);
+            }
+        }
+        Ok(true)
+    })?;
+
+    Ok(pcm.unwrap_or(Pcm { samples: Vec::new(), rate: 44_100, channels: 2 }))
+}
+
+/// RIFF WAVE with the tags twice: as an INFO list, which most tools read from WAV files, and
+/// as an ID3v2.4 chunk for the ones that only know ID3
+fn wav(audio: &Pcm, request: &ClipRequest) -> Vec<u8> {
+    let podcast = request.podcast_title.as_deref().unwrap_or("Unknown podcast");
+    let episode = request.episode_title.as_deref().unwrap_or("Unknown episode");
+    let title = request.title();
+    let comment = format!("{} of {}", format_range(request.start, Some(request.end)), request.ep_link);
+
+    let mut fmt = Vec::with_capacity(16);
+    fmt.extend_from_slice(&1u16.to_le_bytes());
+    fmt.extend_from_slice(&audio.channels.to_le_bytes());
+    fmt.extend_from_slice(&audio.rate.to_le_bytes());
+    fmt.extend_from_slice(&(audio.rate * audio.channels as u32 * 2).to_le_bytes());
+    fmt.extend_from_slice(&(audio.channels * 2).to_le_bytes());
+    fmt.extend_from_slice(&16u16.to_le_bytes());
+
+    let data: Vec<u8> = audio.samples.iter().flat_map(|sample| sample.to_le_bytes()).collect();
+
+    let mut info = b"INFO".to_vec();
+    for (id, value) in [(b"INAM", title.as_str()), (b"IPRD", episode), (b"IART", podcast), (b"ICMT", comment.as_str())] {
+        let mut text = value.as_bytes().to_vec();
+        text.push(0);
+        chunk(&mut info, id, &text);
+    }
+
+    let mut frames = Vec::new();
+    id3_frame(&mut frames, b"TIT2", &id3_text(&title));
+    id3_frame(&mut frames, b"TALB", &id3_text(episode));
+    id3_frame(&mut frames, b"TPE1", &id3_text(podcast));
+    let mut comm = vec![3];
+    comm.extend_from_slice(b"eng\0");
+    comm.extend_from_slice(comment.as_bytes());
+    id3_frame(&mut frames, b"COMM", &comm);
+    let mut id3 = b"ID3\x04\x00\x00".to_vec();
+    id3.extend_from_slice(&syncsafe(frames.len() as u32));
+    id3.extend_from_slice(&frames);
+
+    let mut wave = b"WAVE".to_vec();
+    chunk(&mut wave, b"fmt ", &fmt);
+    chunk(&mut wave, b"data", &data);
+    chunk(&mut wave, b"LIST", &info);
+    chunk(&mut wave, b"id3 ", &id3);
+
+    let mut riff = Vec::with_capacity(wave.len() + 8);
+    chunk(&mut riff, b"RIFF", &wave);
+    riff
+}
+
+/// RIFF chunks are padded to an even length
+fn chunk(out: &mut Vec<u8>, id: &[u8; 4], body: &[u8]) {
+    out.extend_from_slice(id);
+    out.extend_from_slice(&(body.len() as u32).to_le_bytes());
+    out.extend_from_slice(body);
+    if body.len() % 2 == 1 {
+        out.push(0);
+    }
+}
+
+/// UTF-8 text frame body
+fn id3_text(text: &str) -> Vec<u8> {
+    let mut body = vec![3];
+    body.extend_from_slice(text.as_bytes());
+    body
+}
+
+fn id3_frame(out: &mut Vec<u8>, id: &[u8; 4], body: &[u8]) {
+    out.extend_from_slice(id);
+    out.extend_from_slice(&syncsafe(body.len() as u32));
+    out.extend_from_slice(&[0, 0]);
+    out.extend_from_slice(body);
+}
+
+/// ID3v2 sizes keep the top bit of every byte clear
+fn syncsafe(size: u32) -> [u8; 4] {
+    [(size >> 21) as u8 & 0x7f, (size >> 14) as u8 & 0x7f, (size >> 7) as u8 & 0x7f, size as u8 & 0x7f]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two seconds of a 440 Hz tone, 8 kHz stereo
+    const TONE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/tone.wav");
+    const TONE_RATE: f64 = 8000.0;
+
+    fn request(start: f64, end: f64, file: PathBuf) -> ClipRequest {
+        ClipRequest {
+            podcast_id: 1,
+            ep_link: "https://example.com/tone.wav".to_string(),
+            podcast_title: Some("Tones".to_string()),
+            episode_title: Some("A".to_string()),
+            start,
+            end,
+            gain_db: 0.0,
+            loudness_target: None,
+            file,
+        }
+    }
+
+    fn output(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("rustcast-clip-{}-{}.wav", std::process::id(), name))
+    }
+
+    /// Frames and channels of a WAV file, as read back by the decoder
+    fn read_back(file: &Path) -> (usize, u16) {
+        let mut frames = 0;
+        let mut channels = 0;
+        decode::decode(file, 0.0, &CancellationToken::new(), |_, samples, spec| {
+            channels = spec.channels;
+            frames += samples.len() / spec.channels as usize;
+            Ok(true)
+        }).unwrap();
+        (frames, channels)
+    }
+
+    #[test]
+    fn exports_the_range() {
+        let file = output("range");
+        let seconds = export_clip(Path::new(TONE), &request(0.5, 1.25, file.clone()), &CancellationToken::new()).unwrap();
+        let (frames, channels) = read_back(&file);
+        let bytes = std::fs::read(&file).unwrap();
+        std::fs::remove_file(&file).unwrap();
+
+        assert_eq!(seconds, 0.75);
+        assert_eq!(frames, (0.75 * TONE_RATE) as usize);
+        assert_eq!(channels, 2);
+
+        // The RIFF size covers the whole file, tags included
+        assert_eq!(&bytes[..4], b"RIFF");
+        assert_eq!(u32::from_le_bytes(bytes[4..8].try_into().unwrap()) as usize, bytes.len() - 8);
+        assert!(bytes.windows(4).any(|id| id == b"INAM"));
+        assert!(bytes.windows(4).any(|id| id == b"id3 "));
+    }
+
+    #[test]
+    fn clip_is_cut_at_the_end_of_the_episode() {
+        let file = output("end");
+        let seconds = export_clip(Path::new(TONE), &request(1.5, 10.0, file.clone()), &CancellationToken::new()).unwrap();
+        let (frames, _) = read_back(&file);
+        std::fs::remove_file(&file).unwrap();
+
+        assert_eq!(seconds, 0.5);
+        assert_eq!(frames, (0.5 * TONE_RATE) as usize);
+    }
+
+    #[test]
+    fn clip_after_the_end_is_refused() {
+        let file = output("after");
+        let result = export_clip(Path::new(TONE), &request(5.0, 6.0, file.clone()), &CancellationToken::new());
+
+        assert!(matches!(result, Err(RustcastError::Export(ExportError::Decode(_)))));
+        assert!(!file.exists());
+    }
+
+    #[test]
+    fn validates_ranges() {
+        assert!(validate_range(0.0, 0.1).is_ok());
+        assert!(validate_range(30.0, 30.0 + MAX_CLIP_SECONDS).is_ok());
+
+        let invalid = |start, end| matches!(validate_range(start, end), Err(RustcastError::Export(ExportError::InvalidRange(_))));
+        assert!(invalid(-1.0, 5.0));
+        assert!(invalid(5.0, 5.0));
+        assert!(invalid(5.0, 4.0));
+        assert!(invalid(0.0, MAX_CLIP_SECONDS + 0.001));
+        assert!(invalid(f64::NAN, 5.0));
+        assert!(invalid(0.0, f64::NAN));
+        assert!(invalid(0.0, f64::INFINITY));
+    }
+
+    #[test]
+    fn syncsafe_sizes_use_seven_bits_a_byte() {
+        assert_eq!(syncsafe(0), [0, 0, 0, 0]);
+        assert_eq!(syncsafe(127), [0, 0, 0, 127]);
+        assert_eq!(syncsafe(128), [0, 0, 1, 0]);
+        assert_eq!(syncsafe(1000), [0, 0, 7, 104]);
+        assert_eq!(syncsafe(0x0fff_ffff), [0x7f, 0x7f, 0x7f, 0x7f]);
+    }
+
+    #[test]
+    fn chunks_are_padded_to_an_even_length() {
+        let mut odd = Vec::new();
+        chunk(&mut odd, b"ICMT", b"abc");
+        assert_eq!(odd, b"ICMT\x03\0\0\0abc\0");
+
+        let mut even = Vec::new();
+        chunk(&mut even, b"ICMT", b"ab");
+        assert_eq!(even, b"ICMT\x02\0\0\0ab");
+    }
+}
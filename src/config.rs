@@ -0,0 +1,256 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use url::Url;
+use crate::directory;
+use crate::error::{ConfigError, RustcastError, RustcastResult};
+use crate::loudness;
+use crate::silence;
+
+const APP_DIR: &str = "rustcast";
+const CONFIG_FILE: &str = "config.toml";
+const DATABASE_FILE: &str = "rustcast.db";
+const CREDENTIALS_FILE: &str = "credentials";
+const BACKUP_DIR: &str = "backups";
+const ARTWORK_DIR: &str = "artwork";
+/// Where everything lived before the XDG layout
+const LEGACY_DATABASE_FILE: &str = ".rustcast.db";
+const LEGACY_CREDENTIALS_FILE: &str = ".rustcast.credentials";
+
+pub const MIN_SPEED: f32 = 0.5;
+pub const MAX_SPEED: f32 = 3.0;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Theme {
+    System,
+    Light,
+    Dark,
+}
+
+/// User settings, stored as TOML under `$XDG_CONFIG_HOME/rustcast/`. Missing keys fall back
+/// to their defaults so older files keep working as settings are added.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub data_dir: PathBuf,
+    /// Database to use instead of the one in `data_dir`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub database_file: Option<PathBuf>,
+    pub download_dir: PathBuf,
+    /// Minutes between automatic feed refreshes, 0 turns them off
+    pub refresh_interval_minutes: u64,
+    pub skip_back_seconds: u64,
+    pub skip_forward_seconds: u64,
+    /// Speed episodes start at, with players that can change it
+    pub default_speed: f32,
+    pub theme: Theme,
+    /// Podcast and episode artwork; turning it off saves bandwidth
+    pub show_artwork: bool,
+    /// Bring playback and exported clips to `loudness_target` LUFS
+    pub normalize_loudness: bool,
+    pub loudness_target: f64,
+    /// Shorten pauses of at least `min_silence_seconds` while playing
+    pub trim_silence: bool,
+    pub min_silence_seconds: f64,
+    pub http_timeout_seconds: u64,
+    pub user_agent: String,
+    /// Podcast directories searched when discovering podcasts; the base URLs can point at a
+    /// local stand-in instead of the real services
+    pub itunes_base_url: String,
+    pub podcast_index_base_url: String,
+    /// Podcast Index only answers signed requests, so it's left out of searches until both
+    /// are set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub podcast_index_key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub podcast_index_secret: Option<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        let data_dir = dirs::data_dir()
+            .map(|dir| dir.join(APP_DIR))
+            .unwrap_or_else(|| PathBuf::from(APP_DIR));
+        let download_dir = dirs::download_dir()
+            .or_else(dirs::audio_dir)
+            .map(|dir| dir.join("Podcasts"))
+            .unwrap_or_else(|| data_dir.join("downloads"));
+
+        Config {
+            data_dir,
+            database_file: None,
+            download_dir,
+            refresh_interval_minutes: 60,
+            skip_back_seconds: 15,
+            skip_forward_seconds: 30,
+            default_speed: 1.0,
+            theme: Theme::System,
+            show_artwork: true,
+            normalize_loudness: false,
+            loudness_target: loudness::DEFAULT_TARGET_LUFS,
+            trim_silence: false,
+            min_silence_seconds: 1.0,
+            http_timeout_seconds: 30,
+            user_agent: default_user_agent(),
+            itunes_base_url: directory::DEFAULT_ITUNES_BASE_URL.to_string(),
+            podcast_index_base_url: directory::DEFAULT_PODCAST_INDEX_BASE_URL.to_string(),
+            podcast_index_key: None,
+            podcast_index_secret: None,
+        }
+    }
+}
+
+impl Config {
+    /// Location of the config file; falls back to the working directory if the platform
+    /// doesn't define a config directory
+    pub fn path() -> PathBuf {
+        dirs::config_dir()
+            .map(|dir| dir.join(APP_DIR))
+            .unwrap_or_else(|| PathBuf::from(APP_DIR))
+            .join(CONFIG_FILE)
+    }
+
+    /// Reads the config file, or the defaults if there is none yet
+    pub fn load(path: &Path) -> RustcastResult<Self> {
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Config::default()),
+            Err(e) => return Err(RustcastError::Config(ConfigError::Io(e.to_string()))),
+        };
+
+        let config: Config = toml::from_str(&text)
+            .map_err(|e| RustcastError::Config(ConfigError::Parse(e.message().to_string())))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn save(&self, path: &Path) -> RustcastResult<()> {
+        self.validate()?;
+        let text = toml::to_string_pretty(self)
+            .map_err(|e| RustcastError::Config(ConfigError::Io(e.to_string())))?;
+
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(config_io)?;
+        }
+        std::fs::write(path, text).map_err(config_io)
+    }
+
+    pub fn validate(&self) -> RustcastResult<()> {
+        let invalid = |msg: &str| Err(RustcastError::Config(ConfigError::Invalid(msg.to_string())));
+
+        if self.data_dir.as_os_str().is_empty() {
+            return invalid("The data directory cannot be empty");
+        }
+        if self.download_dir.as_os_str().is_empty() {
+            return invalid("The download directory cannot be empty");
+        }
+        if self.skip_back_seconds == 0 || self.skip_back_seconds > 600 {
+            return invalid("Skip back must be between 1 and 600 seconds");
+        }
+        if self.skip_forward_seconds == 0 || self.skip_forward_seconds > 600 {
+            return invalid("Skip forward must be between 1 and 600 seconds");
+        }
+        if !(MIN_SPEED..=MAX_SPEED).contains(&self.default_speed) {
+            return invalid(&format!("Playback speed must be between {} and {}", MIN_SPEED, MAX_SPEED));
+        }
+        if !(loudness::MIN_TARGET_LUFS..=loudness::MAX_TARGET_LUFS).contains(&self.loudness_target) {
+            return invalid(&format!("The loudness target must be between {} and {} LUFS",
+                loudness::MIN_TARGET_LUFS, loudness::MAX_TARGET_LUFS));
+        }
+        if !(silence::MIN_SILENCE_SECONDS..=silence::MAX_SILENCE_SECONDS).contains(&self.min_silence_seconds) {
+            return invalid(&format!("Trimmed silences must be between {} and {} seconds long",
+                silence::MIN_SILENCE_SECONDS, silence::MAX_SILENCE_SECONDS));
+        }
+        if self.http_timeout_seconds == 0 || self.http_timeout_seconds > 600 {
+            return invalid("The HTTP timeout must be between 1 and 600 seconds");
+        }
+        if self.user_agent.trim().is_empty() {
+            return invalid("The user agent cannot be empty");
+        }
+        if reqwest::header::HeaderValue::from_str(&self.user_agent).is_err() {
+            return invalid("The user agent contains characters that can't be sent in a header");
+        }
+        if !is_http_url(&self.itunes_base_url) {
+            return invalid("The iTunes address must be an HTTP or HTTPS URL");
+        }
+        if !is_http_url(&self.podcast_index_base_url) {
+            return invalid("The Podcast Index address must be an HTTP or HTTPS URL");
+        }
+        Ok(())
+    }
+
+    pub fn database_path(&self) -> PathBuf {
+        self.database_file.clone().unwrap_or_else(|| self.data_dir.join(DATABASE_FILE))
+    }
+
+    pub fn backup_dir(&self) -> PathBuf {
+        self.data_dir.join(BACKUP_DIR)
+    }
+
+    pub fn credentials_path(&self) -> PathBuf {
+        self.data_dir.join(CREDENTIALS_FILE)
+    }
+
+    /// Thumbnails of artwork; they can always be fetched again, so they go in the cache
+    /// directory if the platform has one
+    pub fn artwork_dir(&self) -> PathBuf {
+        dirs::cache_dir()
+            .map(|dir| dir.join(APP_DIR))
+            .unwrap_or_else(|| self.data_dir.join("cache"))
+            .join(ARTWORK_DIR)
+    }
+
+    pub fn refresh_interval(&self) -> Option<Duration> {
+        (self.refresh_interval_minutes > 0).then(|| Duration::from_secs(self.refresh_interval_minutes * 60))
+    }
+
+    pub fn http_timeout(&self) -> Duration {
+        Duration::from_secs(self.http_timeout_seconds)
+    }
+
+    /// Creates the data directory and moves the database and credentials over from their old
+    /// places in the home directory, unless the new locations are already in use
+    pub fn prepare_data_dir(&self) -> RustcastResult<()> {
+        std::fs::create_dir_all(&self.data_dir).map_err(config_io)?;
+
+        if let Some(home) = dirs::home_dir() {
+            migrate_file(&home.join(LEGACY_DATABASE_FILE), &self.data_dir.join(DATABASE_FILE))?;
+            migrate_file(&home.join(LEGACY_CREDENTIALS_FILE), &self.credentials_path())?;
+        }
+        Ok(())
+    }
+}
+
+pub fn default_user_agent() -> String {
+    concat!("Rustcast/", env!("CARGO_PKG_VERSION")).to_string()
+}
+
+fn is_http_url(url: &str) -> bool {
+    Url::parse(url.trim()).is_ok_and(|url| matches!(url.scheme(), "http" | "https"))
+}
+
+fn migrate_file(from: &Path, to: &Path) -> RustcastResult<()> {
+    if !from.is_file() {
+        return Ok(());
+    }
+    if to.exists() {
+        warn!("Not moving {} because {} already exists", from.display(), to.display());
+        return Ok(());
+    }
+
+    // A rename fails across filesystems, in which case the file is copied instead
+    if std::fs::rename(from, to).is_err() {
+        std::fs::copy(from, to).map_err(config_io)?;
+        if let Err(e) = std::fs::remove_file(from) {
+            warn!("Copied {} but couldn't remove it: {}", from.display(), e);
+        }
+    }
+    info!("Moved {} to {}", from.display(), to.display());
+    Ok(())
+}
+
+fn config_io(err: std::io::Error) -> RustcastError {
+    RustcastError::Config(ConfigError::Io(err.to_string()))
+}
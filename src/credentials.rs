@@ -0,0 +1,223 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use crate::error::{CredentialsError, RustcastError, RustcastResult};
+
+const FILE_MAGIC: &[u8] = b"RCCRED1";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const KDF_ROUNDS: u32 = 600_000;
+
+pub type SharedCredentials = Arc<RwLock<CredentialStore>>;
+
+/// Authentication for a private feed, applied to both feed and enclosure requests
+#[derive(Default, PartialEq, Clone, Serialize, Deserialize)]
+pub struct FeedCredentials {
+    pub username: String,
+    pub password: String,
+    /// Extra request headers such as API tokens, one `(name, value)` pair each
+    pub headers: Vec<(String, String)>,
+}
+
+// Secrets must never end up in logs, so only the shape of the credentials is printed
+impl fmt::Debug for FeedCredentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FeedCredentials")
+            .field("username", &if self.username.is_empty() { "" } else { "<redacted>" })
+            .field("password", &if self.password.is_empty() { "" } else { "<redacted>" })
+            .field("headers", &self.headers.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl FeedCredentials {
+    pub fn is_empty(&self) -> bool {
+        self.username.is_empty() && self.password.is_empty() && self.headers.is_empty()
+    }
+
+    /// Headers to add to every request for this feed, including Basic auth if configured
+    pub fn request_headers(&self) -> Vec<(String, String)> {
+        let mut headers = Vec::new();
+        if !self.username.is_empty() || !self.password.is_empty() {
+            headers.push(("Authorization".to_string(), basic_auth(&self.username, &self.password)));
+        }
+        headers.extend(self.headers.iter().cloned());
+        headers
+    }
+
+    /// Parses `Name: value` lines as typed in the credentials dialog
+    pub fn parse_headers(text: &str) -> Vec<(String, String)> {
+        text.lines()
+            .filter_map(|line| line.split_once(':'))
+            .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+            .filter(|(name, _)| !name.is_empty())
+            .collect()
+    }
+
+    pub fn format_headers(&self) -> String {
+        self.headers.iter()
+            .map(|(name, value)| format!("{}: {}", name, value))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// A passphrase typed by the user; kept out of `Debug` output
+#[derive(Clone, PartialEq)]
+pub struct Passphrase(pub String);
+
+impl fmt::Debug for Passphrase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Passphrase(<redacted>)")
+    }
+}
+
+/// Per-podcast credentials, kept in a separate file encrypted with a key derived from the
+/// user's passphrase so the main database never contains secrets.
+pub struct CredentialStore {
+    path: PathBuf,
+    key: Option<[u8; 32]>,
+    salt: [u8; SALT_LEN],
+    entries: HashMap<i32, FeedCredentials>,
+}
+
+impl CredentialStore {
+    pub fn new(path: PathBuf) -> Self {
+        CredentialStore {
+            path,
+            key: None,
+            salt: [0; SALT_LEN],
+            entries: HashMap::new(),
+        }
+    }
+
+    pub fn shared(path: PathBuf) -> SharedCredentials {
+        Arc::new(RwLock::new(Self::new(path)))
+    }
+
+    pub fn exists(&self) -> bool {
+        self.path.is_file()
+    }
+
+    pub fn is_unlocked(&self) -> bool {
+        self.key.is_some()
+    }
+
+    /// Decrypts the store, or starts an empty one protected by `passphrase` if none exists yet
+    pub fn unlock(&mut self, passphrase: &Passphrase) -> RustcastResult<()> {
+        if passphrase.0.is_empty() {
+            return Err(RustcastError::Credentials(CredentialsError::EmptyPassphrase));
+        }
+
+        if !self.exists() {
+            rand::thread_rng().fill_bytes(&mut self.salt);
+            self.key = Some(derive_key(passphrase, &self.salt));
+            self.entries.clear();
+            return self.save();
+        }
+
+        let data = std::fs::read(&self.path)
+            .map_err(|e| RustcastError::Credentials(CredentialsError::Io(e.to_string())))?;
+        let header_len = FILE_MAGIC.len() + SALT_LEN + NONCE_LEN;
+        if data.len() < header_len || !data.starts_with(FILE_MAGIC) {
+            return Err(RustcastError::Credentials(CredentialsError::Corrupted));
+        }
+
+        let salt = &data[FILE_MAGIC.len()..FILE_MAGIC.len() + SALT_LEN];
+        let nonce = Nonce::from_slice(&data[FILE_MAGIC.len() + SALT_LEN..header_len]);
+        let mut salt_bytes = [0; SALT_LEN];
+        salt_bytes.copy_from_slice(salt);
+
+        let key = derive_key(passphrase, &salt_bytes);
+        let plaintext = ChaCha20Poly1305::new(Key::from_slice(&key))
+            .decrypt(nonce, &data[header_len..])
+            .map_err(|_| RustcastError::Credentials(CredentialsError::WrongPassphrase))?;
+        let entries: HashMap<i32, FeedCredentials> = serde_json::from_slice(&plaintext)
+            .map_err(|_| RustcastError::Credentials(CredentialsError::Corrupted))?;
+
+        self.salt = salt_bytes;
+        self.key = Some(key);
+        self.entries = entries;
+        Ok(())
+    }
+
+    pub fn get(&self, podcast_id: i32) -> Option<&FeedCredentials> {
+        self.entries.get(&podcast_id)
+    }
+
+    pub fn set(&mut self, podcast_id: i32, credentials: Option<FeedCredentials>) -> RustcastResult<()> {
+        if !self.is_unlocked() {
+            return Err(RustcastError::Credentials(CredentialsError::Locked));
+        }
+
+        match credentials.filter(|c| !c.is_empty()) {
+            Some(credentials) => self.entries.insert(podcast_id, credentials),
+            None => self.entries.remove(&podcast_id),
+        };
+        self.save()
+    }
+
+    fn save(&self) -> RustcastResult<()> {
+        let Some(key) = &self.key else {
+            return Err(RustcastError::Credentials(CredentialsError::Locked));
+        };
+
+        let plaintext = serde_json::to_vec(&self.entries)
+            .map_err(|_| RustcastError::Credentials(CredentialsError::Corrupted))?;
+        let mut nonce = [0; NONCE_LEN];
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let ciphertext = ChaCha20Poly1305::new(Key::from_slice(key))
+            .encrypt(Nonce::from_slice(&nonce), plaintext.as_slice())
+            .map_err(|_| RustcastError::Credentials(CredentialsError::Corrupted))?;
+
+        let mut data = Vec::with_capacity(FILE_MAGIC.len() + SALT_LEN + NONCE_LEN + ciphertext.len());
+        data.extend_from_slice(FILE_MAGIC);
+        data.extend_from_slice(&self.salt);
+        data.extend_from_slice(&nonce);
+        data.extend_from_slice(&ciphertext);
+
+        write_private_file(&self.path, &data)
+            .map_err(|e| RustcastError::Credentials(CredentialsError::Io(e.to_string())))
+    }
+}
+
+pub fn credentials_for(shared: &SharedCredentials, podcast_id: i32) -> Option<FeedCredentials> {
+    shared.read().ok().and_then(|store| store.get(podcast_id).cloned())
+}
+
+fn derive_key(passphrase: &Passphrase, salt: &[u8; SALT_LEN]) -> [u8; 32] {
+    let mut key = [0; 32];
+    pbkdf2::pbkdf2_hmac::<sha2::Sha256>(passphrase.0.as_bytes(), salt, KDF_ROUNDS, &mut key);
+    key
+}
+
+#[cfg(unix)]
+fn write_private_file(path: &std::path::Path, data: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+    use std::os::unix::fs::OpenOptionsExt;
+
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)?;
+    file.write_all(data)
+}
+
+#[cfg(not(unix))]
+fn write_private_file(path: &std::path::Path, data: &[u8]) -> std::io::Result<()> {
+    std::fs::write(path, data)
+}
+
+fn basic_auth(username: &str, password: &str) -> String {
+    format!("Basic {}", STANDARD.encode(format!("{}:{}", username, password)))
+}
+
@@ -1,9 +1,17 @@
 use sea_orm::*;
+use crate::entity::bookmark;
 use crate::entity::episode;
 use crate::entity::podcast;
 use crate::entity::episode_state;
+use crate::entity::listening_session;
+use crate::entity::podcast_tag;
+use crate::entity::podcast_url_history;
+use crate::entity::smart_playlist;
+use crate::entity::tag;
 use crate::error::{RustcastError, RustcastResult};
+use crate::protocol::PodcastEdit;
 
+#[derive(Clone)]
 pub struct DataProvider {
     db: DatabaseConnection
 }
@@ -15,7 +23,21 @@ impl DataProvider {
         }
     }
 
-    pub async fn add_podcast(&self, title: String, link: String, description: String) -> Result<(), sea_orm::DbErr> {
+    pub async fn add_podcast(&self, title: String, link: String, description: String) -> Result<podcast::Model, sea_orm::DbErr> {
+        // Subscribing again to a feed whose history was kept picks that history back up
+        let unsubscribed = podcast::Entity::find()
+            .filter(podcast::Column::Link.eq(link.as_str()))
+            .filter(podcast::Column::UnsubscribedAt.is_not_null())
+            .one(&self.db)
+            .await?;
+        if let Some(unsubscribed) = unsubscribed {
+            let mut podcast: podcast::ActiveModel = unsubscribed.into();
+            podcast.title = ActiveValue::Set(Some(title));
+            podcast.description = ActiveValue::Set(Some(description));
+            podcast.unsubscribed_at = ActiveValue::Set(None);
+            return podcast.update(&self.db).await;
+        }
+
         let podcast_to_add = podcast::ActiveModel {
             title: ActiveValue::set(Some(title)),
             link: ActiveValue::set(Some(link)),
@@ -23,17 +45,333 @@ impl DataProvider {
             ..Default::default()
         };
 
-        podcast_to_add.insert(&self.db).await?;
-        Ok(())
+        podcast_to_add.insert(&self.db).await
+    }
+
+    /// Points a podcast at its new feed URL and remembers the old one
+    pub async fn move_podcast_feed(&self, podcast_id: i32, old_url: &str, new_url: &str, reason: &str) -> Result<(), sea_orm::DbErr> {
+        let txn = self.db.begin().await?;
+
+        podcast::Entity::update_many()
+            .col_expr(podcast::Column::Link, sea_query::Expr::value(new_url))
+            .filter(podcast::Column::Id.eq(podcast_id))
+            .exec(&txn)
+            .await?;
+
+        let history_entry = podcast_url_history::ActiveModel {
+            podcast_id: ActiveValue::Set(podcast_id),
+            url: ActiveValue::Set(old_url.to_string()),
+            replaced_by: ActiveValue::Set(new_url.to_string()),
+            reason: ActiveValue::Set(reason.to_string()),
+            changed_at: ActiveValue::Set(crate::utils::unix_timestamp()),
+            ..Default::default()
+        };
+        history_entry.insert(&txn).await?;
+
+        txn.commit().await
+    }
+
+    pub async fn get_podcast(&self, podcast_id: i32) -> Result<Option<podcast::Model>, sea_orm::DbErr> {
+        podcast::Entity::find_by_id(podcast_id).one(&self.db).await
     }
 
+    /// Subscribed podcasts in the order the user arranged them; ones never moved come last
     pub async fn get_podcasts(&self) -> Result<Vec<podcast::Model>, sea_orm::DbErr> {
         let res: Vec<podcast::Model> = podcast::Entity::find()
+            .filter(podcast::Column::UnsubscribedAt.is_null())
+            .order_by_asc(sea_query::Expr::col(podcast::Column::SortOrder).is_null())
+            .order_by_asc(podcast::Column::SortOrder)
+            .order_by_asc(podcast::Column::Id)
             .all(&self.db)
         .await?;
         Ok(res)
     }
 
+    pub async fn update_podcast(&self, podcast_id: i32, edit: &PodcastEdit) -> Result<(), sea_orm::DbErr> {
+        let (preferred_quality, preferred_format) = edit.enclosure_preference.columns();
+        podcast::Entity::update_many()
+            .col_expr(podcast::Column::TitleOverride, sea_query::Expr::value(edit.title_override.clone()))
+            .col_expr(podcast::Column::Description, sea_query::Expr::value(edit.description.clone()))
+            .col_expr(podcast::Column::GainDb, sea_query::Expr::value(edit.gain_db))
+            .col_expr(podcast::Column::PreferredQuality, sea_query::Expr::value(preferred_quality))
+            .col_expr(podcast::Column::PreferredFormat, sea_query::Expr::value(preferred_format))
+            .filter(podcast::Column::Id.eq(podcast_id))
+            .exec(&self.db)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn set_podcast_image(&self, podcast_id: i32, image_url: Option<String>) -> Result<(), sea_orm::DbErr> {
+        podcast::Entity::update_many()
+            .col_expr(podcast::Column::ImageUrl, sea_query::Expr::value(image_url))
+            .filter(podcast::Column::Id.eq(podcast_id))
+            .exec(&self.db)
+            .await?;
+        Ok(())
+    }
+
+    /// Stores the side panel order, `podcast_ids` being the podcasts from top to bottom
+    pub async fn set_podcast_order(&self, podcast_ids: &[i32]) -> Result<(), sea_orm::DbErr> {
+        let txn = self.db.begin().await?;
+
+        for (position, podcast_id) in podcast_ids.iter().enumerate() {
+            podcast::Entity::update_many()
+                .col_expr(podcast::Column::SortOrder, sea_query::Expr::value(position as i32))
+                .filter(podcast::Column::Id.eq(*podcast_id))
+                .exec(&txn)
+                .await?;
+        }
+
+        txn.commit().await
+    }
+
+    /// Removes a podcast with its episodes. With `keep_history` the playback positions stay
+    /// and the podcast is only hidden, so subscribing again restores them.
+    pub async fn unsubscribe_podcast(&self, podcast_id: i32, keep_history: bool) -> Result<(), sea_orm::DbErr> {
+        let txn = self.db.begin().await?;
+
+        episode::Entity::delete_many()
+            .filter(episode::Column::PodcastId.eq(podcast_id))
+            .exec(&txn)
+            .await?;
+
+        if keep_history {
+            podcast::Entity::update_many()
+                .col_expr(podcast::Column::UnsubscribedAt, sea_query::Expr::value(crate::utils::unix_timestamp()))
+                .col_expr(podcast::Column::SortOrder, sea_query::Expr::value(Option::<i32>::None))
+                .filter(podcast::Column::Id.eq(podcast_id))
+                .exec(&txn)
+                .await?;
+        } else {
+            // Done explicitly rather than relying on the foreign keys, which SQLite only
+            // enforces when the connection asks for it
+            episode_state::Entity::delete_many()
+                .filter(episode_state::Column::PodcastId.eq(podcast_id))
+                .exec(&txn)
+                .await?;
+            podcast_url_history::Entity::delete_many()
+                .filter(podcast_url_history::Column::PodcastId.eq(podcast_id))
+                .exec(&txn)
+                .await?;
+            podcast_tag::Entity::delete_many()
+                .filter(podcast_tag::Column::PodcastId.eq(podcast_id))
+                .exec(&txn)
+                .await?;
+            listening_session::Entity::delete_many()
+                .filter(listening_session::Column::PodcastId.eq(podcast_id))
+                .exec(&txn)
+                .await?;
+            bookmark::Entity::delete_many()
+                .filter(bookmark::Column::PodcastId.eq(podcast_id))
+                .exec(&txn)
+                .await?;
+            podcast::Entity::delete_by_id(podcast_id)
+                .exec(&txn)
+                .await?;
+        }
+
+        txn.commit().await
+    }
+
+    /// Tags by name, each with the podcasts that have it
+    pub async fn get_tags(&self) -> Result<Vec<(tag::Model, Vec<i32>)>, sea_orm::DbErr> {
+        let tags = tag::Entity::find()
+            .order_by_asc(tag::Column::Name)
+            .all(&self.db)
+            .await?;
+        let podcast_tags = podcast_tag::Entity::find()
+            .all(&self.db)
+            .await?;
+
+        Ok(tags.into_iter()
+            .map(|tag| {
+                let podcast_ids = podcast_tags.iter()
+                    .filter(|pt| pt.tag_id == tag.id)
+                    .map(|pt| pt.podcast_id)
+                    .collect();
+                (tag, podcast_ids)
+            })
+            .collect())
+    }
+
+    pub async fn find_tag(&self, name: &str) -> Result<Option<tag::Model>, sea_orm::DbErr> {
+        tag::Entity::find()
+            .filter(tag::Column::Name.eq(name))
+            .one(&self.db)
+            .await
+    }
+
+    pub async fn create_tag(&self, name: &str) -> Result<tag::Model, sea_orm::DbErr> {
+        let tag_to_add = tag::ActiveModel {
+            name: ActiveValue::Set(name.to_string()),
+            ..Default::default()
+        };
+
+        tag_to_add.insert(&self.db).await
+    }
+
+    pub async fn rename_tag(&self, tag_id: i32, name: &str) -> Result<(), sea_orm::DbErr> {
+        tag::Entity::update_many()
+            .col_expr(tag::Column::Name, sea_query::Expr::value(name))
+            .filter(tag::Column::Id.eq(tag_id))
+            .exec(&self.db)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn delete_tag(&self, tag_id: i32) -> Result<(), sea_orm::DbErr> {
+        let txn = self.db.begin().await?;
+
+        podcast_tag::Entity::delete_many()
+            .filter(podcast_tag::Column::TagId.eq(tag_id))
+            .exec(&txn)
+            .await?;
+        tag::Entity::delete_by_id(tag_id)
+            .exec(&txn)
+            .await?;
+
+        txn.commit().await
+    }
+
+    pub async fn tag_podcast(&self, podcast_id: i32, tag_id: i32) -> Result<(), sea_orm::DbErr> {
+        let podcast_tag_to_add = podcast_tag::ActiveModel {
+            podcast_id: ActiveValue::Set(podcast_id),
+            tag_id: ActiveValue::Set(tag_id),
+        };
+
+        podcast_tag::Entity::insert(podcast_tag_to_add)
+            .on_conflict(
+                sea_query::OnConflict::columns([podcast_tag::Column::PodcastId, podcast_tag::Column::TagId])
+                    .do_nothing()
+                    .to_owned()
+            )
+            .exec_without_returning(&self.db)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn untag_podcast(&self, podcast_id: i32, tag_id: i32) -> Result<(), sea_orm::DbErr> {
+        podcast_tag::Entity::delete_many()
+            .filter(podcast_tag::Column::PodcastId.eq(podcast_id))
+            .filter(podcast_tag::Column::TagId.eq(tag_id))
+            .exec(&self.db)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn get_tagged_podcast_ids(&self, tag_id: i32) -> Result<Vec<i32>, sea_orm::DbErr> {
+        podcast_tag::Entity::find()
+            .select_only()
+            .column(podcast_tag::Column::PodcastId)
+            .filter(podcast_tag::Column::TagId.eq(tag_id))
+            .into_tuple()
+            .all(&self.db)
+            .await
+    }
+
+    /// Podcasts with an episode that was started but not finished
+    pub async fn get_podcasts_in_progress(&self) -> Result<Vec<i32>, sea_orm::DbErr> {
+        episode_state::Entity::find()
+            .select_only()
+            .column(episode_state::Column::PodcastId)
+            .distinct()
+            .filter(episode_state::Column::Finished.eq(false))
+            .filter(episode_state::Column::Time.gt(0.0))
+            .into_tuple()
+            .all(&self.db)
+            .await
+    }
+
+    /// Podcast and publication date of every episode that was never played
+    pub async fn get_unplayed_episode_dates(&self) -> Result<Vec<(i32, Option<String>)>, sea_orm::DbErr> {
+        episode::Entity::find()
+            .select_only()
+            .column(episode::Column::PodcastId)
+            .column(episode::Column::PubDate)
+            .left_join(episode_state::Entity)
+            .filter(episode_state::Column::Id.is_null())
+            .into_tuple()
+            .all(&self.db)
+            .await
+    }
+
+    pub async fn get_episodes_of_podcasts(&self, podcast_ids: &[i32]) -> Result<Vec<episode::Model>, sea_orm::DbErr> {
+        episode::Entity::find()
+            .filter(episode::Column::PodcastId.is_in(podcast_ids.iter().copied()))
+            .all(&self.db)
+            .await
+    }
+
+    pub async fn get_episode_states_of_podcasts(&self, podcast_ids: &[i32]) -> Result<std::collections::HashMap<String, f64>, sea_orm::DbErr> {
+        let states: Vec<episode_state::Model> = episode_state::Entity::find()
+            .filter(episode_state::Column::PodcastId.is_in(podcast_ids.iter().copied()))
+            .all(&self.db)
+            .await?;
+
+        Ok(states.into_iter().map(|state| (state.ep_link, state.time)).collect())
+    }
+
+    pub async fn get_playlists(&self) -> Result<Vec<smart_playlist::Model>, sea_orm::DbErr> {
+        smart_playlist::Entity::find()
+            .order_by_asc(smart_playlist::Column::Name)
+            .all(&self.db)
+            .await
+    }
+
+    pub async fn get_playlist(&self, playlist_id: i32) -> Result<Option<smart_playlist::Model>, sea_orm::DbErr> {
+        smart_playlist::Entity::find_by_id(playlist_id).one(&self.db).await
+    }
+
+    /// Adds a playlist, or replaces name and query of the one with `playlist_id`
+    pub async fn save_playlist(&self, playlist_id: Option<i32>, name: &str, query: &str) -> Result<(), sea_orm::DbErr> {
+        let playlist = smart_playlist::ActiveModel {
+            id: playlist_id.map_or(ActiveValue::NotSet, ActiveValue::Unchanged),
+            name: ActiveValue::Set(name.to_string()),
+            query: ActiveValue::Set(query.to_string()),
+        };
+
+        match playlist_id {
+            Some(_) => playlist.update(&self.db).await?,
+            None => playlist.insert(&self.db).await?,
+        };
+        Ok(())
+    }
+
+    pub async fn delete_playlist(&self, playlist_id: i32) -> Result<(), sea_orm::DbErr> {
+        smart_playlist::Entity::delete_by_id(playlist_id)
+            .exec(&self.db)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn get_every_episode(&self) -> Result<Vec<episode::Model>, sea_orm::DbErr> {
+        episode::Entity::find()
+            .all(&self.db)
+            .await
+    }
+
+    /// Playback state of every episode ever played, by episode link
+    pub async fn get_every_episode_state(&self) -> Result<std::collections::HashMap<String, episode_state::Model>, sea_orm::DbErr> {
+        let states: Vec<episode_state::Model> = episode_state::Entity::find()
+            .all(&self.db)
+            .await?;
+
+        Ok(states.into_iter().map(|state| (state.ep_link.clone(), state)).collect())
+    }
+
+    /// Tags of every tagged podcast
+    pub async fn get_podcast_tags(&self) -> Result<std::collections::HashMap<i32, Vec<i32>>, sea_orm::DbErr> {
+        let podcast_tags = podcast_tag::Entity::find()
+            .all(&self.db)
+            .await?;
+
+        let mut tags: std::collections::HashMap<i32, Vec<i32>> = std::collections::HashMap::new();
+        for podcast_tag in podcast_tags {
+            tags.entry(podcast_tag.podcast_id).or_default().push(podcast_tag.tag_id);
+        }
+        Ok(tags)
+    }
+
     pub async  fn get_all_episodes(&self, podcast_id: i32) -> Result<Vec<episode::Model>, sea_orm::DbErr> {
         let episodes: Vec<episode::Model> = episode::Entity::find()
             .filter(episode::Column::PodcastId.eq(podcast_id))
@@ -43,7 +381,10 @@ impl DataProvider {
         Ok(episodes)
     }
 
-    pub async fn add_episodes(&self, episodes: Vec<rss::Item>, podcast_id: i32) -> RustcastResult<()> {
+    /// Replaces the stored episodes of a podcast with those in the feed. Episodes already
+    /// stored keep when they were first seen and whether they were triaged; on the first fetch
+    /// the whole back catalogue counts as triaged so it doesn't flood the inbox.
+    pub async fn replace_episodes(&self, episodes: Vec<rss::Item>, podcast_id: i32) -> RustcastResult<()> {
         let mut active_models = Vec::new();
         let mut failed_count = 0;
 
@@ -70,28 +411,199 @@ impl DataProvider {
             log::warn!("Failed to parse {} episodes out of {}", failed_count, active_models.len() + failed_count);
         }
 
-        episode::Entity::insert_many(active_models).exec(&self.db).await?;
+        let txn = self.db.begin().await?;
+
+        let known: std::collections::HashMap<String, (Option<i64>, bool)> = episode::Entity::find()
+            .select_only()
+            .column(episode::Column::Link)
+            .column(episode::Column::FirstSeen)
+            .column(episode::Column::Triaged)
+            .filter(episode::Column::PodcastId.eq(podcast_id))
+            .into_tuple::<(Option<String>, Option<i64>, bool)>()
+            .all(&txn)
+            .await?
+            .into_iter()
+            .filter_map(|(link, first_seen, triaged)| Some((link?, (first_seen, triaged))))
+            .collect();
+        let first_fetch = known.is_empty();
+        let now = crate::utils::unix_timestamp();
+
+        for episode_model in &mut active_models {
+            let link = match &episode_model.link {
+                ActiveValue::Set(Some(link)) => Some(link.as_str()),
+                _ => None,
+            };
+            let (first_seen, triaged) = link.and_then(|link| known.get(link).copied())
+                .unwrap_or((Some(now), first_fetch));
+            episode_model.first_seen = ActiveValue::Set(first_seen);
+            episode_model.triaged = ActiveValue::Set(triaged);
+        }
+
+        episode::Entity::delete_many()
+            .filter(episode::Column::PodcastId.eq(podcast_id))
+            .exec(&txn)
+            .await?;
+        episode::Entity::insert_many(active_models).exec(&txn).await?;
+
+        txn.commit().await?;
         Ok(())
     }
 
-    pub async fn delete_episodes_by_podcast_id(&self, podcast_id: i32) -> Result<(), sea_orm::DbErr> {
-        episode::Entity::delete_many().filter(episode::Column::PodcastId.eq(podcast_id)).exec(&self.db).await?;
+    /// Episodes nobody looked at yet, most recently seen first
+    pub async fn get_inbox(&self) -> Result<Vec<episode::Model>, sea_orm::DbErr> {
+        episode::Entity::find()
+            .filter(episode::Column::Triaged.eq(false))
+            .order_by_desc(episode::Column::FirstSeen)
+            .order_by_asc(episode::Column::Id)
+            .all(&self.db)
+            .await
+    }
+
+    /// Takes episodes out of the inbox
+    pub async fn archive_episodes(&self, links: &[String]) -> Result<(), sea_orm::DbErr> {
+        episode::Entity::update_many()
+            .col_expr(episode::Column::Triaged, sea_query::Expr::value(true))
+            .filter(episode::Column::Link.is_in(links.iter().cloned()))
+            .exec(&self.db)
+            .await?;
         Ok(())
     }
 
+    /// Marks episodes as finished, given as podcast and link, and takes them out of the inbox
+    pub async fn mark_episodes_played(&self, episodes: &[(i32, String)]) -> Result<(), sea_orm::DbErr> {
+        let txn = self.db.begin().await?;
+
+        for (podcast_id, link) in episodes {
+            let episode_state_active_model = episode_state::ActiveModel {
+                time: ActiveValue::Set(0.0),
+                finished: ActiveValue::Set(true),
+                podcast_id: ActiveValue::Set(*podcast_id),
+                ep_link: ActiveValue::Set(link.clone()),
+                ..Default::default()
+            };
+
+            episode_state::Entity::insert(episode_state_active_model)
+                .on_conflict(
+                    sea_query::OnConflict::column(episode_state::Column::EpLink)
+                        .update_column(episode_state::Column::Finished)
+                        .to_owned()
+                )
+                .exec_without_returning(&txn)
+                .await?;
+        }
+        episode::Entity::update_many()
+            .col_expr(episode::Column::Triaged, sea_query::Expr::value(true))
+            .filter(episode::Column::Link.is_in(episodes.iter().map(|(_, link)| link.clone())))
+            .exec(&txn)
+            .await?;
+
+        txn.commit().await
+    }
+
+    pub async fn add_listening_session(&self, session: listening_session::ActiveModel) -> Result<(), sea_orm::DbErr> {
+        listening_session::Entity::insert(session)
+            .exec(&self.db)
+            .await?;
+        Ok(())
+    }
+
+    /// Every listening session, oldest first
+    pub async fn get_listening_sessions(&self) -> Result<Vec<listening_session::Model>, sea_orm::DbErr> {
+        listening_session::Entity::find()
+            .order_by_asc(listening_session::Column::StartedAt)
+            .all(&self.db)
+            .await
+    }
+
+    pub async fn get_episode_by_link(&self, link: &str) -> Result<Option<episode::Model>, sea_orm::DbErr> {
+        episode::Entity::find()
+            .filter(episode::Column::Link.eq(link))
+            .one(&self.db)
+            .await
+    }
+
+    pub async fn add_bookmark(&self, bookmark: bookmark::ActiveModel) -> Result<(), sea_orm::DbErr> {
+        bookmark::Entity::insert(bookmark)
+            .exec(&self.db)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn get_bookmark(&self, bookmark_id: i32) -> Result<Option<bookmark::Model>, sea_orm::DbErr> {
+        bookmark::Entity::find_by_id(bookmark_id)
+            .one(&self.db)
+            .await
+    }
+
+    pub async fn update_bookmark(&self, bookmark_id: i32, end_position: Option<f64>, note: &str) -> Result<(), sea_orm::DbErr> {
+        bookmark::Entity::update_many()
+            .col_expr(bookmark::Column::EndPosition, sea_query::Expr::value(end_position))
+            .col_expr(bookmark::Column::Note, sea_query::Expr::value(note))
+            .filter(bookmark::Column::Id.eq(bookmark_id))
+            .exec(&self.db)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn delete_bookmark(&self, bookmark_id: i32) -> Result<(), sea_orm::DbErr> {
+        bookmark::Entity::delete_by_id(bookmark_id)
+            .exec(&self.db)
+            .await?;
+        Ok(())
+    }
+
+    /// Every bookmark, grouped by episode and in the order they come up in it
+    pub async fn get_bookmarks(&self) -> Result<Vec<bookmark::Model>, sea_orm::DbErr> {
+        bookmark::Entity::find()
+            .order_by_asc(bookmark::Column::PodcastId)
+            .order_by_asc(bookmark::Column::EpLink)
+            .order_by_asc(bookmark::Column::Position)
+            .all(&self.db)
+            .await
+    }
+
+    /// Every stored podcast, including those unsubscribed from whose history was kept
+    pub async fn get_all_podcasts(&self) -> Result<Vec<podcast::Model>, sea_orm::DbErr> {
+        podcast::Entity::find()
+            .all(&self.db)
+            .await
+    }
+
     pub async  fn upsert_episode_state(&self, progress: f64, podcast_id: i32, link: &str) -> Result<(), sea_orm::DbErr> {
         let episode_state_active_model = episode_state::ActiveModel {
             time: ActiveValue::Set(progress),
             finished: ActiveValue::Set(false),
             podcast_id: ActiveValue::Set(podcast_id),
             ep_link: ActiveValue::Set(link.to_string()),
+            last_played: ActiveValue::Set(Some(crate::utils::unix_timestamp())),
+            ..Default::default()
+        };
+
+        episode_state::Entity::insert(episode_state_active_model)
+            .on_conflict(
+                sea_query::OnConflict::column(episode_state::Column::EpLink)
+                    .update_columns([episode_state::Column::Time, episode_state::Column::LastPlayed])
+                    .to_owned()
+            )
+            .exec(&self.db)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn import_episode_state(&self, progress: f64, finished: bool, podcast_id: i32, link: &str) -> Result<(), sea_orm::DbErr> {
+        let episode_state_active_model = episode_state::ActiveModel {
+            time: ActiveValue::Set(progress),
+            finished: ActiveValue::Set(finished),
+            podcast_id: ActiveValue::Set(podcast_id),
+            ep_link: ActiveValue::Set(link.to_string()),
             ..Default::default()
         };
 
         episode_state::Entity::insert(episode_state_active_model)
             .on_conflict(
                 sea_query::OnConflict::column(episode_state::Column::EpLink)
-                    .update_column(episode_state::Column::Time)
+                    .update_columns([episode_state::Column::Time, episode_state::Column::Finished])
                     .to_owned()
             )
             .exec(&self.db)
@@ -0,0 +1,223 @@
+use std::path::{Path, PathBuf};
+use log::{info, warn};
+use sea_orm::{ConnectionTrait, Database, DatabaseConnection};
+use sea_orm_migration::MigratorTrait;
+use crate::error::{DatabaseError, RustcastError, RustcastResult};
+use crate::utils;
+
+/// How many automatic backups are kept; older ones are removed after each new backup
+const BACKUPS_KEPT: usize = 5;
+const BACKUP_PREFIX: &str = "rustcast-";
+const BACKUP_SUFFIX: &str = ".db";
+/// Files SQLite keeps next to the database while it's in WAL mode
+const SIDE_FILE_SUFFIXES: [&str; 2] = ["-wal", "-shm"];
+
+/// Where the database lives. A `DATABASE_URL` from the environment takes precedence over the
+/// file, but then there's nothing to back up or restore.
+#[derive(Debug, PartialEq, Clone)]
+pub struct DatabaseLocation {
+    pub file: PathBuf,
+    pub url_override: Option<String>,
+    pub backup_dir: PathBuf,
+}
+
+impl DatabaseLocation {
+    fn url(&self) -> String {
+        self.url_override.clone()
+            .unwrap_or_else(|| format!("sqlite://{}?mode=rwc", self.file.display()))
+    }
+
+    fn backed_up_file(&self) -> Option<&Path> {
+        self.url_override.is_none().then_some(self.file.as_path())
+    }
+
+    pub fn latest_backup(&self) -> Option<PathBuf> {
+        self.backed_up_file()?;
+        backups(&self.backup_dir).pop()
+    }
+}
+
+/// Connects to the database and brings its schema up to date. If migrations are pending on
+/// an existing file, a copy is taken first so a failed migration can be rolled back by hand.
+pub async fn open(location: &DatabaseLocation) -> RustcastResult<DatabaseConnection> {
+    let existed = location.backed_up_file()
+        .is_some_and(|file| file.metadata().is_ok_and(|m| m.len() > 0));
+
+    let db = Database::connect(location.url()).await
+        .map_err(|e| RustcastError::Database(DatabaseError::ConnectionFailed(e.to_string())))?;
+
+    let pending = migrations::Migrator::get_pending_migrations(&db).await
+        .map_err(|e| RustcastError::Database(DatabaseError::MigrationFailed(e.to_string())))?;
+    if pending.is_empty() {
+        return Ok(db);
+    }
+
+    if existed {
+        let backup = backup(&db, &location.backup_dir).await?;
+        info!("Backed up the database to {} before running {} migrations", backup.display(), pending.len());
+    }
+
+    info!("Running database migrations...");
+    migrations::Migrator::up(&db, None).await
+        .map_err(|e| RustcastError::Database(DatabaseError::MigrationFailed(e.to_string())))?;
+    info!("Database migrations completed successfully");
+
+    Ok(db)
+}
+
+/// Replaces the database file with the newest backup. The file being replaced is kept next
+/// to it in case it can still be salvaged.
+pub fn restore_latest_backup(location: &DatabaseLocation) -> RustcastResult<PathBuf> {
+    let backup = location.latest_backup().ok_or_else(|| {
+        RustcastError::Database(DatabaseError::BackupFailed("There is no backup to restore".to_string()))
+    })?;
+
+    if location.file.exists() {
+        let mut broken = location.file.clone().into_os_string();
+        broken.push(format!(".broken-{}", utils::unix_timestamp()));
+        std::fs::rename(&location.file, &broken).map_err(backup_io)?;
+        warn!("Moved the database being restored over to {}", PathBuf::from(&broken).display());
+
+        // A journal left behind would be applied to the restored file, so it goes along
+        for suffix in SIDE_FILE_SUFFIXES {
+            let side_file = with_suffix(&location.file, suffix);
+            if side_file.exists() {
+                std::fs::rename(&side_file, with_suffix(Path::new(&broken), suffix)).map_err(backup_io)?;
+            }
+        }
+    }
+    std::fs::copy(&backup, &location.file).map_err(backup_io)?;
+
+    info!("Restored {} from {}", location.file.display(), backup.display());
+    Ok(backup)
+}
+
+async fn backup(db: &DatabaseConnection, backup_dir: &Path) -> RustcastResult<PathBuf> {
+    std::fs::create_dir_all(backup_dir).map_err(backup_io)?;
+    let path = new_backup_path(backup_dir, utils::unix_timestamp());
+
+    // VACUUM INTO writes a consistent copy through SQLite itself, unlike copying the file
+    let target = path.display().to_string().replace('\'', "''");
+    db.execute_unprepared(&format!("VACUUM INTO '{}'", target)).await
+        .map_err(|e| RustcastError::Database(DatabaseError::BackupFailed(e.to_string())))?;
+
+    let mut backups = backups(backup_dir);
+    let excess = backups.len().saturating_sub(BACKUPS_KEPT);
+    for old in backups.drain(..excess) {
+        if let Err(e) = std::fs::remove_file(&old) {
+            warn!("Failed to remove old backup {}: {}", old.display(), e);
+        }
+    }
+
+    Ok(path)
+}
+
+/// `rustcast-<timestamp>.db`, with a counter after the timestamp when there already is a
+/// backup from the same second
+fn new_backup_path(backup_dir: &Path, timestamp: i64) -> PathBuf {
+    (0..)
+        .map(|count: u32| match count {
+            0 => format!("{}{}{}", BACKUP_PREFIX, timestamp, BACKUP_SUFFIX),
+            count => format!("{}{}-{}{}", BACKUP_PREFIX, timestamp, count, BACKUP_SUFFIX),
+        })
+        .map(|name| backup_dir.join(name))
+        .find(|path| !path.exists())
+        .unwrap_or_default()
+}
+
+/// Automatic backups in `backup_dir`, oldest first
+fn backups(backup_dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(backup_dir) else {
+        return Vec::new();
+    };
+
+    let mut backups: Vec<((i64, u32), PathBuf)> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_str()?.to_string();
+            let stamp = name.strip_prefix(BACKUP_PREFIX)?.strip_suffix(BACKUP_SUFFIX)?;
+            let (timestamp, count) = stamp.split_once('-').unwrap_or((stamp, "0"));
+            Some(((timestamp.parse().ok()?, count.parse().ok()?), entry.path()))
+        })
+        .collect();
+    backups.sort();
+    backups.into_iter().map(|(_, path)| path).collect()
+}
+
+/// `path` with `suffix` added to its file name
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+fn backup_io(err: std::io::Error) -> RustcastError {
+    RustcastError::Database(DatabaseError::BackupFailed(err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An empty directory of its own under the system's temporary directory
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("rustcast-database-{}-{}", std::process::id(), name));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn backups_from_the_same_second_are_kept_apart() {
+        let dir = scratch_dir("same-second");
+        let first = new_backup_path(&dir, 1_700_000_000);
+        std::fs::write(&first, "first").unwrap();
+        let second = new_backup_path(&dir, 1_700_000_000);
+        std::fs::write(&second, "second").unwrap();
+        let third = new_backup_path(&dir, 1_700_000_000);
+        std::fs::write(&third, "third").unwrap();
+        let older = new_backup_path(&dir, 1_600_000_000);
+        std::fs::write(&older, "older").unwrap();
+
+        assert_eq!(first, dir.join("rustcast-1700000000.db"));
+        assert_eq!(second, dir.join("rustcast-1700000000-1.db"));
+        assert_eq!(backups(&dir), [older, first, second, third.clone()]);
+
+        let location = DatabaseLocation {
+            file: dir.join("rustcast.db"),
+            url_override: None,
+            backup_dir: dir.clone(),
+        };
+        assert_eq!(location.latest_backup(), Some(third));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn restoring_moves_the_journal_aside() {
+        let dir = scratch_dir("restore");
+        let location = DatabaseLocation {
+            file: dir.join("rustcast.db"),
+            url_override: None,
+            backup_dir: dir.join("backups"),
+        };
+        std::fs::create_dir_all(&location.backup_dir).unwrap();
+        std::fs::write(new_backup_path(&location.backup_dir, 1_700_000_000), "backup").unwrap();
+        std::fs::write(&location.file, "broken").unwrap();
+        std::fs::write(with_suffix(&location.file, "-wal"), "journal").unwrap();
+        std::fs::write(with_suffix(&location.file, "-shm"), "index").unwrap();
+
+        restore_latest_backup(&location).unwrap();
+
+        assert_eq!(std::fs::read_to_string(&location.file).unwrap(), "backup");
+        assert!(!with_suffix(&location.file, "-wal").exists());
+        assert!(!with_suffix(&location.file, "-shm").exists());
+        let mut moved_aside: Vec<String> = std::fs::read_dir(&dir).unwrap()
+            .filter_map(|entry| entry.unwrap().file_name().into_string().ok())
+            .filter(|name| name.contains(".broken-"))
+            .map(|name| name.split_once(".broken-").map(|(_, rest)| rest.trim_start_matches(char::is_numeric).to_string()).unwrap())
+            .collect();
+        moved_aside.sort();
+        assert_eq!(moved_aside, ["", "-shm", "-wal"]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,103 @@
+use std::path::Path;
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::{DecoderOptions, CODEC_TYPE_NULL};
+use symphonia::core::errors::Error as DecodeError;
+use symphonia::core::formats::{FormatOptions, SeekMode, SeekTo};
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+use symphonia::core::units::Time;
+use tokio_util::sync::CancellationToken;
+use crate::error::{ExportError, NetworkError, RustcastError, RustcastResult};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct AudioSpec {
+    pub rate: u32,
+    pub channels: u16,
+}
+
+fn decode_error(e: DecodeError) -> RustcastError {
+    RustcastError::Export(ExportError::Decode(e.to_string()))
+}
+
+/// Decodes an audio file from `from` seconds on, handing each packet to `packet` as
+/// interleaved 16-bit samples along with the time its first frame starts at. Decoding stops
+/// at the end of the file or once `packet` returns `false`.
+///
+/// Formats that can't seek are decoded from the start, so the first packets may begin before
+/// `from`. Blocks while it decodes, so it belongs on a blocking thread.
+pub fn decode(
+    source: &Path,
+    from: f64,
+    cancel: &CancellationToken,
+    mut packet: impl FnMut(f64, &[i16], AudioSpec) -> RustcastResult<bool>,
+) -> RustcastResult<()> {
+    let file = std::fs::File::open(source)
+        .map_err(|e| RustcastError::Export(ExportError::Decode(e.to_string())))?;
+
+    let mut hint = Hint::new();
+    if let Some(extension) = source.extension().and_then(|extension| extension.to_str()) {
+        hint.with_extension(extension);
+    }
+    let probed = symphonia::default::get_probe()
+        .format(
+            &hint,
+            MediaSourceStream::new(Box::new(file), Default::default()),
+            &FormatOptions::default(),
+            &MetadataOptions::default(),
+        )
+        .map_err(decode_error)?;
+    let mut format = probed.format;
+
+    let track = format.tracks().iter()
+        .find(|track| track.codec_params.codec != CODEC_TYPE_NULL)
+        .ok_or_else(|| RustcastError::Export(ExportError::Decode("no audio track".to_string())))?;
+    let track_id = track.id;
+    let time_base = track.codec_params.time_base;
+    let mut decoder = symphonia::default::get_codecs()
+        .make(&track.codec_params, &DecoderOptions::default())
+        .map_err(decode_error)?;
+
+    if from > 0.0 && format.seek(SeekMode::Accurate, SeekTo::Time { time: Time::from(from), track_id: Some(track_id) }).is_ok() {
+        decoder.reset();
+    }
+
+    loop {
+        if cancel.is_cancelled() {
+            return Err(RustcastError::Network(NetworkError::Cancelled));
+        }
+        let next = match format.next_packet() {
+            Ok(next) => next,
+            Err(DecodeError::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(()),
+            Err(e) => return Err(decode_error(e)),
+        };
+        if next.track_id() != track_id {
+            continue;
+        }
+        let decoded = match decoder.decode(&next) {
+            Ok(decoded) => decoded,
+            // A damaged packet is a moment of silence, not a failed decode
+            Err(DecodeError::DecodeError(_)) => continue,
+            Err(e) => return Err(decode_error(e)),
+        };
+
+        let spec = *decoded.spec();
+        let time = match time_base {
+            Some(time_base) => {
+                let time = time_base.calc_time(next.ts());
+                time.seconds as f64 + time.frac
+            }
+            None => next.ts() as f64 / spec.rate as f64,
+        };
+
+        let mut buffer = SampleBuffer::<i16>::new(decoded.capacity() as u64, spec);
+        buffer.copy_interleaved_ref(decoded);
+        let spec = AudioSpec {
+            rate: spec.rate,
+            channels: spec.channels.count() as u16,
+        };
+        if !packet(time, buffer.samples(), spec)? {
+            return Ok(());
+        }
+    }
+}
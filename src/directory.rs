@@ -0,0 +1,361 @@
+use serde::Deserialize;
+use sha1::{Digest, Sha1};
+use tokio_util::sync::CancellationToken;
+use crate::config::Config;
+use crate::error::{NetworkError, RssError, RustcastError, RustcastResult};
+use crate::http::HttpClient;
+
+pub const DEFAULT_ITUNES_BASE_URL: &str = "https://itunes.apple.com";
+pub const DEFAULT_PODCAST_INDEX_BASE_URL: &str = "https://api.podcastindex.org/api/1.0";
+const SEARCH_LIMIT: usize = 25;
+
+/// Endpoints and credentials of the podcast directories, taken from the settings
+#[derive(Debug, PartialEq, Clone)]
+pub struct DirectoryConfig {
+    pub itunes_base_url: String,
+    pub podcast_index_base_url: String,
+    pub podcast_index_key: Option<String>,
+    pub podcast_index_secret: Option<String>,
+}
+
+impl DirectoryConfig {
+    pub fn from_config(config: &Config) -> Self {
+        let credential = |value: &Option<String>| value.as_ref()
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty());
+
+        DirectoryConfig {
+            itunes_base_url: config.itunes_base_url.trim().to_string(),
+            podcast_index_base_url: config.podcast_index_base_url.trim().to_string(),
+            podcast_index_key: credential(&config.podcast_index_key),
+            podcast_index_secret: credential(&config.podcast_index_secret),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum DirectorySource {
+    ITunes,
+    PodcastIndex,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct DirectoryResult {
+    pub title: String,
+    pub author: String,
+    pub feed_url: String,
+    pub description: String,
+    pub artwork_url: Option<String>,
+    pub source: DirectorySource,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ITunesResponse {
+    results: Vec<ITunesPodcast>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ITunesPodcast {
+    collection_name: Option<String>,
+    artist_name: Option<String>,
+    feed_url: Option<String>,
+    primary_genre_name: Option<String>,
+    track_count: Option<u32>,
+    artwork_url600: Option<String>,
+    artwork_url100: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct PodcastIndexResponse {
+    feeds: Vec<PodcastIndexFeed>,
+}
+
+#[derive(Deserialize)]
+struct PodcastIndexFeed {
+    title: Option<String>,
+    author: Option<String>,
+    url: Option<String>,
+    description: Option<String>,
+    artwork: Option<String>,
+    image: Option<String>,
+}
+
+/// Searches every configured directory, keeping whatever succeeded if one of them fails
+pub async fn search(
+    client: &HttpClient,
+    config: &DirectoryConfig,
+    term: &str,
+    cancel: &CancellationToken,
+) -> RustcastResult<Vec<DirectoryResult>> {
+    if term.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut results = Vec::new();
+    let mut last_error = None;
+
+    match search_itunes(client, config, term, cancel).await {
+        Ok(found) => results.extend(found),
+        Err(e @ RustcastError::Network(NetworkError::Cancelled)) => return Err(e),
+        Err(e) => {
+            log::warn!("iTunes search failed: {}", e);
+            last_error = Some(e);
+        }
+    }
+
+    if config.podcast_index_key.is_some() && config.podcast_index_secret.is_some() {
+        match search_podcast_index(client, config, term, cancel).await {
+            Ok(found) => {
+                for result in found {
+                    if !results.iter().any(|r: &DirectoryResult| crate::utils::same_feed_url(&r.feed_url, &result.feed_url)) {
+                        results.push(result);
+                    }
+                }
+            }
+            Err(e) => {
+                log::warn!("Podcast Index search failed: {}", e);
+                last_error = Some(e);
+            }
+        }
+    }
+
+    match last_error {
+        Some(e) if results.is_empty() => Err(e),
+        _ => Ok(results),
+    }
+}
+
+pub async fn search_itunes(
+    client: &HttpClient,
+    config: &DirectoryConfig,
+    term: &str,
+    cancel: &CancellationToken,
+) -> RustcastResult<Vec<DirectoryResult>> {
+    let url = format!("{}/search", config.itunes_base_url.trim_end_matches('/'));
+    let limit = SEARCH_LIMIT.to_string();
+    let body: ITunesResponse = client.get_json(
+        &url,
+        &[("media", "podcast"), ("entity", "podcast"), ("term", term.trim()), ("limit", &limit)],
+        &[],
+        cancel,
+    ).await?;
+
+    Ok(body.results.into_iter()
+        .filter_map(|p| {
+            let feed_url = p.feed_url?;
+            let details: Vec<String> = [
+                p.primary_genre_name,
+                p.track_count.map(|count| format!("{} episodes", count)),
+            ].into_iter().flatten().collect();
+
+            Some(DirectoryResult {
+                title: p.collection_name.unwrap_or_else(|| feed_url.clone()),
+                author: p.artist_name.unwrap_or_default(),
+                description: details.join(" · "),
+                artwork_url: p.artwork_url600.or(p.artwork_url100),
+                feed_url,
+                source: DirectorySource::ITunes,
+            })
+        })
+        .collect())
+}
+
+pub async fn search_podcast_index(
+    client: &HttpClient,
+    config: &DirectoryConfig,
+    term: &str,
+    cancel: &CancellationToken,
+) -> RustcastResult<Vec<DirectoryResult>> {
+    let (Some(key), Some(secret)) = (&config.podcast_index_key, &config.podcast_index_secret) else {
+        return Err(RustcastError::Network(NetworkError::RequestFailed(
+            "Podcast Index API key and secret are not configured".to_string()
+        )));
+    };
+
+    let auth_date = crate::utils::unix_timestamp().to_string();
+
+    let url = format!("{}/search/byterm", config.podcast_index_base_url.trim_end_matches('/'));
+    let limit = SEARCH_LIMIT.to_string();
+    let authorization = podcast_index_authorization(key, secret, &auth_date);
+    let body: PodcastIndexResponse = client.get_json(
+        &url,
+        &[("q", term.trim()), ("max", &limit)],
+        &[("X-Auth-Key", key), ("X-Auth-Date", &auth_date), ("Authorization", &authorization)],
+        cancel,
+    ).await?;
+
+    Ok(body.feeds.into_iter()
+        .filter_map(|f| {
+            let feed_url = f.url?;
+            Some(DirectoryResult {
+                title: f.title.unwrap_or_else(|| feed_url.clone()),
+                author: f.author.unwrap_or_default(),
+                description: f.description.unwrap_or_default(),
+                artwork_url: f.artwork.filter(|a| !a.is_empty()).or(f.image.filter(|i| !i.is_empty())),
+                feed_url,
+                source: DirectorySource::PodcastIndex,
+            })
+        })
+        .collect())
+}
+
+/// Resolves an Apple Podcasts show id to its RSS feed through the iTunes lookup API
+pub async fn lookup_feed_url(
+    client: &HttpClient,
+    config: &DirectoryConfig,
+    itunes_id: &str,
+    cancel: &CancellationToken,
+) -> RustcastResult<String> {
+    let url = format!("{}/lookup", config.itunes_base_url.trim_end_matches('/'));
+    let body: ITunesResponse = client.get_json(
+        &url,
+        &[("id", itunes_id), ("entity", "podcast")],
+        &[],
+        cancel,
+    ).await?;
+
+    body.results.into_iter()
+        .find_map(|p| p.feed_url)
+        .ok_or_else(|| RustcastError::Rss(RssError::InvalidFeed(
+            format!("Apple Podcasts show {} has no public feed", itunes_id)
+        )))
+}
+
+/// Podcast Index expects the hex SHA-1 of key, secret and the `X-Auth-Date` value
+fn podcast_index_authorization(key: &str, secret: &str, auth_date: &str) -> String {
+    let mut hasher = Sha1::new();
+    hasher.update(key.as_bytes());
+    hasher.update(secret.as_bytes());
+    hasher.update(auth_date.as_bytes());
+
+    hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use tokio::task::JoinHandle;
+    use crate::test_server::{self, header};
+    use super::*;
+
+    async fn canned_response(body: &str) -> (String, JoinHandle<String>) {
+        test_server::serve_body("application/json", body).await
+    }
+
+    fn client() -> HttpClient {
+        HttpClient::new(Duration::from_secs(5), "Rustcast tests").unwrap()
+    }
+
+    fn directory_config(base_url: &str) -> DirectoryConfig {
+        DirectoryConfig {
+            itunes_base_url: base_url.to_string(),
+            podcast_index_base_url: format!("{}/api/1.0/", base_url),
+            podcast_index_key: Some("KEY".to_string()),
+            podcast_index_secret: Some("SECRET".to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn parses_itunes_search() {
+        let (base_url, server) = canned_response(r#"{"resultCount": 2, "results": [
+            {"collectionName": "Rust Talk", "artistName": "Ferris", "feedUrl": "https://example.com/rust.xml",
+             "primaryGenreName": "Technology", "trackCount": 42,
+             "artworkUrl100": "https://example.com/100.jpg", "artworkUrl600": "https://example.com/600.jpg"},
+            {"collectionName": "No feed", "artistName": "Nobody"}
+        ]}"#).await;
+
+        let results = search_itunes(&client(), &directory_config(&base_url), " rust ", &CancellationToken::new())
+            .await
+            .unwrap();
+        let request = server.await.unwrap();
+
+        assert!(request.starts_with("GET /search?media=podcast&entity=podcast&term=rust&limit=25 "));
+        assert_eq!(results, [DirectoryResult {
+            title: "Rust Talk".to_string(),
+            author: "Ferris".to_string(),
+            feed_url: "https://example.com/rust.xml".to_string(),
+            description: "Technology · 42 episodes".to_string(),
+            artwork_url: Some("https://example.com/600.jpg".to_string()),
+            source: DirectorySource::ITunes,
+        }]);
+    }
+
+    #[tokio::test]
+    async fn signs_podcast_index_search() {
+        let (base_url, server) = canned_response(r#"{"status": "true", "feeds": [
+            {"title": "Linux Weekly", "author": "Tux", "url": "https://example.org/linux.rss",
+             "description": "News", "artwork": "", "image": "https://example.org/cover.png"},
+            {"title": "No feed"}
+        ]}"#).await;
+
+        let results = search_podcast_index(&client(), &directory_config(&base_url), "linux", &CancellationToken::new())
+            .await
+            .unwrap();
+        let request = server.await.unwrap();
+
+        assert!(request.starts_with("GET /api/1.0/search/byterm?q=linux&max=25 "));
+        assert_eq!(header(&request, "X-Auth-Key"), Some("KEY"));
+        let auth_date = header(&request, "X-Auth-Date").unwrap();
+        assert!(auth_date.parse::<u64>().is_ok());
+        let authorization = podcast_index_authorization("KEY", "SECRET", auth_date);
+        assert_eq!(header(&request, "Authorization"), Some(authorization.as_str()));
+
+        assert_eq!(results, [DirectoryResult {
+            title: "Linux Weekly".to_string(),
+            author: "Tux".to_string(),
+            feed_url: "https://example.org/linux.rss".to_string(),
+            description: "News".to_string(),
+            artwork_url: Some("https://example.org/cover.png".to_string()),
+            source: DirectorySource::PodcastIndex,
+        }]);
+    }
+
+    #[tokio::test]
+    async fn podcast_index_needs_credentials() {
+        let config = DirectoryConfig {
+            podcast_index_secret: None,
+            ..directory_config("http://127.0.0.1:9")
+        };
+
+        let result = search_podcast_index(&client(), &config, "linux", &CancellationToken::new()).await;
+        assert!(matches!(result, Err(RustcastError::Network(NetworkError::RequestFailed(_)))));
+    }
+
+    #[tokio::test]
+    async fn looks_up_apple_podcasts_feed() {
+        let (base_url, server) = canned_response(
+            r#"{"results": [{"collectionName": "Rust Talk", "feedUrl": "https://example.com/rust.xml"}]}"#
+        ).await;
+
+        let feed_url = lookup_feed_url(&client(), &directory_config(&base_url), "1234", &CancellationToken::new())
+            .await
+            .unwrap();
+
+        assert!(server.await.unwrap().starts_with("GET /lookup?id=1234&entity=podcast "));
+        assert_eq!(feed_url, "https://example.com/rust.xml");
+    }
+
+    #[test]
+    fn authorization_is_sha1_of_key_secret_and_date() {
+        // SHA-1 of "abc"
+        assert_eq!(podcast_index_authorization("a", "b", "c"), "a9993e364706816aba3e25717850c26c9cd0d89d");
+    }
+
+    #[test]
+    fn takes_directories_from_the_settings() {
+        let config = Config {
+            itunes_base_url: " http://localhost:8080 ".to_string(),
+            podcast_index_key: Some(" KEY ".to_string()),
+            podcast_index_secret: Some("  ".to_string()),
+            ..Config::default()
+        };
+
+        let directories = DirectoryConfig::from_config(&config);
+        assert_eq!(directories.itunes_base_url, "http://localhost:8080");
+        assert_eq!(directories.podcast_index_base_url, DEFAULT_PODCAST_INDEX_BASE_URL);
+        assert_eq!(directories.podcast_index_key.as_deref(), Some("KEY"));
+        assert_eq!(directories.podcast_index_secret, None);
+    }
+}
@@ -0,0 +1,60 @@
+use std::path::{Path, PathBuf};
+use log::info;
+use sha1::{Digest, Sha1};
+use crate::error::{DownloadError, RustcastError, RustcastResult};
+
+/// Downloaded episodes of a podcast all live in one directory under the download directory,
+/// so unsubscribing can remove them in one go
+pub fn podcast_dir(download_dir: &Path, podcast_id: i32) -> PathBuf {
+    download_dir.join(podcast_id.to_string())
+}
+
+/// File a downloaded episode is saved to. It's named after its link, which stays the same
+/// when the feed is refreshed, unlike the episode's id.
+pub fn episode_file(download_dir: &Path, podcast_id: i32, link: &str) -> PathBuf {
+    let hash: String = Sha1::digest(link.as_bytes()).iter().take(8).map(|b| format!("{:02x}", b)).collect();
+    let extension = url::Url::parse(link).ok()
+        .and_then(|url| {
+            Path::new(url.path()).extension()
+                .and_then(|e| e.to_str())
+                .map(|e| e.to_ascii_lowercase())
+        })
+        .filter(|e| !e.is_empty() && e.len() <= 5 && e.chars().all(|c| c.is_ascii_alphanumeric()))
+        .unwrap_or_else(|| "mp3".to_string());
+
+    podcast_dir(download_dir, podcast_id).join(format!("{}.{}", hash, extension))
+}
+
+pub async fn remove_podcast_downloads(download_dir: &Path, podcast_id: i32) -> RustcastResult<()> {
+    let dir = podcast_dir(download_dir, podcast_id);
+    match tokio::fs::remove_dir_all(&dir).await {
+        Ok(()) => {
+            info!("Removed downloads of podcast {} from {}", podcast_id, dir.display());
+            Ok(())
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(RustcastError::Download(DownloadError::Io(format!("{}: {}", dir.display(), e)))),
+    }
+}
+
+/// Podcasts that have at least one downloaded file
+pub async fn downloaded_podcasts(download_dir: &Path) -> Vec<i32> {
+    let Ok(mut entries) = tokio::fs::read_dir(download_dir).await else {
+        return Vec::new();
+    };
+
+    let mut podcast_ids = Vec::new();
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let Some(podcast_id) = entry.file_name().to_str().and_then(|name| name.parse().ok()) else {
+            continue;
+        };
+        let has_files = match tokio::fs::read_dir(entry.path()).await {
+            Ok(mut files) => matches!(files.next_entry().await, Ok(Some(_))),
+            Err(_) => false,
+        };
+        if has_files {
+            podcast_ids.push(podcast_id);
+        }
+    }
+    podcast_ids
+}
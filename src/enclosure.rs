@@ -0,0 +1,208 @@
+use serde::{Deserialize, Serialize};
+use crate::entity::{episode, podcast};
+use crate::player;
+
+/// Formats the preference can ask for, as MIME type and name
+pub const FORMATS: [(&str, &str); 5] = [
+    ("audio/mpeg", "MP3"),
+    ("audio/mp4", "AAC"),
+    ("audio/ogg", "Ogg Vorbis"),
+    ("audio/flac", "FLAC"),
+    ("video/mp4", "MP4 video"),
+];
+
+/// One version of an episode: the feed item's `enclosure` or a source of one of its
+/// `podcast:alternateEnclosure` elements
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Enclosure {
+    pub url: String,
+    pub mime_type: Option<String>,
+    /// Bits per second, as the feed gives it
+    pub bitrate: Option<f64>,
+    /// Size in bytes
+    pub length: Option<u64>,
+    pub title: Option<String>,
+}
+
+impl Enclosure {
+    pub fn is_video(&self) -> bool {
+        self.mime_type.as_deref().is_some_and(|mime_type| mime_type.starts_with("video/"))
+    }
+
+    /// `MP3, 128 kbps`, or the feed's own title for it
+    pub fn describe(&self) -> String {
+        if let Some(title) = &self.title {
+            return title.clone();
+        }
+        let format = match self.mime_type.as_deref() {
+            Some(mime_type) => format_name(mime_type).map_or_else(|| mime_type.to_string(), str::to_string),
+            None => "Unknown format".to_string(),
+        };
+        match self.bitrate {
+            Some(bitrate) => format!("{}, {:.0} kbps", format, bitrate / 1000.0),
+            None => format,
+        }
+    }
+
+    /// Bitrate if the feed gives one, size otherwise; versions are compared by it
+    fn weight(&self) -> f64 {
+        self.bitrate.or(self.length.map(|length| length as f64)).unwrap_or(0.0)
+    }
+}
+
+pub fn format_name(mime_type: &str) -> Option<&'static str> {
+    FORMATS.iter().find(|(known, _)| *known == mime_type).map(|(_, name)| *name)
+}
+
+/// Which version to play when an episode comes in several
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum Quality {
+    /// The feed's own enclosure
+    #[default]
+    FeedDefault,
+    Highest,
+    Smallest,
+}
+
+impl Quality {
+    pub const ALL: [Quality; 3] = [Quality::FeedDefault, Quality::Highest, Quality::Smallest];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Quality::FeedDefault => "Feed default",
+            Quality::Highest => "Highest quality",
+            Quality::Smallest => "Smallest download",
+        }
+    }
+
+    fn as_column(&self) -> Option<String> {
+        match self {
+            Quality::FeedDefault => None,
+            Quality::Highest => Some("highest".to_string()),
+            Quality::Smallest => Some("smallest".to_string()),
+        }
+    }
+
+    fn from_column(column: Option<&str>) -> Self {
+        match column {
+            Some("highest") => Quality::Highest,
+            Some("smallest") => Quality::Smallest,
+            _ => Quality::FeedDefault,
+        }
+    }
+}
+
+/// A podcast's choice among the versions of its episodes
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct EnclosurePreference {
+    pub quality: Quality,
+    /// MIME type to prefer, any if `None`
+    pub format: Option<String>,
+}
+
+impl EnclosurePreference {
+    pub fn of(podcast: &podcast::Model) -> Self {
+        EnclosurePreference {
+            quality: Quality::from_column(podcast.preferred_quality.as_deref()),
+            format: podcast.preferred_format.clone(),
+        }
+    }
+
+    /// Quality and format as they're stored
+    pub fn columns(&self) -> (Option<String>, Option<String>) {
+        (self.quality.as_column(), self.format.clone())
+    }
+
+    /// The version to play. Versions the player can't decode are left out and audio is
+    /// preferred over video, unless video is the preferred format; then the format counts,
+    /// then the quality. Falls back to the first version, so its failure can be reported.
+    pub fn choose<'a>(&self, enclosures: &'a [Enclosure]) -> Option<&'a Enclosure> {
+        let mut candidates: Vec<&Enclosure> = enclosures.iter()
+            .filter(|enclosure| player::check_playable(enclosure.mime_type.as_deref()).is_ok())
+            .collect();
+        if candidates.is_empty() {
+            return enclosures.first();
+        }
+
+        let wants_video = self.format.as_deref().is_some_and(|format| format.starts_with("video/"));
+        if !wants_video && candidates.iter().any(|enclosure| !enclosure.is_video()) {
+            candidates.retain(|enclosure| !enclosure.is_video());
+        }
+        if let Some(format) = &self.format {
+            if candidates.iter().any(|enclosure| enclosure.mime_type.as_ref() == Some(format)) {
+                candidates.retain(|enclosure| enclosure.mime_type.as_ref() == Some(format));
+            }
+        }
+
+        match self.quality {
+            Quality::FeedDefault => candidates.first().copied(),
+            Quality::Highest => candidates.into_iter().rev().max_by(|a, b| a.weight().total_cmp(&b.weight())),
+            Quality::Smallest => candidates.into_iter().min_by(|a, b| a.weight().total_cmp(&b.weight())),
+        }
+    }
+}
+
+/// The item's enclosure followed by its alternates. Alternates are only kept with a source
+/// that can be fetched over HTTP.
+pub fn from_rss_item(item: &rss::Item) -> Vec<Enclosure> {
+    let mut enclosures = Vec::new();
+    if let Some(enclosure) = item.enclosure() {
+        enclosures.push(Enclosure {
+            url: enclosure.url().to_string(),
+            mime_type: mime_type(enclosure.mime_type()),
+            bitrate: None,
+            length: enclosure.length().trim().parse().ok().filter(|length| *length > 0),
+            title: None,
+        });
+    }
+
+    let alternates = item.extensions().get("podcast")
+        .and_then(|podcast| podcast.get("alternateEnclosure"))
+        .into_iter()
+        .flatten();
+    for alternate in alternates {
+        let source = alternate.children.get("source")
+            .into_iter()
+            .flatten()
+            .filter_map(|source| source.attrs.get("uri"))
+            .find(|uri| uri.starts_with("https://") || uri.starts_with("http://"));
+        let Some(url) = source else {
+            continue;
+        };
+        if enclosures.iter().any(|enclosure| enclosure.url == *url) {
+            continue;
+        }
+        enclosures.push(Enclosure {
+            url: url.clone(),
+            mime_type: alternate.attrs.get("type").and_then(|t| mime_type(t)),
+            bitrate: alternate.attrs.get("bitrate").and_then(|b| b.trim().parse().ok()).filter(|b: &f64| *b > 0.0),
+            length: alternate.attrs.get("length").and_then(|l| l.trim().parse().ok()).filter(|l| *l > 0),
+            title: alternate.attrs.get("title").map(|t| t.trim().to_string()).filter(|t| !t.is_empty()),
+        });
+    }
+    enclosures
+}
+
+/// Versions of a stored episode; just its link for episodes stored before alternates were
+/// kept
+pub fn of_episode(episode: &episode::Model) -> Vec<Enclosure> {
+    let stored = episode.enclosures.as_deref()
+        .and_then(|enclosures| serde_json::from_str::<Vec<Enclosure>>(enclosures).ok())
+        .filter(|enclosures| !enclosures.is_empty());
+    if let Some(enclosures) = stored {
+        return enclosures;
+    }
+    episode.link.iter()
+        .map(|link| Enclosure {
+            url: link.clone(),
+            mime_type: episode.enclosure_type.clone(),
+            bitrate: None,
+            length: None,
+            title: None,
+        })
+        .collect()
+}
+
+pub fn mime_type(mime_type: &str) -> Option<String> {
+    Some(mime_type.trim().to_ascii_lowercase()).filter(|mime_type| !mime_type.is_empty())
+}
@@ -13,6 +13,12 @@ pub struct Model {
     pub description: Option<String>,
     pub guid: Option<String>,
     pub pub_date: Option<String>,
+    pub duration: Option<i32>,
+    pub first_seen: Option<i64>,
+    pub triaged: bool,
+    pub image_url: Option<String>,
+    pub enclosure_type: Option<String>,
+    pub enclosures: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
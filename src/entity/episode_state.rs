@@ -12,6 +12,7 @@ pub struct Model {
     pub finished: bool,
     pub podcast_id: i32,
     pub ep_link: String,
+    pub last_played: Option<i64>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -2,6 +2,12 @@
 
 pub mod prelude;
 
+pub mod bookmark;
 pub mod episode;
 pub mod episode_state;
+pub mod listening_session;
 pub mod podcast;
+pub mod podcast_tag;
+pub mod podcast_url_history;
+pub mod smart_playlist;
+pub mod tag;
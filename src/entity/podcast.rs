@@ -10,14 +10,35 @@ pub struct Model {
     pub title: Option<String>,
     pub link: Option<String>,
     pub description: Option<String>,
+    pub title_override: Option<String>,
+    pub sort_order: Option<i32>,
+    pub unsubscribed_at: Option<i64>,
+    pub image_url: Option<String>,
+    pub gain_db: i32,
+    pub preferred_quality: Option<String>,
+    pub preferred_format: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::bookmark::Entity")]
+    Bookmark,
     #[sea_orm(has_many = "super::episode::Entity")]
     Episode,
     #[sea_orm(has_many = "super::episode_state::Entity")]
     EpisodeState,
+    #[sea_orm(has_many = "super::listening_session::Entity")]
+    ListeningSession,
+    #[sea_orm(has_many = "super::podcast_tag::Entity")]
+    PodcastTag,
+    #[sea_orm(has_many = "super::podcast_url_history::Entity")]
+    PodcastUrlHistory,
+}
+
+impl Related<super::bookmark::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Bookmark.def()
+    }
 }
 
 impl Related<super::episode::Entity> for Entity {
@@ -32,4 +53,31 @@ impl Related<super::episode_state::Entity> for Entity {
     }
 }
 
+impl Related<super::listening_session::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ListeningSession.def()
+    }
+}
+
+impl Related<super::podcast_tag::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::PodcastTag.def()
+    }
+}
+
+impl Related<super::podcast_url_history::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::PodcastUrlHistory.def()
+    }
+}
+
+impl Related<super::tag::Entity> for Entity {
+    fn to() -> RelationDef {
+        super::podcast_tag::Relation::Tag.def()
+    }
+    fn via() -> Option<RelationDef> {
+        Some(super::podcast_tag::Relation::Podcast.def().rev())
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
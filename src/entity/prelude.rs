@@ -1,8 +1,20 @@
 //! `SeaORM` Entity. Generated by sea-orm-codegen 0.12.15
 
+#[allow(unused_imports)]
+pub use super::bookmark::Entity as Bookmark;
 #[allow(unused_imports)]
 pub use super::episode::Entity as Episode;
 #[allow(unused_imports)]
 pub use super::episode_state::Entity as EpisodeState;
 #[allow(unused_imports)]
+pub use super::listening_session::Entity as ListeningSession;
+#[allow(unused_imports)]
 pub use super::podcast::Entity as Podcast;
+#[allow(unused_imports)]
+pub use super::podcast_tag::Entity as PodcastTag;
+#[allow(unused_imports)]
+pub use super::podcast_url_history::Entity as PodcastUrlHistory;
+#[allow(unused_imports)]
+pub use super::smart_playlist::Entity as SmartPlaylist;
+#[allow(unused_imports)]
+pub use super::tag::Entity as Tag;
@@ -7,6 +7,10 @@ pub enum RustcastError {
     Rss(RssError),
     Player(PlayerError),
     Ui(UiError),
+    Credentials(CredentialsError),
+    Config(ConfigError),
+    Download(DownloadError),
+    Export(ExportError),
 }
 
 #[derive(Debug, Clone)]
@@ -15,6 +19,9 @@ pub enum NetworkError {
     InvalidUrl(String),
     ConnectionTimeout,
     InvalidResponse(String),
+    Cancelled,
+    /// No HTTP client could be built, and why
+    Unavailable(String),
 }
 
 #[derive(Debug, Clone)]
@@ -23,6 +30,8 @@ pub enum DatabaseError {
     QueryFailed(String),
     DataNotFound(String),
     ConstraintViolation(String),
+    MigrationFailed(String),
+    BackupFailed(String),
 }
 
 #[derive(Debug, Clone)]
@@ -41,10 +50,42 @@ pub enum PlayerError {
     UnsupportedFormat(String),
 }
 
+#[derive(Debug, Clone)]
+pub enum CredentialsError {
+    Locked,
+    WrongPassphrase,
+    EmptyPassphrase,
+    Corrupted,
+    Io(String),
+}
+
+#[derive(Debug, Clone)]
+pub enum ConfigError {
+    Io(String),
+    Parse(String),
+    Invalid(String),
+}
+
+#[derive(Debug, Clone)]
+pub enum DownloadError {
+    Io(String),
+    InvalidImage(String),
+}
+
+#[derive(Debug, Clone)]
+pub enum ExportError {
+    Io(String),
+    Decode(String),
+    /// The part of the episode asked for can't be exported, and why
+    InvalidRange(String),
+}
+
 #[derive(Debug, Clone)]
 pub enum UiError {
     InvalidState(String),
     ComponentError(String),
+    /// Something the user entered can't be used, and why
+    InvalidInput(String),
 }
 
 impl fmt::Display for RustcastError {
@@ -55,6 +96,10 @@ impl fmt::Display for RustcastError {
             RustcastError::Rss(e) => write!(f, "RSS error: {}", e),
             RustcastError::Player(e) => write!(f, "Player error: {}", e),
             RustcastError::Ui(e) => write!(f, "UI error: {}", e),
+            RustcastError::Credentials(e) => write!(f, "Credentials error: {}", e),
+            RustcastError::Config(e) => write!(f, "Configuration error: {}", e),
+            RustcastError::Download(e) => write!(f, "Download error: {}", e),
+            RustcastError::Export(e) => write!(f, "Export error: {}", e),
         }
     }
 }
@@ -66,6 +111,8 @@ impl fmt::Display for NetworkError {
             NetworkError::InvalidUrl(url) => write!(f, "Invalid URL: {}", url),
             NetworkError::ConnectionTimeout => write!(f, "Connection timeout"),
             NetworkError::InvalidResponse(msg) => write!(f, "Invalid response: {}", msg),
+            NetworkError::Cancelled => write!(f, "Request cancelled"),
+            NetworkError::Unavailable(msg) => write!(f, "Network unavailable: {}", msg),
         }
     }
 }
@@ -77,6 +124,8 @@ impl fmt::Display for DatabaseError {
             DatabaseError::QueryFailed(msg) => write!(f, "Database query failed: {}", msg),
             DatabaseError::DataNotFound(item) => write!(f, "Data not found: {}", item),
             DatabaseError::ConstraintViolation(msg) => write!(f, "Constraint violation: {}", msg),
+            DatabaseError::MigrationFailed(msg) => write!(f, "Database migration failed: {}", msg),
+            DatabaseError::BackupFailed(msg) => write!(f, "Database backup failed: {}", msg),
         }
     }
 }
@@ -108,6 +157,48 @@ impl fmt::Display for UiError {
         match self {
             UiError::InvalidState(msg) => write!(f, "Invalid UI state: {}", msg),
             UiError::ComponentError(msg) => write!(f, "Component error: {}", msg),
+            UiError::InvalidInput(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl fmt::Display for CredentialsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CredentialsError::Locked => write!(f, "Credential store is locked"),
+            CredentialsError::WrongPassphrase => write!(f, "Wrong passphrase"),
+            CredentialsError::EmptyPassphrase => write!(f, "Passphrase cannot be empty"),
+            CredentialsError::Corrupted => write!(f, "Credential store is corrupted"),
+            CredentialsError::Io(msg) => write!(f, "Credential store I/O failed: {}", msg),
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(msg) => write!(f, "Failed to access the config: {}", msg),
+            ConfigError::Parse(msg) => write!(f, "Failed to parse the config: {}", msg),
+            ConfigError::Invalid(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl fmt::Display for DownloadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DownloadError::Io(msg) => write!(f, "Failed to access downloaded files: {}", msg),
+            DownloadError::InvalidImage(msg) => write!(f, "Unreadable image: {}", msg),
+        }
+    }
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportError::Io(msg) => write!(f, "Failed to write the export: {}", msg),
+            ExportError::Decode(msg) => write!(f, "Couldn't read the episode's audio: {}", msg),
+            ExportError::InvalidRange(msg) => write!(f, "{}", msg),
         }
     }
 }
@@ -118,6 +209,10 @@ impl std::error::Error for DatabaseError {}
 impl std::error::Error for RssError {}
 impl std::error::Error for PlayerError {}
 impl std::error::Error for UiError {}
+impl std::error::Error for CredentialsError {}
+impl std::error::Error for ConfigError {}
+impl std::error::Error for DownloadError {}
+impl std::error::Error for ExportError {}
 
 // Conversion traits for better ergonomics
 impl From<sea_orm::DbErr> for RustcastError {
@@ -126,17 +221,17 @@ impl From<sea_orm::DbErr> for RustcastError {
     }
 }
 
-impl From<ureq::Error> for RustcastError {
-    fn from(err: ureq::Error) -> Self {
-        match err {
-            ureq::Error::Status(code, response) => {
-                RustcastError::Network(NetworkError::RequestFailed(
-                    format!("HTTP {}: {}", code, response.status_text())
-                ))
-            },
-            ureq::Error::Transport(transport) => {
-                RustcastError::Network(NetworkError::RequestFailed(transport.to_string()))
-            },
+impl From<reqwest::Error> for RustcastError {
+    fn from(err: reqwest::Error) -> Self {
+        if err.is_timeout() {
+            return RustcastError::Network(NetworkError::ConnectionTimeout);
+        }
+
+        match err.status() {
+            Some(status) => RustcastError::Network(NetworkError::RequestFailed(
+                format!("HTTP {}", status)
+            )),
+            None => RustcastError::Network(NetworkError::RequestFailed(err.to_string())),
         }
     }
 }
@@ -173,6 +268,7 @@ impl RustcastError {
                 "Failed to connect to the podcast server. Please check your internet connection.".to_string(),
             RustcastError::Network(NetworkError::InvalidUrl(_)) =>
                 "The podcast URL is invalid. Please check the URL and try again.".to_string(),
+            RustcastError::Network(NetworkError::Unavailable(msg)) => msg.clone(),
             RustcastError::Network(NetworkError::ConnectionTimeout) =>
                 "Connection timed out. Please try again later.".to_string(),
             RustcastError::Rss(RssError::ParseFailed(_)) =>
@@ -180,11 +276,25 @@ impl RustcastError {
             RustcastError::Rss(RssError::MissingRequiredField(field)) =>
                 format!("The podcast feed is missing required information: {}", field),
             RustcastError::Database(DatabaseError::ConnectionFailed(_)) =>
-                "Database connection failed. Please restart the application.".to_string(),
-            RustcastError::Player(PlayerError::OpenFailed(_)) =>
-                "Failed to open the audio file. The file may be corrupted or in an unsupported format.".to_string(),
-            RustcastError::Player(PlayerError::PlaybackFailed(_)) =>
-                "Playback failed. Please try again.".to_string(),
+                "The database could not be opened. It may be missing, locked by another program or not a Rustcast database.".to_string(),
+            RustcastError::Database(DatabaseError::ConstraintViolation(msg)) => msg.clone(),
+            RustcastError::Database(DatabaseError::MigrationFailed(_)) =>
+                "The database could not be upgraded to this version of Rustcast.".to_string(),
+            RustcastError::Player(PlayerError::OpenFailed(msg)) =>
+                format!("The episode couldn't be opened ({}). The file may have moved, or the server may be down.", msg),
+            RustcastError::Player(PlayerError::PlaybackFailed(msg)) =>
+                format!("Playback stopped: {}. Please try again.", msg),
+            RustcastError::Player(PlayerError::SeekFailed(_)) =>
+                "The player couldn't jump to that position.".to_string(),
+            RustcastError::Player(PlayerError::UnsupportedFormat(format)) =>
+                format!("This episode is in a format Rustcast can't play ({}).", format),
+            RustcastError::Credentials(CredentialsError::Locked) =>
+                "Private feed credentials are locked. Unlock them with your passphrase first.".to_string(),
+            RustcastError::Credentials(CredentialsError::WrongPassphrase) =>
+                "The passphrase is wrong.".to_string(),
+            RustcastError::Config(ConfigError::Invalid(msg)) => msg.clone(),
+            RustcastError::Ui(UiError::InvalidInput(msg)) => msg.clone(),
+            RustcastError::Export(ExportError::InvalidRange(msg)) => msg.clone(),
             _ => self.to_string(),
         }
     }
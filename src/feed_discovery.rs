@@ -0,0 +1,127 @@
+use url::Url;
+
+const FEED_TYPES: [&str; 2] = ["application/rss+xml", "application/atom+xml"];
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct FeedCandidate {
+    pub title: Option<String>,
+    pub url: String,
+}
+
+/// Extracts the numeric show id from `podcasts.apple.com/<country>/podcast/<slug>/id123` style links
+pub fn apple_podcasts_id(link: &str) -> Option<String> {
+    let url = Url::parse(link.trim()).ok()?;
+    let host = url.host_str()?;
+    if host != "podcasts.apple.com" && host != "itunes.apple.com" {
+        return None;
+    }
+
+    url.path_segments()?
+        .filter_map(|segment| segment.strip_prefix("id"))
+        .find(|id| !id.is_empty() && id.chars().all(|c| c.is_ascii_digit()))
+        .map(|id| id.to_string())
+}
+
+pub fn is_html(content_type: &str, content: &str) -> bool {
+    if content_type.eq_ignore_ascii_case("text/html") || content_type.eq_ignore_ascii_case("application/xhtml+xml") {
+        return true;
+    }
+
+    let start: String = content.trim_start().chars().take(64).collect::<String>().to_ascii_lowercase();
+    start.starts_with("<!doctype html") || start.starts_with("<html")
+}
+
+/// Collects `<link rel="alternate">` feeds announced by a web page, resolved against `page_url`
+pub fn find_feed_links(html: &str, page_url: &str) -> Vec<FeedCandidate> {
+    let base = Url::parse(page_url).ok();
+    let lowercase = html.to_ascii_lowercase();
+    let mut candidates: Vec<FeedCandidate> = Vec::new();
+    let mut position = 0;
+
+    while let Some(offset) = lowercase[position..].find("<link") {
+        let tag_start = position + offset + "<link".len();
+        let Some(tag_length) = html[tag_start..].find('>') else {
+            break;
+        };
+        position = tag_start + tag_length;
+
+        let attributes = parse_attributes(&html[tag_start..position]);
+        let attribute = |name: &str| attributes.iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str());
+
+        let is_alternate = attribute("rel")
+            .map(|rel| rel.split_ascii_whitespace().any(|r| r.eq_ignore_ascii_case("alternate")))
+            .unwrap_or(false);
+        let is_feed = attribute("type")
+            .map(|t| FEED_TYPES.iter().any(|feed_type| t.trim().eq_ignore_ascii_case(feed_type)))
+            .unwrap_or(false);
+
+        if !is_alternate || !is_feed {
+            continue;
+        }
+
+        let Some(href) = attribute("href").filter(|href| !href.trim().is_empty()) else {
+            continue;
+        };
+        let resolved = match &base {
+            Some(base) => base.join(href.trim()).map(|u| u.to_string()).ok(),
+            None => Url::parse(href.trim()).map(|u| u.to_string()).ok(),
+        };
+
+        if let Some(url) = resolved {
+            if !candidates.iter().any(|c| c.url == url) {
+                candidates.push(FeedCandidate {
+                    title: attribute("title").map(|t| t.trim().to_string()).filter(|t| !t.is_empty()),
+                    url,
+                });
+            }
+        }
+    }
+
+    candidates
+}
+
+/// Parses `name="value"` pairs of a single tag; names are lowercased and basic entities decoded
+fn parse_attributes(tag: &str) -> Vec<(String, String)> {
+    let mut attributes = Vec::new();
+    let mut chars = tag.trim_end_matches('/').chars().peekable();
+
+    loop {
+        while chars.next_if(|c| c.is_whitespace() || *c == '/').is_some() {}
+
+        let name: String = std::iter::from_fn(|| chars.next_if(|c| !c.is_whitespace() && *c != '=')).collect();
+        if name.is_empty() {
+            break;
+        }
+
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        if chars.next_if_eq(&'=').is_none() {
+            attributes.push((name.to_ascii_lowercase(), String::new()));
+            continue;
+        }
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+
+        let value: String = match chars.next_if(|c| *c == '"' || *c == '\'') {
+            Some(quote) => {
+                let value = std::iter::from_fn(|| chars.next_if(|c| *c != quote)).collect();
+                chars.next();
+                value
+            }
+            None => std::iter::from_fn(|| chars.next_if(|c| !c.is_whitespace())).collect(),
+        };
+
+        attributes.push((name.to_ascii_lowercase(), decode_entities(&value)));
+    }
+
+    attributes
+}
+
+fn decode_entities(value: &str) -> String {
+    value
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
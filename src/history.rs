@@ -0,0 +1,239 @@
+use std::collections::HashMap;
+use std::time::Instant;
+use chrono::{Duration, Local, NaiveDate, TimeZone};
+use sea_orm::ActiveValue;
+use crate::entity::{episode, listening_session};
+
+/// A position change bigger than playback explains by this much is taken for a seek
+const SEEK_TOLERANCE_SECONDS: f64 = 5.0;
+/// Sessions shorter than this are noise from skipping through episodes and aren't logged
+const MIN_SESSION_SECONDS: f64 = 5.0;
+/// Days shown in the daily activity chart, today included
+pub const ACTIVITY_DAYS: i64 = 30;
+/// Sessions listed as recent history
+const RECENT_SESSIONS: usize = 50;
+
+/// One stretch of uninterrupted listening, ready to be stored
+#[derive(Debug, PartialEq, Clone)]
+pub struct SessionRecord {
+    pub podcast_id: i32,
+    pub ep_link: String,
+    pub episode_title: Option<String>,
+    pub start_position: f64,
+    pub end_position: f64,
+    pub started_at: i64,
+    pub ended_at: i64,
+    pub speed: f64,
+    /// Silence skipped within the session
+    pub trimmed_seconds: f64,
+}
+
+impl SessionRecord {
+    pub fn into_active_model(self) -> listening_session::ActiveModel {
+        listening_session::ActiveModel {
+            podcast_id: ActiveValue::Set(self.podcast_id),
+            ep_link: ActiveValue::Set(self.ep_link),
+            episode_title: ActiveValue::Set(self.episode_title),
+            start_position: ActiveValue::Set(self.start_position),
+            end_position: ActiveValue::Set(self.end_position),
+            started_at: ActiveValue::Set(self.started_at),
+            ended_at: ActiveValue::Set(self.ended_at),
+            speed: ActiveValue::Set(self.speed),
+            trimmed_seconds: ActiveValue::Set(self.trimmed_seconds),
+            ..Default::default()
+        }
+    }
+}
+
+struct OpenSession {
+    record: SessionRecord,
+    last_seen: Instant,
+}
+
+/// Cuts what the player does into listening sessions. It's shown the player every frame and
+/// hands back a session whenever one ends: on pause, on another episode or on a seek.
+#[derive(Default)]
+pub struct SessionTracker {
+    open: Option<OpenSession>,
+}
+
+impl SessionTracker {
+    /// `playing` is the episode being heard right now, `None` while paused or loading
+    pub fn observe(&mut self, playing: Option<&episode::Model>, position: f64, speed: f64) -> Option<SessionRecord> {
+        let now = Instant::now();
+        if let Some(open) = &mut self.open {
+            let same_episode = playing.is_some_and(|e| e.link.as_deref() == Some(open.record.ep_link.as_str()));
+            let expected = open.record.end_position + now.duration_since(open.last_seen).as_secs_f64() * speed;
+            let seeked = position < open.record.end_position - SEEK_TOLERANCE_SECONDS
+                || position > expected + SEEK_TOLERANCE_SECONDS;
+
+            if same_episode && !seeked {
+                open.record.end_position = position;
+                open.record.ended_at = crate::utils::unix_timestamp();
+                open.last_seen = now;
+                return None;
+            }
+        }
+
+        let ended = self.close();
+        if let Some(episode) = playing {
+            if let Some(link) = &episode.link {
+                let started_at = crate::utils::unix_timestamp();
+                self.open = Some(OpenSession {
+                    record: SessionRecord {
+                        podcast_id: episode.podcast_id,
+                        ep_link: link.clone(),
+                        episode_title: episode.title.clone(),
+                        start_position: position,
+                        end_position: position,
+                        started_at,
+                        ended_at: started_at,
+                        speed,
+                        trimmed_seconds: 0.0,
+                    },
+                    last_seen: now,
+                });
+            }
+        }
+        ended
+    }
+
+    /// Records a silence the player skipped from `from` to `to`, which isn't a seek of the
+    /// listener's own
+    pub fn trimmed(&mut self, from: f64, to: f64) {
+        if let Some(open) = &mut self.open {
+            open.record.trimmed_seconds += to - from;
+            open.record.end_position = to;
+            open.last_seen = Instant::now();
+        }
+    }
+
+    /// Ends the session in progress, if there is one worth logging
+    pub fn close(&mut self) -> Option<SessionRecord> {
+        self.open.take()
+            .map(|open| open.record)
+            .filter(|record| record.end_position - record.start_position >= MIN_SESSION_SECONDS)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct HistoryEntry {
+    pub started_at: i64,
+    pub podcast: String,
+    pub episode: String,
+    pub listened_seconds: f64,
+}
+
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct ListeningStats {
+    /// Time spent listening
+    pub total_seconds: f64,
+    /// Difference between the length of what was heard and the time it took
+    pub saved_seconds: f64,
+    /// Silence skipped, which is part of `saved_seconds`
+    pub trimmed_seconds: f64,
+    /// Silence skipped per episode, most first
+    pub trimmed_per_episode: Vec<(String, f64)>,
+    /// Time spent per podcast, most listened first
+    pub per_podcast: Vec<(String, f64)>,
+    /// Time spent each day of the last `ACTIVITY_DAYS`, oldest first
+    pub daily: Vec<(NaiveDate, f64)>,
+    /// Latest sessions, newest first
+    pub recent: Vec<HistoryEntry>,
+}
+
+/// Length of the episode covered by a session and the time it took, at its speed and
+/// without the silence that was skipped
+fn content_and_time(session: &listening_session::Model) -> (f64, f64) {
+    let content = (session.end_position - session.start_position).max(0.0);
+    let speed = if session.speed > 0.0 { session.speed } else { 1.0 };
+    (content, (content - session.trimmed_seconds).max(0.0) / speed)
+}
+
+fn local_date(timestamp: i64) -> Option<NaiveDate> {
+    Local.timestamp_opt(timestamp, 0).single().map(|d| d.date_naive())
+}
+
+/// `sessions` oldest first, as stored; `titles` names the podcasts by id
+pub fn listening_stats(sessions: &[listening_session::Model], titles: &HashMap<i32, String>) -> ListeningStats {
+    let title = |podcast_id: i32| titles.get(&podcast_id).cloned().unwrap_or_else(|| "Unknown podcast".to_string());
+    let today = Local::now().date_naive();
+    let first_day = today - Duration::days(ACTIVITY_DAYS - 1);
+
+    let mut stats = ListeningStats::default();
+    let mut per_podcast: HashMap<i32, f64> = HashMap::new();
+    let mut daily: HashMap<NaiveDate, f64> = HashMap::new();
+    let mut trimmed: HashMap<&str, (String, f64)> = HashMap::new();
+
+    for session in sessions {
+        let (content, time) = content_and_time(session);
+        stats.total_seconds += time;
+        stats.saved_seconds += content - time;
+        if session.trimmed_seconds > 0.0 {
+            stats.trimmed_seconds += session.trimmed_seconds;
+            trimmed.entry(&session.ep_link)
+                .or_insert_with(|| (session.episode_title.clone().unwrap_or_else(|| session.ep_link.clone()), 0.0))
+                .1 += session.trimmed_seconds;
+        }
+        *per_podcast.entry(session.podcast_id).or_default() += time;
+        if let Some(day) = local_date(session.started_at).filter(|day| *day >= first_day) {
+            *daily.entry(day).or_default() += time;
+        }
+    }
+
+    stats.per_podcast = per_podcast.into_iter()
+        .map(|(podcast_id, seconds)| (title(podcast_id), seconds))
+        .collect();
+    stats.per_podcast.sort_by(|a, b| b.1.total_cmp(&a.1));
+    stats.trimmed_per_episode = trimmed.into_values().collect();
+    stats.trimmed_per_episode.sort_by(|a, b| b.1.total_cmp(&a.1));
+    stats.daily = (0..ACTIVITY_DAYS)
+        .map(|offset| first_day + Duration::days(offset))
+        .map(|day| (day, daily.get(&day).copied().unwrap_or_default()))
+        .collect();
+    stats.recent = sessions.iter()
+        .rev()
+        .take(RECENT_SESSIONS)
+        .map(|session| HistoryEntry {
+            started_at: session.started_at,
+            podcast: title(session.podcast_id),
+            episode: session.episode_title.clone().unwrap_or_else(|| session.ep_link.clone()),
+            listened_seconds: content_and_time(session).1,
+        })
+        .collect();
+    stats
+}
+
+/// The whole listening log as CSV, one session per row
+pub fn history_csv(sessions: &[listening_session::Model], titles: &HashMap<i32, String>) -> String {
+    let time = |timestamp: i64| Local.timestamp_opt(timestamp, 0).single()
+        .map(|d| d.to_rfc3339())
+        .unwrap_or_default();
+
+    let mut csv = String::from("started_at,ended_at,podcast,episode,link,start_position,end_position,speed,trimmed_seconds\n");
+    for session in sessions {
+        let row = [
+            time(session.started_at),
+            time(session.ended_at),
+            titles.get(&session.podcast_id).cloned().unwrap_or_default(),
+            session.episode_title.clone().unwrap_or_default(),
+            session.ep_link.clone(),
+            format!("{:.1}", session.start_position),
+            format!("{:.1}", session.end_position),
+            format!("{:.2}", session.speed),
+            format!("{:.1}", session.trimmed_seconds),
+        ];
+        let row: Vec<String> = row.iter().map(|field| csv_field(field)).collect();
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
@@ -0,0 +1,388 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio_util::sync::CancellationToken;
+use url::Url;
+use crate::credentials::FeedCredentials;
+use crate::error::{DownloadError, NetworkError, RustcastError, RustcastResult};
+use crate::utils::validate_podcast_url;
+
+pub const MAX_REDIRECTS: usize = 10;
+/// How much of a download has to arrive before progress is reported again
+const PROGRESS_STEP: u64 = 64 * 1024;
+
+/// Non-blocking HTTP client shared by all network actions. Every request has its own
+/// timeout and can be abandoned through a `CancellationToken` while it is in flight.
+#[derive(Clone)]
+pub struct HttpClient {
+    client: reqwest::Client,
+    timeout: Duration,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Redirect {
+    pub from: String,
+    pub to: String,
+    pub status: u16,
+}
+
+impl Redirect {
+    pub fn is_permanent(&self) -> bool {
+        matches!(self.status, 301 | 308)
+    }
+}
+
+pub struct FetchedDocument {
+    /// URL the content was served from, after redirects
+    pub url: String,
+    pub content_type: String,
+    pub content: String,
+    pub redirects: Vec<Redirect>,
+}
+
+impl FetchedDocument {
+    /// Where the requested URL has permanently moved. Only an unbroken chain of 301/308
+    /// redirects from the start counts, anything after a temporary hop is not persisted.
+    pub fn permanent_redirect(&self) -> Option<&Redirect> {
+        self.redirects.iter()
+            .take_while(|r| r.is_permanent())
+            .last()
+    }
+}
+
+impl HttpClient {
+    pub fn new(timeout: Duration, user_agent: &str) -> RustcastResult<Self> {
+        let client = reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .connect_timeout(timeout)
+            .user_agent(user_agent)
+            .build()?;
+
+        Ok(HttpClient {
+            client,
+            timeout,
+        })
+    }
+
+    /// Performs a single GET request; redirects are returned as-is so the caller can track them
+    pub async fn get(
+        &self,
+        url: &str,
+        credentials: Option<&FeedCredentials>,
+        cancel: &CancellationToken,
+    ) -> RustcastResult<reqwest::Response> {
+        validate_podcast_url(url)?;
+
+        let mut request = self.client.get(url);
+        if let Some(credentials) = credentials {
+            for (name, value) in credentials.request_headers() {
+                request = request.header(name, value);
+            }
+        }
+
+        let response = self.send(request, cancel).await?;
+        match response.status().as_u16() {
+            404 => Err(RustcastError::Network(NetworkError::RequestFailed(
+                "Not found (404)".to_string()
+            ))),
+            401 | 403 => Err(RustcastError::Network(NetworkError::RequestFailed(
+                format!("Access denied ({}) - check the feed's credentials", response.status().as_u16())
+            ))),
+            code if code >= 500 => Err(RustcastError::Network(NetworkError::RequestFailed(
+                "Server error - please try again later".to_string()
+            ))),
+            code if code >= 400 => Err(RustcastError::Network(NetworkError::RequestFailed(
+                format!("HTTP {}", code)
+            ))),
+            _ => Ok(response),
+        }
+    }
+
+    /// Follows redirects to the document and downloads it, calling `progress` with the bytes
+    /// received so far and the expected total
+    pub async fn fetch_document(
+        &self,
+        url: &str,
+        credentials: Option<&FeedCredentials>,
+        cancel: &CancellationToken,
+        progress: impl Fn(u64, Option<u64>),
+    ) -> RustcastResult<FetchedDocument> {
+        let (response, current, redirects) = self.follow_redirects(url, credentials, cancel).await?;
+
+        let content_type = response.headers().get(reqwest::header::CONTENT_TYPE)
+            .and_then(|t| t.to_str().ok())
+            .and_then(|t| t.split(';').next())
+            .map(|t| t.trim().to_string())
+            .unwrap_or_default();
+        let content = self.read_body(response, cancel, progress).await?;
+
+        Ok(FetchedDocument {
+            url: current,
+            content_type,
+            content,
+            redirects,
+        })
+    }
+
+    /// Follows redirects and writes the response to `file`. It's written next to it first, so
+    /// an interrupted download never leaves a partial file behind under the final name.
+    pub async fn download(
+        &self,
+        url: &str,
+        credentials: Option<&FeedCredentials>,
+        cancel: &CancellationToken,
+        file: &Path,
+        progress: impl Fn(u64, Option<u64>),
+    ) -> RustcastResult<()> {
+        let (mut response, _, _) = self.follow_redirects(url, credentials, cancel).await?;
+        let total = response.content_length();
+
+        let mut partial = file.as_os_str().to_owned();
+        partial.push(".part");
+        let partial = PathBuf::from(partial);
+        let mut out = tokio::fs::File::create(&partial).await.map_err(download_io)?;
+
+        let mut received = 0;
+        let mut reported = 0;
+        let written: RustcastResult<()> = async {
+            loop {
+                let chunk = tokio::select! {
+                    _ = cancel.cancelled() => return Err(RustcastError::Network(NetworkError::Cancelled)),
+                    chunk = response.chunk() => chunk.map_err(body_error)?,
+                };
+                let Some(chunk) = chunk else {
+                    break;
+                };
+
+                out.write_all(&chunk).await.map_err(download_io)?;
+                received += chunk.len() as u64;
+                if received - reported >= PROGRESS_STEP {
+                    reported = received;
+                    progress(received, total);
+                }
+            }
+            out.flush().await.map_err(download_io)
+        }.await;
+
+        if let Err(e) = written {
+            let _ = tokio::fs::remove_file(&partial).await;
+            return Err(e);
+        }
+        progress(received, total);
+        tokio::fs::rename(&partial, file).await.map_err(download_io)
+    }
+
+    /// Follows redirects to the media and returns its content type without downloading it,
+    /// failing the way the player would if the server won't serve it
+    pub async fn probe(
+        &self,
+        url: &str,
+        credentials: Option<&FeedCredentials>,
+        cancel: &CancellationToken,
+    ) -> RustcastResult<Option<String>> {
+        let (response, _, _) = self.follow_redirects(url, credentials, cancel).await?;
+        Ok(response.headers().get(reqwest::header::CONTENT_TYPE)
+            .and_then(|t| t.to_str().ok())
+            .and_then(|t| t.split(';').next())
+            .map(|t| t.trim().to_string()))
+    }
+
+    /// Requests `url` and follows its redirects, returning the final response, its URL and
+    /// the redirects on the way
+    async fn follow_redirects(
+        &self,
+        url: &str,
+        credentials: Option<&FeedCredentials>,
+        cancel: &CancellationToken,
+    ) -> RustcastResult<(reqwest::Response, String, Vec<Redirect>)> {
+        let mut current = url.to_string();
+        let mut redirects = Vec::new();
+        let configured = Url::parse(url).map_err(|_| RustcastError::network_invalid_url(url))?;
+
+        loop {
+            let forward = Url::parse(&current).is_ok_and(|target| forwards_credentials(&configured, &target));
+            let response = self.get(&current, credentials.filter(|_| forward), cancel).await?;
+            let location = response.headers().get(reqwest::header::LOCATION)
+                .and_then(|l| l.to_str().ok())
+                .map(|l| l.to_string());
+
+            match (response.status().as_u16(), location) {
+                (status @ (301 | 302 | 303 | 307 | 308), Some(location)) => {
+                    if redirects.len() >= MAX_REDIRECTS {
+                        return Err(RustcastError::Network(NetworkError::RequestFailed(
+                            "Too many redirects".to_string()
+                        )));
+                    }
+
+                    let next = Url::parse(&current)
+                        .and_then(|base| base.join(&location))
+                        .map_err(|_| RustcastError::network_invalid_url(&location))?
+                        .to_string();
+
+                    log::debug!("Redirect {} {} -> {}", status, current, next);
+                    redirects.push(Redirect {
+                        from: current,
+                        to: next.clone(),
+                        status,
+                    });
+                    current = next;
+                }
+                _ => return Ok((response, current, redirects)),
+            }
+        }
+    }
+
+    /// GET with query parameters and extra headers, decoding the body as JSON
+    pub async fn get_json<T: serde::de::DeserializeOwned>(
+        &self,
+        url: &str,
+        query: &[(&str, &str)],
+        headers: &[(&str, &str)],
+        cancel: &CancellationToken,
+    ) -> RustcastResult<T> {
+        let mut request = self.client.get(url).query(query);
+        for (name, value) in headers {
+            request = request.header(*name, *value);
+        }
+
+        let response = self.send(request, cancel).await?.error_for_status()?;
+        let content = self.read_text(response, cancel).await?;
+
+        serde_json::from_str(&content)
+            .map_err(|e| RustcastError::Network(NetworkError::InvalidResponse(e.to_string())))
+    }
+
+    async fn send(&self, request: reqwest::RequestBuilder, cancel: &CancellationToken) -> RustcastResult<reqwest::Response> {
+        tokio::select! {
+            _ = cancel.cancelled() => Err(RustcastError::Network(NetworkError::Cancelled)),
+            response = request.timeout(self.timeout).send() => Ok(response?),
+        }
+    }
+
+    async fn read_body(
+        &self,
+        mut response: reqwest::Response,
+        cancel: &CancellationToken,
+        progress: impl Fn(u64, Option<u64>),
+    ) -> RustcastResult<String> {
+        let total = response.content_length();
+        let mut body = Vec::with_capacity(total.unwrap_or_default().min(16 * 1024 * 1024) as usize);
+        let mut reported = 0;
+
+        loop {
+            let chunk = tokio::select! {
+                _ = cancel.cancelled() => return Err(RustcastError::Network(NetworkError::Cancelled)),
+                chunk = response.chunk() => chunk.map_err(body_error)?,
+            };
+            let Some(chunk) = chunk else {
+                break;
+            };
+
+            body.extend_from_slice(&chunk);
+            if body.len() as u64 - reported >= PROGRESS_STEP {
+                reported = body.len() as u64;
+                progress(reported, total);
+            }
+        }
+        progress(body.len() as u64, total);
+
+        Ok(String::from_utf8_lossy(&body).into_owned())
+    }
+
+    async fn read_text(&self, response: reqwest::Response, cancel: &CancellationToken) -> RustcastResult<String> {
+        tokio::select! {
+            _ = cancel.cancelled() => Err(RustcastError::Network(NetworkError::Cancelled)),
+            content = response.text() => content.map_err(body_error),
+        }
+    }
+}
+
+/// Timeouts while reading the body stay timeouts, anything else means the response was broken
+fn body_error(err: reqwest::Error) -> RustcastError {
+    match RustcastError::from(err) {
+        RustcastError::Network(NetworkError::RequestFailed(msg)) =>
+            RustcastError::Network(NetworkError::InvalidResponse(msg)),
+        other => other,
+    }
+}
+
+fn download_io(err: std::io::Error) -> RustcastError {
+    RustcastError::Download(DownloadError::Io(err.to_string()))
+}
+
+/// Credentials are only sent to the origin they were configured for, or to the same host when
+/// a redirect moves it from http to https. A downgrade to plain http never gets them.
+pub fn forwards_credentials(configured: &Url, target: &Url) -> bool {
+    if configured.origin() == target.origin() {
+        return true;
+    }
+    configured.scheme() == "http"
+        && target.scheme() == "https"
+        && configured.host() == target.host()
+        && configured.port().is_none()
+        && target.port().is_none()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_server::{self, header};
+    use super::*;
+
+    fn url(url: &str) -> Url {
+        Url::parse(url).unwrap()
+    }
+
+    #[test]
+    fn credentials_stay_with_their_origin() {
+        let configured = url("https://feeds.example.com/private.xml");
+
+        assert!(forwards_credentials(&configured, &url("https://feeds.example.com/moved.xml")));
+        assert!(forwards_credentials(&configured, &url("https://feeds.example.com:443/moved.xml")));
+        assert!(!forwards_credentials(&configured, &url("https://cdn.example.com/private.xml")));
+        assert!(!forwards_credentials(&configured, &url("https://feeds.example.com:8443/private.xml")));
+        assert!(!forwards_credentials(&configured, &url("http://feeds.example.com/private.xml")));
+    }
+
+    #[test]
+    fn credentials_follow_an_upgrade_to_https() {
+        let configured = url("http://feeds.example.com/private.xml");
+
+        assert!(forwards_credentials(&configured, &url("https://feeds.example.com/private.xml")));
+        assert!(!forwards_credentials(&configured, &url("https://feeds.example.com:8443/private.xml")));
+        assert!(!forwards_credentials(&url("http://feeds.example.com:8080/"), &url("https://feeds.example.com/")));
+    }
+
+    #[tokio::test]
+    async fn redirect_to_another_port_drops_credentials() {
+        let (target_url, target) = test_server::serve_body("application/rss+xml", "<rss/>").await;
+        let (start_url, start) = test_server::serve_once(format!(
+            "HTTP/1.1 302 Found\r\nLocation: {}/feed.xml\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+            target_url
+        )).await;
+        let credentials = FeedCredentials {
+            username: "user".to_string(),
+            password: "secret".to_string(),
+            headers: vec![("X-Token".to_string(), "token".to_string())],
+        };
+
+        let client = HttpClient::new(Duration::from_secs(5), "Rustcast tests").unwrap();
+        let document = client.fetch_document(
+            &format!("{}/private.xml", start_url),
+            Some(&credentials),
+            &CancellationToken::new(),
+            |_, _| {},
+        ).await.unwrap();
+
+        let first = start.await.unwrap();
+        assert!(header(&first, "Authorization").is_some());
+        assert_eq!(header(&first, "X-Token"), Some("token"));
+
+        // Same host, different port: a different origin
+        let second = target.await.unwrap();
+        assert_eq!(header(&second, "Authorization"), None);
+        assert_eq!(header(&second, "X-Token"), None);
+
+        assert_eq!(document.content, "<rss/>");
+        assert_eq!(document.redirects.len(), 1);
+    }
+}
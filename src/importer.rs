@@ -0,0 +1,291 @@
+use sea_orm::{ConnectionTrait, Database, DatabaseConnection, DbBackend, QueryResult, Statement};
+use crate::entity::episode;
+use crate::error::{DatabaseError, RustcastError, RustcastResult};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ImportSource {
+    AntennaPod,
+    GPodder,
+}
+
+impl ImportSource {
+    pub const ALL: [ImportSource; 2] = [ImportSource::AntennaPod, ImportSource::GPodder];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ImportSource::AntennaPod => "AntennaPod (database export)",
+            ImportSource::GPodder => "gPodder (Database file)",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct ImportedPodcast {
+    pub title: String,
+    pub feed_url: String,
+    pub description: String,
+    pub episodes: Vec<ImportedEpisode>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct ImportedEpisode {
+    pub title: String,
+    pub guid: Option<String>,
+    pub enclosure_url: Option<String>,
+    /// Resume position in seconds
+    pub position: f64,
+    pub finished: bool,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct UnmatchedItem {
+    pub podcast_title: String,
+    pub episode_title: Option<String>,
+    pub reason: String,
+}
+
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct ImportReport {
+    pub podcasts_created: usize,
+    pub podcasts_existing: usize,
+    pub episodes_imported: usize,
+    pub unmatched: Vec<UnmatchedItem>,
+}
+
+pub async fn read_history(source: ImportSource, path: &str) -> RustcastResult<Vec<ImportedPodcast>> {
+    if !std::path::Path::new(path).is_file() {
+        return Err(RustcastError::Database(DatabaseError::DataNotFound(
+            format!("Import file not found: {}", path)
+        )));
+    }
+
+    let db = Database::connect(format!("sqlite://{}?mode=ro", path)).await
+        .map_err(|e| RustcastError::Database(DatabaseError::ConnectionFailed(e.to_string())))?;
+
+    let podcasts = match source {
+        ImportSource::AntennaPod => read_antennapod(&db).await,
+        ImportSource::GPodder => read_gpodder(&db).await,
+    };
+
+    let _ = db.close().await;
+    podcasts
+}
+
+async fn read_antennapod(db: &DatabaseConnection) -> RustcastResult<Vec<ImportedPodcast>> {
+    let feeds = db.query_all(Statement::from_string(
+        DbBackend::Sqlite,
+        "SELECT id, title, download_url, description FROM Feeds",
+    )).await?;
+
+    // `read` is -1 for new, 0 for unplayed and 1 for played; positions are in milliseconds
+    let items = db.query_all(Statement::from_string(
+        DbBackend::Sqlite,
+        "SELECT i.feed AS feed_id, i.title AS title, i.item_identifier AS guid, i.read AS read, \
+                m.download_url AS enclosure_url, m.position AS position \
+         FROM FeedItems i LEFT JOIN FeedMedia m ON m.feeditem = i.id \
+         WHERE i.read = 1 OR m.position > 0",
+    )).await?;
+
+    let mut podcasts = Vec::new();
+    for feed in feeds {
+        let feed_id: i64 = feed.try_get("", "id")?;
+        let episodes = items.iter()
+            .filter(|item| item.try_get::<i64>("", "feed_id").ok() == Some(feed_id))
+            .map(|item| {
+                let read: i64 = item.try_get::<Option<i64>>("", "read").ok().flatten().unwrap_or(0);
+                let position_ms: i64 = item.try_get::<Option<i64>>("", "position").ok().flatten().unwrap_or(0);
+                ImportedEpisode {
+                    title: optional_string(item, "title").unwrap_or_default(),
+                    guid: optional_string(item, "guid"),
+                    enclosure_url: optional_string(item, "enclosure_url"),
+                    position: position_ms as f64 / 1000.0,
+                    finished: read == 1,
+                }
+            })
+            .collect();
+
+        if let Some(podcast) = imported_podcast(&feed, "download_url", episodes) {
+            podcasts.push(podcast);
+        }
+    }
+
+    Ok(podcasts)
+}
+
+async fn read_gpodder(db: &DatabaseConnection) -> RustcastResult<Vec<ImportedPodcast>> {
+    let feeds = db.query_all(Statement::from_string(
+        DbBackend::Sqlite,
+        "SELECT id, title, url, description FROM podcast",
+    )).await?;
+
+    // Positions are in seconds, and gPodder counts an episode as played once its position
+    // reaches its length. `is_new` is cleared by marking an episode as old as well, so it says
+    // nothing about whether it was heard.
+    let items = db.query_all(Statement::from_string(
+        DbBackend::Sqlite,
+        "SELECT podcast_id, title, guid, url AS enclosure_url, current_position, total_time \
+         FROM episode WHERE current_position > 0",
+    )).await?;
+
+    let mut podcasts = Vec::new();
+    for feed in feeds {
+        let feed_id: i64 = feed.try_get("", "id")?;
+        let episodes = items.iter()
+            .filter(|item| item.try_get::<i64>("", "podcast_id").ok() == Some(feed_id))
+            .map(|item| {
+                let position: i64 = item.try_get::<Option<i64>>("", "current_position").ok().flatten().unwrap_or(0);
+                let total: i64 = item.try_get::<Option<i64>>("", "total_time").ok().flatten().unwrap_or(0);
+                ImportedEpisode {
+                    title: optional_string(item, "title").unwrap_or_default(),
+                    guid: optional_string(item, "guid"),
+                    enclosure_url: optional_string(item, "enclosure_url"),
+                    position: position as f64,
+                    finished: total > 0 && position >= total,
+                }
+            })
+            .collect();
+
+        if let Some(podcast) = imported_podcast(&feed, "url", episodes) {
+            podcasts.push(podcast);
+        }
+    }
+
+    Ok(podcasts)
+}
+
+fn imported_podcast(feed: &QueryResult, url_column: &str, episodes: Vec<ImportedEpisode>) -> Option<ImportedPodcast> {
+    let feed_url = optional_string(feed, url_column)?;
+    // Local folders and other non-network sources can't be subscribed to
+    if !feed_url.starts_with("http://") && !feed_url.starts_with("https://") {
+        log::warn!("Skipping non-HTTP feed during import: {}", feed_url);
+        return None;
+    }
+
+    Some(ImportedPodcast {
+        title: optional_string(feed, "title").unwrap_or_else(|| feed_url.clone()),
+        description: optional_string(feed, "description")
+            .map(|d| d.chars().take(2000).collect())
+            .unwrap_or_default(),
+        feed_url,
+        episodes,
+    })
+}
+
+fn optional_string(row: &QueryResult, column: &str) -> Option<String> {
+    row.try_get::<Option<String>>("", column)
+        .ok()
+        .flatten()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+/// Finds the local episode for an imported one, by GUID first and then by enclosure URL
+pub fn match_episode<'a>(imported: &ImportedEpisode, episodes: &'a [episode::Model]) -> Option<&'a episode::Model> {
+    imported.guid.as_ref()
+        .and_then(|guid| episodes.iter().find(|e| e.guid.as_ref() == Some(guid)))
+        .or_else(|| imported.enclosure_url.as_ref()
+            .and_then(|url| episodes.iter().find(|e| e.link.as_ref() == Some(url))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> String {
+        format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name)
+    }
+
+    fn episode(id: i32, guid: Option<&str>, link: Option<&str>) -> episode::Model {
+        episode::Model {
+            id,
+            podcast_id: 1,
+            title: Some(format!("Episode {}", id)),
+            link: link.map(str::to_string),
+            description: None,
+            guid: guid.map(str::to_string),
+            pub_date: None,
+            duration: None,
+            first_seen: None,
+            triaged: true,
+            image_url: None,
+            enclosure_type: None,
+            enclosures: None,
+        }
+    }
+
+    fn imported(guid: Option<&str>, enclosure_url: Option<&str>) -> ImportedEpisode {
+        ImportedEpisode {
+            title: "Imported".to_string(),
+            guid: guid.map(str::to_string),
+            enclosure_url: enclosure_url.map(str::to_string),
+            position: 0.0,
+            finished: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn reads_antennapod_history() {
+        let podcasts = read_history(ImportSource::AntennaPod, &fixture("antennapod.db")).await.unwrap();
+
+        // The local folder feed is skipped
+        assert_eq!(podcasts.len(), 1);
+        let podcast = &podcasts[0];
+        assert_eq!(podcast.title, "Rust Talk");
+        assert_eq!(podcast.feed_url, "https://example.com/rust.xml");
+        assert_eq!(podcast.description, "All about Rust");
+
+        // Untouched episodes carry nothing to import
+        assert_eq!(podcast.episodes.len(), 2);
+        let played = podcast.episodes.iter().find(|e| e.title == "Played episode").unwrap();
+        assert!(played.finished);
+        assert_eq!(played.guid.as_deref(), Some("guid-1"));
+        assert_eq!(played.enclosure_url.as_deref(), Some("https://example.com/1.mp3"));
+        let started = podcast.episodes.iter().find(|e| e.title == "Half way").unwrap();
+        assert!(!started.finished);
+        assert_eq!(started.position, 90.5);
+    }
+
+    #[tokio::test]
+    async fn reads_gpodder_history() {
+        let podcasts = read_history(ImportSource::GPodder, &fixture("gpodder.db")).await.unwrap();
+
+        assert_eq!(podcasts.len(), 1);
+        let podcast = &podcasts[0];
+        assert_eq!(podcast.title, "Linux Weekly");
+        assert_eq!(podcast.feed_url, "https://example.org/linux.rss");
+
+        let titles: Vec<&str> = podcast.episodes.iter().map(|e| e.title.as_str()).collect();
+        assert_eq!(titles, ["Finished", "Started"]);
+        assert!(podcast.episodes[0].finished);
+        assert_eq!(podcast.episodes[0].position, 1800.0);
+        assert!(!podcast.episodes[1].finished);
+        assert_eq!(podcast.episodes[1].position, 600.0);
+    }
+
+    #[tokio::test]
+    async fn missing_file_is_reported() {
+        let result = read_history(ImportSource::GPodder, &fixture("missing.db")).await;
+        assert!(matches!(result, Err(RustcastError::Database(DatabaseError::DataNotFound(_)))));
+    }
+
+    #[test]
+    fn matches_by_guid_before_enclosure() {
+        let episodes = [
+            episode(1, Some("a"), Some("https://example.com/1.mp3")),
+            episode(2, Some("b"), Some("https://example.com/2.mp3")),
+        ];
+
+        let by_guid = imported(Some("b"), Some("https://example.com/1.mp3"));
+        assert_eq!(match_episode(&by_guid, &episodes).map(|e| e.id), Some(2));
+
+        let by_url = imported(Some("unknown"), Some("https://example.com/1.mp3"));
+        assert_eq!(match_episode(&by_url, &episodes).map(|e| e.id), Some(1));
+
+        let no_guid = imported(None, Some("https://example.com/2.mp3"));
+        assert_eq!(match_episode(&no_guid, &episodes).map(|e| e.id), Some(2));
+
+        let unmatched = imported(Some("unknown"), Some("https://example.com/3.mp3"));
+        assert_eq!(match_episode(&unmatched, &episodes), None);
+        assert_eq!(match_episode(&imported(None, None), &episodes), None);
+    }
+}
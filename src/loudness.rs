@@ -0,0 +1,429 @@
+use crate::error::{DatabaseError, RustcastError, RustcastResult};
+
+/// Loudness most podcast platforms normalize to
+pub const DEFAULT_TARGET_LUFS: f64 = -16.0;
+pub const MIN_TARGET_LUFS: f64 = -30.0;
+pub const MAX_TARGET_LUFS: f64 = -10.0;
+/// Range of a podcast's own volume offset
+pub const MAX_PODCAST_GAIN_DB: i32 = 12;
+/// Most a quiet recording is raised, so its noise floor doesn't come up with it
+const MAX_NORMALIZATION_GAIN_DB: f64 = 12.0;
+/// Highest sample peak after normalization, in dBFS
+const PEAK_CEILING_DB: f64 = -1.0;
+
+const BLOCK_SECONDS: f64 = 0.4;
+/// Blocks overlap by 75%, so a new one starts every 100 ms
+const BLOCK_STEPS: usize = 4;
+const ABSOLUTE_GATE_LUFS: f64 = -70.0;
+const RELATIVE_GATE_LU: f64 = -10.0;
+
+pub fn db_to_gain(db: f64) -> f64 {
+    10f64.powf(db / 20.0)
+}
+
+fn gain_to_db(gain: f64) -> f64 {
+    20.0 * gain.log10()
+}
+
+pub fn validate_podcast_gain(gain_db: i32) -> RustcastResult<()> {
+    if gain_db.abs() > MAX_PODCAST_GAIN_DB {
+        return Err(RustcastError::Database(DatabaseError::ConstraintViolation(
+            format!("The volume offset must be between -{0} and +{0} dB.", MAX_PODCAST_GAIN_DB)
+        )));
+    }
+    Ok(())
+}
+
+/// Second-order IIR filter, transposed direct form II
+#[derive(Debug, Clone, Copy)]
+struct Biquad {
+    b: [f64; 3],
+    a: [f64; 2],
+    z: [f64; 2],
+}
+
+impl Biquad {
+    fn process(&mut self, x: f64) -> f64 {
+        let y = self.b[0] * x + self.z[0];
+        self.z[0] = self.b[1] * x - self.a[0] * y + self.z[1];
+        self.z[1] = self.b[2] * x - self.a[1] * y;
+        y
+    }
+}
+
+/// The two stages of the BS.1770 K-weighting filter, a high shelf for the head's acoustic
+/// effect and a high pass, recomputed for sample rates other than 48 kHz
+fn k_weighting(rate: u32) -> [Biquad; 2] {
+    let rate = rate as f64;
+
+    let k = (std::f64::consts::PI * 1681.974450955533 / rate).tan();
+    let q = 0.7071752369554196;
+    let vh = db_to_gain(3.999843853973347);
+    let vb = vh.powf(0.4996667741545416);
+    let a0 = 1.0 + k / q + k * k;
+    let shelf = Biquad {
+        b: [(vh + vb * k / q + k * k) / a0, 2.0 * (k * k - vh) / a0, (vh - vb * k / q + k * k) / a0],
+        a: [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        z: [0.0; 2],
+    };
+
+    let k = (std::f64::consts::PI * 38.13547087602444 / rate).tan();
+    let q = 0.5003270373238773;
+    let a0 = 1.0 + k / q + k * k;
+    let high_pass = Biquad {
+        b: [1.0, -2.0, 1.0],
+        a: [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        z: [0.0; 2],
+    };
+
+    [shelf, high_pass]
+}
+
+fn power_to_lufs(power: f64) -> f64 {
+    -0.691 + 10.0 * power.log10()
+}
+
+/// Measures loudness in LUFS following EBU R128: K-weighted, in gated 400 ms blocks. Samples
+/// are fed as they're decoded, so it works on a stream as well as on a whole file.
+pub struct LoudnessMeter {
+    channels: usize,
+    filters: Vec<[Biquad; 2]>,
+    step_frames: usize,
+    /// Frames and summed energy of the 100 ms step being filled
+    frames: usize,
+    energy: f64,
+    /// Mean square of the last steps, which make up the current block
+    steps: Vec<f64>,
+    /// Power of every complete block
+    blocks: Vec<f64>,
+    peak: f64,
+}
+
+impl LoudnessMeter {
+    pub fn new(rate: u32, channels: usize) -> Self {
+        let channels = channels.max(1);
+        LoudnessMeter {
+            channels,
+            filters: vec![k_weighting(rate); channels],
+            step_frames: ((rate as f64 * BLOCK_SECONDS) as usize / BLOCK_STEPS).max(1),
+            frames: 0,
+            energy: 0.0,
+            steps: Vec::with_capacity(BLOCK_STEPS),
+            blocks: Vec::new(),
+            peak: 0.0,
+        }
+    }
+
+    /// Interleaved samples scaled to -1.0..=1.0
+    pub fn push(&mut self, samples: &[f32]) {
+        for frame in samples.chunks_exact(self.channels) {
+            for (sample, filters) in frame.iter().zip(&mut self.filters) {
+                let sample = *sample as f64;
+                self.peak = self.peak.max(sample.abs());
+                let shelved = filters[0].process(sample);
+                let weighted = filters[1].process(shelved);
+                self.energy += weighted * weighted;
+            }
+
+            self.frames += 1;
+            if self.frames == self.step_frames {
+                if self.steps.len() == BLOCK_STEPS {
+                    self.steps.remove(0);
+                }
+                self.steps.push(self.energy / self.step_frames as f64);
+                if self.steps.len() == BLOCK_STEPS {
+                    self.blocks.push(self.steps.iter().sum::<f64>() / BLOCK_STEPS as f64);
+                }
+                self.frames = 0;
+                self.energy = 0.0;
+            }
+        }
+    }
+
+    /// Loudness of everything pushed so far, `None` until there's a block above silence
+    pub fn integrated(&self) -> Option<f64> {
+        let audible: Vec<f64> = self.blocks.iter().copied()
+            .filter(|power| power_to_lufs(*power) > ABSOLUTE_GATE_LUFS)
+            .collect();
+        if audible.is_empty() {
+            return None;
+        }
+        let relative_gate = power_to_lufs(audible.iter().sum::<f64>() / audible.len() as f64) + RELATIVE_GATE_LU;
+
+        let gated: Vec<f64> = audible.into_iter()
+            .filter(|power| power_to_lufs(*power) > relative_gate)
+            .collect();
+        Some(power_to_lufs(gated.iter().sum::<f64>() / gated.len() as f64))
+    }
+
+    /// Highest absolute sample value so far
+    pub fn peak(&self) -> f64 {
+        self.peak
+    }
+}
+
+/// Gain in dB that brings audio measured by `meter` to `target` LUFS, bounded so quiet audio
+/// isn't raised without limit and the loudest sample stays under the ceiling
+pub fn normalization_gain_db(meter: &LoudnessMeter, target: f64) -> f64 {
+    let Some(loudness) = meter.integrated() else {
+        return 0.0;
+    };
+    let mut gain = (target - loudness).min(MAX_NORMALIZATION_GAIN_DB);
+    if meter.peak() > 0.0 {
+        gain = gain.min(PEAK_CEILING_DB - gain_to_db(meter.peak()));
+    }
+    gain
+}
+
+/// How often the normalization gain catches up with the loudness measured so far
+const NORMALIZATION_UPDATE_SECONDS: f64 = 0.5;
+/// Time constant gain changes are smoothed with, so moving the volume doesn't click
+const GAIN_SMOOTHING_SECONDS: f64 = 0.05;
+
+/// The gain playback goes through: the listener's volume, the podcast's offset and, while
+/// normalizing, what brings the episode as heard so far to the target loudness. Works on
+/// interleaved samples scaled to -1.0..=1.0, measuring them before any gain.
+pub struct GainStage {
+    channels: usize,
+    rate: u32,
+    meter: LoudnessMeter,
+    volume: f32,
+    podcast_gain_db: f64,
+    target: Option<f64>,
+    normalization_db: f64,
+    /// Frames since the normalization gain was last updated
+    frames: usize,
+    update_frames: usize,
+    /// Linear gain of the last frame, following `gain()`
+    current: f32,
+    smoothing: f32,
+}
+
+impl GainStage {
+    pub fn new(rate: u32, channels: usize) -> Self {
+        let channels = channels.max(1);
+        GainStage {
+            channels,
+            rate,
+            meter: LoudnessMeter::new(rate, channels),
+            volume: 1.0,
+            podcast_gain_db: 0.0,
+            target: None,
+            normalization_db: 0.0,
+            frames: 0,
+            update_frames: (rate as f64 * NORMALIZATION_UPDATE_SECONDS) as usize,
+            current: 1.0,
+            smoothing: 1.0 - (-1.0 / (rate as f64 * GAIN_SMOOTHING_SECONDS)).exp() as f32,
+        }
+    }
+
+    /// Starts measuring over for another episode
+    pub fn reset(&mut self) {
+        self.meter = LoudnessMeter::new(self.rate, self.channels);
+        self.normalization_db = 0.0;
+        self.frames = 0;
+        self.current = self.gain();
+    }
+
+    /// Linear volume, above 1.0 for a boost
+    pub fn set_volume(&mut self, volume: f32) {
+        self.volume = volume.max(0.0);
+    }
+
+    /// The podcast's offset in dB, and the loudness to normalize to in LUFS if any
+    pub fn set_loudness(&mut self, podcast_gain_db: f64, target: Option<f64>) {
+        self.podcast_gain_db = podcast_gain_db;
+        self.target = target;
+    }
+
+    /// Linear gain the samples are brought to
+    pub fn gain(&self) -> f32 {
+        let normalization_db = if self.target.is_some() { self.normalization_db } else { 0.0 };
+        self.volume * db_to_gain(self.podcast_gain_db + normalization_db) as f32
+    }
+
+    pub fn process(&mut self, samples: &mut [f32]) {
+        self.meter.push(samples);
+        for frame in samples.chunks_exact_mut(self.channels) {
+            self.frames += 1;
+            if self.frames >= self.update_frames {
+                self.frames = 0;
+                if let Some(target) = self.target {
+                    self.normalization_db = normalization_gain_db(&self.meter, target);
+                }
+            }
+
+            self.current += (self.gain() - self.current) * self.smoothing;
+            for sample in frame {
+                *sample = (*sample * self.current).clamp(-1.0, 1.0);
+            }
+        }
+    }
+}
+
+/// Scales 16-bit samples, clipping anything pushed past full scale
+pub fn apply_gain(samples: &mut [i16], gain_db: f64) {
+    if gain_db == 0.0 {
+        return;
+    }
+    let gain = db_to_gain(gain_db);
+    for sample in samples {
+        *sample = (*sample as f64 * gain).round().clamp(i16::MIN as f64, i16::MAX as f64) as i16;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 48_000;
+
+    /// Three seconds of a 997 Hz sine, the BS.1770 reference tone, peaking at `amplitude`
+    fn sine(amplitude: f32) -> Vec<f32> {
+        (0..RATE as usize * 3)
+            .map(|i| amplitude * (2.0 * std::f32::consts::PI * 997.0 * i as f32 / RATE as f32).sin())
+            .collect()
+    }
+
+    fn measure(samples: &[f32]) -> LoudnessMeter {
+        let mut meter = LoudnessMeter::new(RATE, 1);
+        meter.push(samples);
+        meter
+    }
+
+    #[test]
+    fn measures_the_reference_tone() {
+        // A full-scale 997 Hz sine in one channel reads -3.01 LUFS, and every 20 dB less
+        // amplitude takes 20 LU off
+        let full = measure(&sine(1.0)).integrated().unwrap();
+        assert!((full - -3.01).abs() < 0.1, "measured {}", full);
+        let quieter = measure(&sine(0.1)).integrated().unwrap();
+        assert!((quieter - -23.01).abs() < 0.1, "measured {}", quieter);
+    }
+
+    #[test]
+    fn stereo_channels_add_up() {
+        let interleaved: Vec<f32> = sine(0.1).into_iter().flat_map(|sample| [sample, sample]).collect();
+        let mut meter = LoudnessMeter::new(RATE, 2);
+        meter.push(&interleaved);
+
+        let loudness = meter.integrated().unwrap();
+        assert!((loudness - -20.0).abs() < 0.1, "measured {}", loudness);
+    }
+
+    #[test]
+    fn silence_has_no_loudness() {
+        let meter = measure(&vec![0.0; RATE as usize * 3]);
+        assert_eq!(meter.integrated(), None);
+        assert_eq!(normalization_gain_db(&meter, DEFAULT_TARGET_LUFS), 0.0);
+    }
+
+    #[test]
+    fn normalizes_to_the_target() {
+        let gain = normalization_gain_db(&measure(&sine(0.1)), DEFAULT_TARGET_LUFS);
+        assert!((gain - 7.01).abs() < 0.1, "gain {}", gain);
+
+        let gain = normalization_gain_db(&measure(&sine(1.0)), DEFAULT_TARGET_LUFS);
+        assert!((gain - -12.99).abs() < 0.1, "gain {}", gain);
+    }
+
+    #[test]
+    fn quiet_audio_is_raised_at_most_12_db() {
+        let gain = normalization_gain_db(&measure(&sine(0.01)), DEFAULT_TARGET_LUFS);
+        assert_eq!(gain, MAX_NORMALIZATION_GAIN_DB);
+    }
+
+    #[test]
+    fn peaks_stay_under_the_ceiling() {
+        // Quiet speech with a single full-scale click can't be raised at all
+        let mut samples = sine(0.05);
+        samples[RATE as usize] = 1.0;
+
+        let gain = normalization_gain_db(&measure(&samples), DEFAULT_TARGET_LUFS);
+        assert!((gain - PEAK_CEILING_DB).abs() < 1e-9, "gain {}", gain);
+    }
+
+    #[test]
+    fn gain_clips_at_full_scale() {
+        let mut samples = [1000, -1000, 20_000, -20_000, i16::MAX, i16::MIN];
+        apply_gain(&mut samples, 6.0);
+        assert_eq!(samples, [1995, -1995, i16::MAX, i16::MIN, i16::MAX, i16::MIN]);
+
+        let mut samples = [1000, -1000, i16::MIN];
+        apply_gain(&mut samples, -6.0);
+        assert_eq!(samples, [501, -501, -16423]);
+
+        let mut samples = [1234, i16::MIN];
+        apply_gain(&mut samples, 0.0);
+        assert_eq!(samples, [1234, i16::MIN]);
+    }
+
+    fn peak(samples: &[f32]) -> f32 {
+        samples.iter().fold(0.0, |peak, sample| peak.max(sample.abs()))
+    }
+
+    #[test]
+    fn gain_stage_applies_volume_and_podcast_offset() {
+        let mut stage = GainStage::new(RATE, 1);
+        stage.set_volume(0.5);
+        stage.set_loudness(6.0, None);
+        stage.reset();
+
+        let mut samples = sine(0.1);
+        stage.process(&mut samples);
+        let expected = 0.1 * 0.5 * db_to_gain(6.0) as f32;
+        assert!((peak(&samples) - expected).abs() < 1e-4, "peak {}", peak(&samples));
+    }
+
+    #[test]
+    fn gain_stage_clips_at_full_scale() {
+        let mut stage = GainStage::new(RATE, 1);
+        stage.set_volume(2.0);
+        stage.reset();
+
+        let mut samples = sine(0.8);
+        stage.process(&mut samples);
+        assert_eq!(peak(&samples), 1.0);
+    }
+
+    #[test]
+    fn gain_stage_normalizes_as_it_listens() {
+        let mut stage = GainStage::new(RATE, 1);
+        stage.set_loudness(0.0, Some(DEFAULT_TARGET_LUFS));
+        stage.reset();
+
+        // -23 LUFS is raised by 7 dB once enough has been heard
+        let mut samples = sine(0.1);
+        stage.process(&mut samples);
+        assert!((peak(&samples[..RATE as usize / 4]) - 0.1).abs() < 1e-4);
+        let raised = peak(&samples[samples.len() - RATE as usize / 4..]);
+        assert!((raised - 0.1 * db_to_gain(7.01) as f32).abs() < 0.005, "peak {}", raised);
+
+        // Without a target the measured gain isn't applied
+        stage.set_loudness(0.0, None);
+        let mut samples = sine(0.1);
+        stage.process(&mut samples);
+        assert!((peak(&samples[samples.len() - RATE as usize / 4..]) - 0.1).abs() < 1e-4);
+    }
+
+    #[test]
+    fn gain_stage_smooths_volume_changes() {
+        let mut stage = GainStage::new(RATE, 1);
+        stage.reset();
+        stage.set_volume(0.0);
+
+        let mut samples = vec![0.5; RATE as usize];
+        stage.process(&mut samples);
+        assert!(samples[0] > 0.49, "first sample {}", samples[0]);
+        assert!(samples[RATE as usize / 100] > 0.0 && samples[RATE as usize / 100] < 0.5);
+        assert!(samples[RATE as usize - 1] < 1e-6);
+    }
+
+    #[test]
+    fn podcast_gain_is_bounded() {
+        assert!(validate_podcast_gain(MAX_PODCAST_GAIN_DB).is_ok());
+        assert!(validate_podcast_gain(-MAX_PODCAST_GAIN_DB).is_ok());
+        assert!(validate_podcast_gain(MAX_PODCAST_GAIN_DB + 1).is_err());
+        assert!(validate_podcast_gain(-MAX_PODCAST_GAIN_DB - 1).is_err());
+    }
+}
@@ -1,26 +1,66 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")] // hide console window on Windows in release
 #![allow(rustdoc::missing_crate_level_docs)] // it's an example
 
+mod artwork;
+mod audio;
+mod bookmarks;
+mod clip;
+mod config;
+mod credentials;
 mod data_provider;
+mod database;
+mod decode;
+mod directory;
+mod downloads;
+mod enclosure;
 mod entity;
 mod error;
+mod feed_discovery;
+mod history;
+mod http;
+mod importer;
+mod loudness;
+mod playback;
+mod player;
+mod playlist;
 mod podcasts_model;
+mod protocol;
+mod show_notes;
+mod silence;
+mod stream_proxy;
+#[cfg(test)]
+mod test_server;
 mod utils;
 mod traits;
+mod ui;
 
-use migrations;
-
+use config::Config;
+use credentials::{CredentialStore, FeedCredentials, SharedCredentials};
 use data_provider::DataProvider;
+use database::DatabaseLocation;
+use directory::DirectoryConfig;
 use eframe::egui;
 use egui_extras::{Column, TableBuilder};
-use entity::{episode, podcast};
-use error::{RustcastError, RustcastResult};
+use entity::episode;
+use error::{NetworkError, PlayerError, RssError, RustcastError, RustcastResult};
+use feed_discovery::FeedCandidate;
+use history::{ListeningStats, SessionTracker};
+use playback::{PlaybackControl, SavedPosition};
+use player::PlayerBackend;
+use silence::SilenceTrimmer;
+use http::HttpClient;
+use importer::{ImportReport, ImportSource, UnmatchedItem};
+use playlist::{EpisodeFacts, Playlist, PlaylistQuery};
 use log::{error, warn, info};
-use podcasts_model::PodcastsModel;
-use sea_orm::{Database, DatabaseConnection};
-use sea_orm_migration::prelude::*;
+use podcasts_model::{EpisodeDetails, PodcastsModel};
+use protocol::{
+    ActionContext, ActionSender, AsyncAction, AsyncActionResult, AsyncEvent, AsyncRequest, AsyncResponse,
+    DatabaseFailure, EpisodesRefresh, InFlight, PlaylistEpisodes, PodcastEdit, PodcastGroups, Progress,
+    RequestKind, TagEpisodes, TagGroup, UiWaker,
+};
+use stream_proxy::StreamProxy;
+use sea_orm::DatabaseConnection;
 use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
-use url2audio::Player;
 use egui_timeline_widget::Timeline;
 
 #[derive(Debug, PartialEq, Clone)]
@@ -33,173 +73,143 @@ pub enum PlayerAction {
 #[derive(Debug, PartialEq, Clone)]
 pub enum PlayerState {
     Open,
+    /// Opened, waiting for the media to start
+    Loading,
+    /// Playing, waiting for more of the stream
+    Buffering,
     Playing,
     Paused,
+    /// Played through to the end
+    Ended,
+    /// Stopped by a failure of the player
+    Error,
+}
+
+impl PlayerState {
+    /// Playback was asked for: it's playing or waiting for the stream
+    pub fn is_active(&self) -> bool {
+        matches!(self, PlayerState::Loading | PlayerState::Buffering | PlayerState::Playing)
+    }
+
+    /// Nothing plays and nothing is waiting to
+    pub fn is_stopped(&self) -> bool {
+        matches!(self, PlayerState::Paused | PlayerState::Ended | PlayerState::Error)
+    }
 }
 
 pub struct PlayerWrapper {
-    pub inner_player: Player,
+    pub inner_player: Box<dyn PlayerBackend>,
     pub player_state: PlayerState,
     pub seek_position: f64,
+    /// Playback rate the backend plays at; normal speed with backends that can't change it
+    pub speed: f64,
+    /// Linear volume, above 1.0 for a boost, for backends that can change it
+    pub volume: f32,
+    pub trimmer: SilenceTrimmer,
+    /// URL the player opened, to ask the stream relay about it
+    pub stream_url: Option<String>,
+    /// Times the open episode's stream was opened again after stalling
+    pub reconnects: u32,
+    /// The open episode is played from its downloaded copy after its stream failed
+    pub playing_download: bool,
+    pub sessions: SessionTracker,
+    pub control: PlaybackControl,
 }
 
-#[derive(Debug, PartialEq, Clone)]
-pub enum AsyncAction {
-    AddPodcast(String, String, String),
-    GetPodcasts,
-    GetEpisodes(String, i32),
-    SaveEpisodeState(f64, i32, String),
-    LoadEpisodeState(String),
-    GetAllEpisodeStates(i32)
-}
-
-#[derive(Debug, PartialEq, Clone)]
-pub enum AsyncActionResult {
-    PodcastsUpdate(Option<Vec<podcast::Model>>),
-    EpisodesUpdate(Option<Vec<episode::Model>>),
-    AddPodcastResult(Option<String>),
-    UniversalResult(Option<String>),
-    EpisodeStateUpdate(f64),
-    AllEpisodeStatesUpdate(Option<std::collections::HashMap<String, f64>>),
+const NOW_PLAYING_ARTWORK_SIZE: f32 = 32.0;
+const PLAYING_REPAINT_INTERVAL: std::time::Duration = std::time::Duration::from_millis(250);
+
+/// When the UI has to draw again without being woken. Results wake it on their own, so only
+/// the playback position needs a steady redraw, coarse enough that a few frames per second
+/// will do, and the next automatic refresh needs a frame to be sent from.
+fn repaint_after(player_state: &PlayerState, next_refresh: Option<std::time::Duration>) -> Option<std::time::Duration> {
+    let playing = player_state.is_active().then_some(PLAYING_REPAINT_INTERVAL);
+    match (playing, next_refresh) {
+        (Some(playing), Some(refresh)) => Some(playing.min(refresh)),
+        (playing, refresh) => playing.or(refresh),
+    }
 }
 
 #[tokio::main]
 async fn main() {
     env_logger::init();
 
-    let (async_action_tx, mut async_action_rx) = unbounded_channel::<AsyncAction>();
-    let (async_action_result_tx, async_action_result_rx) = unbounded_channel::<AsyncActionResult>();
+    let (async_action_tx, mut async_action_rx) = unbounded_channel::<AsyncRequest>();
+    let (async_action_result_tx, async_action_result_rx) = unbounded_channel::<AsyncResponse>();
+
+    let config_path = Config::path();
+    let (config, config_error) = match Config::load(&config_path) {
+        Ok(config) => (config, None),
+        Err(e) => {
+            error!("Failed to load {}, using the defaults: {}", config_path.display(), e);
+            (Config::default(), Some(format!("{}\n\nUsing the default settings for now.", e.user_friendly_message())))
+        }
+    };
+    if let Err(e) = config.prepare_data_dir() {
+        error!("Failed to set up the data directory {}: {}", config.data_dir.display(), e);
+    }
+
+    let credentials = CredentialStore::shared(config.credentials_path());
+    let stream_proxy = StreamProxy::start(credentials.clone())
+        .map_err(|e| error!("Failed to start the stream relay for private feeds: {}", e))
+        .ok();
 
+    let waker = UiWaker::default();
+    let async_waker = waker.clone();
+    let async_credentials = credentials.clone();
+    let async_config = config.clone();
     let async_action_thread = tokio::spawn(async move {
-        let home = std::env::var("HOME").unwrap();
-        let connection = std::env::var("DATABASE_URL")
-            .unwrap_or(format!("sqlite://{}/.rustcast.db?mode=rwc", home));
-        let db: DatabaseConnection = match Database::connect(&connection).await {
-            Ok(conn) => conn,
-            Err(e) => {
-                error!("Failed to connect to database: {}", e);
-                panic!("Database connection failed - application cannot continue");
-            }
+        let credentials = async_credentials;
+        let config = async_config;
+        let location = DatabaseLocation {
+            file: config.database_path(),
+            url_override: std::env::var("DATABASE_URL").ok(),
+            backup_dir: config.backup_dir(),
+        };
+        let Some(db) = open_database(location, &mut async_action_rx, &async_action_result_tx, &async_waker).await else {
+            return;
         };
-
-        // Run migrations automatically
-        info!("Running database migrations...");
-        if let Err(e) = migrations::Migrator::up(&db, None).await {
-            error!("Failed to run migrations: {}", e);
-            panic!("Database migration failed - application cannot continue");
-        }
-        info!("Database migrations completed successfully");
 
         let data_provider = DataProvider::new(db);
+        // Without an HTTP client the library still works; only the network actions fail
+        let http = HttpClient::new(config.http_timeout(), &config.user_agent).map_err(|e| {
+            error!("Failed to create HTTP client: {}", e);
+            http_client_failure(&e)
+        });
+        let network = NetworkContext {
+            http,
+            credentials,
+            directory_config: DirectoryConfig::from_config(&config),
+        };
         if let Err(e) = data_provider.get_podcasts().await {
             error!("Failed to initialize podcasts: {}", e);
         }
 
-        loop {
-            match async_action_rx.recv().await {
-                Some(AsyncAction::AddPodcast(title, link, description)) => {
-                    let title_clone = title.clone();
-                    match handle_add_podcast(&data_provider, title, link, description).await {
-                        Ok(_) => {
-                            info!("Successfully added podcast: {}", title_clone);
-                            // Send success signal or refresh podcasts
-                            let _ = async_action_result_tx.send(AsyncActionResult::AddPodcastResult(None));
-                        }
-                        Err(e) => {
-                            error!("Failed to add podcast '{}': {}", title_clone, e);
-                            let _ = async_action_result_tx.send(AsyncActionResult::AddPodcastResult(
-                                Some(e.user_friendly_message())
-                            ));
-                        }
-                    }
-                }
-                Some(AsyncAction::GetPodcasts) => match data_provider.get_podcasts().await {
-                    Ok(res) => {
-                        let _ = async_action_result_tx.send(AsyncActionResult::PodcastsUpdate(Some(res)));
-                    }
-                    Err(_) => {
-                        let _ = async_action_result_tx.send(AsyncActionResult::PodcastsUpdate(None));
-                    }
-                },
-                Some(AsyncAction::GetEpisodes(link, podcast_id)) => {
-                    match handle_get_episodes(&data_provider, &link, podcast_id).await {
-                        Ok(episodes) => {
-                            info!("Successfully loaded {} episodes for podcast {}", episodes.len(), podcast_id);
-                            let _ = async_action_result_tx.send(AsyncActionResult::EpisodesUpdate(Some(episodes)));
-                        }
-                        Err(e) => {
-                            error!("Failed to load episodes for podcast {}: {}", podcast_id, e);
-                            let _ = async_action_result_tx.send(AsyncActionResult::UniversalResult(
-                                Some(e.user_friendly_message())
-                            ));
-                            // Still try to load cached episodes from database
-                            match data_provider.get_all_episodes(podcast_id).await {
-                                Ok(cached_episodes) => {
-                                    warn!("Using cached episodes for podcast {}", podcast_id);
-                                    let _ = async_action_result_tx.send(AsyncActionResult::EpisodesUpdate(Some(cached_episodes)));
-                                }
-                                Err(db_err) => {
-                                    error!("Failed to load cached episodes: {}", db_err);
-                                    let _ = async_action_result_tx.send(AsyncActionResult::EpisodesUpdate(None));
-                                }
-                            }
-                        }
-                    }
-                }
-                Some(AsyncAction::SaveEpisodeState(progress, podcast_id, link)) => {
-                    match data_provider.upsert_episode_state(progress, podcast_id, &link).await {
-                        Ok(_) => {
-                            info!("Saved episode state: progress={:.1}s, podcast_id={}", progress, podcast_id);
-                        }
-                        Err(e) => {
-                            error!("Failed to save episode state: {}", e);
-                            let _ = async_action_result_tx.send(AsyncActionResult::UniversalResult(Some(e.to_string())));
-                        }
-                    }
-                }
-                Some(AsyncAction::LoadEpisodeState(link)) => {
-                    match data_provider.get_episode_state(&link).await {
-                        Ok(res) => {
-                            if let Some(state) = res {
-                                info!("Loaded episode state: time={:.1}s for link={}", state.time, link);
-                                let _ = async_action_result_tx.send(AsyncActionResult::EpisodeStateUpdate(state.time));
-                            } else {
-                                info!("No saved state found for episode: {}", link);
-                                let _ = async_action_result_tx.send(AsyncActionResult::EpisodeStateUpdate(0.0));
-                            }
-                        }
-                        Err(e) => {
-                            error!("Failed to load episode state for {}: {}", link, e);
-                            let _ = async_action_result_tx.send(AsyncActionResult::UniversalResult(Some(e.to_string())));
-                        }
-                    }
-                }
-                Some(AsyncAction::GetAllEpisodeStates(podcast_id)) => {
-                    match data_provider.get_all_episode_states(podcast_id).await {
-                        Ok(states) => {
-                            info!("Loaded {} episode states for podcast {}", states.len(), podcast_id);
-                            let _ = async_action_result_tx.send(AsyncActionResult::AllEpisodeStatesUpdate(Some(states)));
-                        }
-                        Err(e) => {
-                            error!("Failed to load episode states for podcast {}: {}", podcast_id, e);
-                            let _ = async_action_result_tx.send(AsyncActionResult::UniversalResult(Some(e.to_string())));
-                        }
-                    }
-                }
-                None => break,
-            }
-        }
+        handle_actions(data_provider, network, config, &mut async_action_rx, &async_action_result_tx, &async_waker).await;
     });
 
-    let player = Player::new();
+    let player = player::backend_from_env();
     let player_wrapper = PlayerWrapper {
         inner_player: player,
         player_state: PlayerState::Paused,
-        seek_position: 0.0
+        seek_position: 0.0,
+        speed: 1.0,
+        volume: 1.0,
+        trimmer: SilenceTrimmer::default(),
+        stream_url: None,
+        reconnects: 0,
+        playing_download: false,
+        sessions: SessionTracker::default(),
+        control: PlaybackControl::new(std::time::Instant::now()),
     };
 
     let native_options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default().with_inner_size([600.0, 400.0]),
+        follow_system_theme: config.theme == config::Theme::System,
+        default_theme: match config.theme {
+            config::Theme::Light => eframe::Theme::Light,
+            _ => eframe::Theme::Dark,
+        },
         ..Default::default()
     };
 
@@ -207,14 +217,21 @@ async fn main() {
         "Rustcast",
         native_options,
         Box::new(move |cc| {
-            Box::new(MyEguiApp::new(
+            waker.set(cc.egui_ctx.clone());
+            let mut app = MyEguiApp::new(
                 cc,
                 player_wrapper,
-                async_action_tx,
+                ActionSender::new(async_action_tx),
                 async_action_result_rx,
-                PlayerState::Open,
                 PodcastsModel::new(),
-            ))
+                stream_proxy,
+                config,
+            );
+            if let Some(e) = config_error {
+                app.error = e;
+                app.show_error = true;
+            }
+            Box::new(app)
         }),
     )
     .unwrap_or_else(|e| error!("An error occured {}", e));
@@ -224,110 +241,187 @@ async fn main() {
 
 struct MyEguiApp {
     player_wrapper: PlayerWrapper,
-    async_action_tx: UnboundedSender<AsyncAction>,
-    async_action_result_rx: UnboundedReceiver<AsyncActionResult>,
+    async_action_tx: ActionSender,
+    async_action_result_rx: UnboundedReceiver<AsyncResponse>,
     player_state: PlayerState,
     show_add_podcast: bool,
+    show_discovery: bool,
     podcasts_model: PodcastsModel,
     show_error: bool,
     error: String,
-    last_update_time: std::time::Instant,
+    notice: Option<String>,
+    show_import: bool,
+    import_source: ImportSource,
+    import_path: String,
+    import_report: Option<ImportReport>,
+    show_credentials: bool,
+    stream_proxy: Option<StreamProxy>,
+    config: Config,
+    config_path: std::path::PathBuf,
+    settings_dialog: Option<ui::SettingsDialog>,
+    stats_dialog: Option<ui::StatsDialog>,
+    clip_dialog: Option<ui::ClipDialog>,
+    playback_failure: Option<ui::PlaybackFailure>,
+    last_refresh: std::time::Instant,
+    database_failure: Option<DatabaseFailure>,
+    recovery_path: String,
+    recovering: bool,
 }
 
 impl MyEguiApp {
     fn new(
-        _cc: &eframe::CreationContext<'_>,
+        cc: &eframe::CreationContext<'_>,
         player_wrapper: PlayerWrapper,
-        async_action_tx: UnboundedSender<AsyncAction>,
-        async_action_result_rx: UnboundedReceiver<AsyncActionResult>,
-        _player_state: PlayerState,
+        async_action_tx: ActionSender,
+        async_action_result_rx: UnboundedReceiver<AsyncResponse>,
         podcasts_model: PodcastsModel,
+        stream_proxy: Option<StreamProxy>,
+        config: Config,
     ) -> Self {
         // Customize egui here with cc.egui_ctx.set_fonts and cc.egui_ctx.set_visuals.
         // Restore app state using cc.storage (requires the "persistence" feature).
         // Use the cc.gl (a glow::Context) to create graphics shaders and buffers that you can use
         // for e.g. egui::PaintCallback.
+        egui_extras::install_image_loaders(&cc.egui_ctx);
+        cc.egui_ctx.set_visuals(ui::theme_visuals(config.theme, cc.integration_info.system_theme));
+
         MyEguiApp {
             player_wrapper,
             async_action_tx,
             async_action_result_rx,
             player_state: PlayerState::Paused,
             show_add_podcast: false,
+            show_discovery: false,
             podcasts_model,
             show_error: false,
             error: String::new(),
-            last_update_time: std::time::Instant::now(),
+            notice: None,
+            show_import: false,
+            import_source: ImportSource::AntennaPod,
+            import_path: String::new(),
+            import_report: None,
+            show_credentials: false,
+            stream_proxy,
+            config,
+            config_path: Config::path(),
+            settings_dialog: None,
+            stats_dialog: None,
+            clip_dialog: None,
+            playback_failure: None,
+            last_refresh: std::time::Instant::now(),
+            database_failure: None,
+            recovery_path: String::new(),
+            recovering: false,
         }
     }
-}
 
-impl eframe::App for MyEguiApp {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        // Periodic auto-save for playing episodes to preserve state
-        let now = std::time::Instant::now();
-        if self.player_wrapper.player_state == PlayerState::Playing {
-            // Auto-save episode state every 5 seconds while playing
-            if now.duration_since(self.last_update_time).as_secs() >= 5 {
-                if let (Some(podcast_id), Some(episode)) = (
-                    self.podcasts_model.current_podcast.id,
-                    &self.podcasts_model.current_episode
-                ) {
-                    if let Some(episode_link) = &episode.link {
-                        let current_position = self.player_wrapper.inner_player.current_position();
-
-                        // Update local state for immediate UI feedback
-                        self.podcasts_model.episode_states.insert(episode_link.clone(), current_position);
-
-                        // Auto-save to database in background
-                        if let Err(e) = self.async_action_tx.send(AsyncAction::SaveEpisodeState(
-                            current_position,
-                            podcast_id,
-                            episode_link.clone()
-                        )) {
-                            error!("Failed to auto-save episode state: {}", e);
-                        } else {
-                            info!("Auto-saved episode state: {:.1}s for '{}'",
-                                current_position,
-                                episode.title.as_deref().unwrap_or("Unknown")
-                            );
+    fn handle_async_result(&mut self, result: AsyncActionResult) {
+        match result {
+            AsyncActionResult::PodcastsUpdate(podcasts) => match podcasts {
+                Ok(podcasts) => {
+                    self.podcasts_model.podcasts = Some(podcasts);
+                    // A podcast's volume offset may have been changed
+                    self.apply_loudness();
+                    self.async_action_tx.send(AsyncAction::GetGroups);
+                    self.async_action_tx.send(AsyncAction::GetPlaylists);
+                    self.async_action_tx.send(AsyncAction::GetInbox);
+                    self.async_action_tx.send(AsyncAction::GetBookmarks);
+                }
+                Err(e) => {
+                    self.error = e;
+                    self.show_error = true;
+                }
+            },
+            AsyncActionResult::EpisodesUpdate(refresh) => match refresh {
+                Ok(refresh) => {
+                    if let Some((old_url, new_url)) = refresh.moved {
+                        if self.podcasts_model.current_podcast.id == Some(refresh.podcast_id) {
+                            self.podcasts_model.current_podcast.link = new_url.clone();
                         }
-
-                        self.last_update_time = now;
+                        self.notice = Some(format!(
+                            "The feed of this podcast has moved and was updated.\n\nOld: {}\nNew: {}",
+                            old_url, new_url
+                        ));
+                        self.async_action_tx.send(AsyncAction::GetPodcasts);
+                    }
+                    if let Some(e) = refresh.refresh_error {
+                        self.error = e;
+                        self.show_error = true;
                     }
+                    self.podcasts_model.episodes = Some(refresh.episodes);
+                    self.async_action_tx.send(AsyncAction::GetInbox);
+                }
+                Err(e) => {
+                    self.podcasts_model.episodes = None;
+                    self.error = e;
+                    self.show_error = true;
                 }
-            }
-        }
-
-        match self.async_action_result_rx.try_recv() {
-            Ok(AsyncActionResult::PodcastsUpdate(podcasts)) => {
-                self.podcasts_model.podcasts = podcasts;
-            }
-            Ok(AsyncActionResult::EpisodesUpdate(episodes)) => {
-                self.podcasts_model.episodes = episodes;
             },
-            Ok(AsyncActionResult::AddPodcastResult(res)) => {
-                if res.is_some() {
-                    self.error = res.unwrap();
+            AsyncActionResult::AddPodcastResult(res) => match res {
+                Ok(()) => {
+                    self.async_action_tx.send(AsyncAction::GetPodcasts);
+                }
+                Err(e) => {
+                    self.error = e;
                     self.show_error = true;
                 }
+            },
+            AsyncActionResult::FeedCandidatesFound(title, description, feed_credentials, candidates) => {
+                self.podcasts_model.feed_choice_dialog = Some(podcasts_model::FeedChoiceDialog {
+                    title,
+                    description,
+                    credentials: feed_credentials,
+                    candidates,
+                    selected: 0,
+                });
             }
-            Ok(AsyncActionResult::UniversalResult(res)) => {
-                if res.is_some() {
-                    self.error = res.unwrap();
-                    self.show_error = true;
+            AsyncActionResult::CredentialsUnlocked(res) => {
+                self.podcasts_model.credentials_dialog.busy = false;
+                self.podcasts_model.credentials_dialog.passphrase = String::new();
+                match res {
+                    Ok(()) => {
+                        self.podcasts_model.credentials_dialog.unlocked = true;
+                        if let Some(podcast_id) = self.podcasts_model.credentials_dialog.podcast_id {
+                            self.async_action_tx.send(AsyncAction::GetCredentials(podcast_id));
+                        }
+                    }
+                    Err(e) => {
+                        self.error = e;
+                        self.show_error = true;
+                    }
                 }
             }
-            Ok(AsyncActionResult::EpisodeStateUpdate(res)) => {
-                if let Some(episode) = &self.podcasts_model.current_episode {
-                    if let Some(link) = &episode.link {
-                        // Always open the episode to ensure it's properly loaded
-                        self.player_wrapper.inner_player.open(link);
-
-                        // Seek to the saved position (or 0.0 if starting fresh)
-                        self.player_wrapper.inner_player.seek(res);
-                        self.player_wrapper.inner_player.play();
-                        self.player_wrapper.player_state = PlayerState::Playing;
-
+            AsyncActionResult::CredentialsLoaded(podcast_id, feed_credentials) => {
+                let dialog = &mut self.podcasts_model.credentials_dialog;
+                if dialog.podcast_id == Some(podcast_id) {
+                    let feed_credentials = feed_credentials.unwrap_or_default();
+                    dialog.headers = feed_credentials.format_headers();
+                    dialog.username = feed_credentials.username;
+                    dialog.password = feed_credentials.password;
+                }
+            }
+            AsyncActionResult::CredentialsSaved(res) => {
+                self.podcasts_model.credentials_dialog.busy = false;
+                match res {
+                    Ok(()) => self.show_credentials = false,
+                    Err(e) => {
+                        self.error = e;
+                        self.show_error = true;
+                    }
+                }
+            }
+            AsyncActionResult::EpisodeStateSaved(Err(e))
+            | AsyncActionResult::EpisodeStateUpdate(Err(e)) => {
+                self.error = e;
+                self.show_error = true;
+            }
+            AsyncActionResult::EpisodeStateSaved(Ok(())) => {}
+            AsyncActionResult::EpisodeStateUpdate(Ok(res)) => {
+                // A timestamp in the show notes may have asked for a position of its own
+                let start_at = self.podcasts_model.start_at.take();
+                let res = match &self.podcasts_model.current_episode {
+                    Some(episode) => {
+                        let res = playback::start_position(res, episode, start_at);
                         if res > 0.0 {
                             info!("Resumed episode '{}' from {:.1}s",
                                 episode.title.as_deref().unwrap_or("Unknown"), res);
@@ -335,24 +429,161 @@ impl eframe::App for MyEguiApp {
                             info!("Started episode '{}' from beginning",
                                 episode.title.as_deref().unwrap_or("Unknown"));
                         }
-                    } else {
-                        error!("Episode link is missing");
-                        self.error = "Episode link is missing or invalid.".to_string();
+                        res
+                    }
+                    None => res,
+                };
+                // Always open the episode to ensure it's properly loaded, at the saved
+                // position (or 0.0 if starting fresh)
+                self.open_current_episode(res);
+            }
+            AsyncActionResult::AllEpisodeStatesUpdate(states) => match states {
+                Ok(states) => self.podcasts_model.episode_states = states,
+                Err(e) => {
+                    self.error = e;
+                    self.show_error = true;
+                }
+            },
+            AsyncActionResult::ImportHistoryResult(res) => {
+                match res {
+                    Ok(report) => {
+                        self.show_import = false;
+                        self.import_path = String::new();
+                        self.import_report = Some(report);
+                        self.async_action_tx.send(AsyncAction::GetPodcasts);
+                    }
+                    Err(e) => {
+                        self.error = e;
                         self.show_error = true;
                     }
-                } else {
-                    error!("No current episode selected");
-                    self.error = "No episode selected for playback.".to_string();
+                }
+            }
+            AsyncActionResult::FeedsRefreshed(res) => match res {
+                Ok(refreshed) => {
+                    self.async_action_tx.send(AsyncAction::GetGroups);
+                    self.async_action_tx.send(AsyncAction::GetInbox);
+                    self.refresh_current_playlist();
+                    if refreshed.iter().any(|refresh| refresh.moved.is_some()) {
+                        self.async_action_tx.send(AsyncAction::GetPodcasts);
+                    }
+                    let current = self.podcasts_model.current_podcast.id;
+                    if let Some(refresh) = refreshed.into_iter().find(|refresh| Some(refresh.podcast_id) == current) {
+                        if let Some((_, new_url)) = refresh.moved {
+                            self.podcasts_model.current_podcast.link = new_url;
+                        }
+                        // A list the user asked for in the meantime is at least as fresh
+                        if !self.async_action_tx.in_flight(RequestKind::Episodes) {
+                            self.podcasts_model.episodes = Some(refresh.episodes);
+                        }
+                    }
+                }
+                // Background refreshes only log failures, the next one may well succeed
+                Err(e) => warn!("Automatic feed refresh failed: {}", e),
+            },
+            AsyncActionResult::ConfigApplied(res) => {
+                if let Err(e) = res {
+                    self.error = e;
                     self.show_error = true;
                 }
             }
-            Ok(AsyncActionResult::AllEpisodeStatesUpdate(states)) => {
-                if let Some(states) = states {
-                    self.podcasts_model.episode_states = states;
+            AsyncActionResult::PodcastSaved(res) => self.podcast_saved(res),
+            AsyncActionResult::Unsubscribed(podcast_id, res) => self.unsubscribed(podcast_id, res),
+            AsyncActionResult::GroupsUpdate(res) => self.groups_updated(res),
+            AsyncActionResult::TagEpisodesUpdate(res) => self.tag_episodes_updated(res),
+            AsyncActionResult::PlaylistsUpdate(res) => self.playlists_updated(res),
+            AsyncActionResult::PlaylistEpisodesUpdate(res) => self.playlist_episodes_updated(res),
+            AsyncActionResult::InboxUpdate(res) => self.inbox_updated(res),
+            AsyncActionResult::EpisodeDownloaded(link, res) => self.episode_downloaded(link, res),
+            AsyncActionResult::SessionLogged(res) => self.session_logged(res),
+            AsyncActionResult::ListeningStatsUpdate(res) => self.listening_stats_updated(res),
+            AsyncActionResult::HistoryExported(res) => self.history_exported(res),
+            AsyncActionResult::ArtworkLoaded(url, res) => self.artwork_loaded(url, res),
+            AsyncActionResult::BookmarksUpdate(res) => self.bookmarks_updated(res),
+            AsyncActionResult::BookmarksExported(res) => self.bookmarks_exported(res),
+            AsyncActionResult::EpisodeFound(link, res) => self.episode_found(link, res),
+            AsyncActionResult::ClipExported(res) => self.clip_exported(res),
+            AsyncActionResult::SilencesDetected(link, res) => self.silences_detected(link, res),
+            AsyncActionResult::EnclosureProbed(link, res) => self.enclosure_probed(link, res),
+            AsyncActionResult::DatabaseUnavailable(failure) => {
+                if self.recovery_path.is_empty() {
+                    self.recovery_path = failure.file.display().to_string();
                 }
+                self.recovering = false;
+                self.database_failure = Some(failure);
             }
-            Err(_) => {}
-        };
+            AsyncActionResult::DatabaseReady(file) => self.database_recovered(file),
+            AsyncActionResult::DirectorySearchResult(res) => {
+                match res {
+                    Ok(results) => self.podcasts_model.discovery_dialog.results = results,
+                    Err(e) => {
+                        self.error = e;
+                        self.show_error = true;
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl eframe::App for MyEguiApp {
+    fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        // Periodic auto-save for playing episodes to preserve state
+        let now = std::time::Instant::now();
+        let saved = self.player_wrapper.control.auto_save(
+            self.player_wrapper.inner_player.as_ref(),
+            &self.player_wrapper.player_state,
+            self.podcasts_model.current_episode.as_ref(),
+            now,
+        );
+        if let Some(saved) = saved {
+            info!("Auto-saved episode state: {:.1}s for '{}'", saved.position, self.podcasts_model.current_episode.as_ref().and_then(|e| e.title.as_deref()).unwrap_or("Unknown"));
+            self.store_position(saved);
+        }
+
+        let next_refresh = self.config.refresh_interval().map(|interval| {
+            let elapsed = now.duration_since(self.last_refresh);
+            if elapsed >= interval {
+                self.last_refresh = now;
+                if !self.async_action_tx.in_flight(RequestKind::Refresh) {
+                    self.async_action_tx.send_tracked(RequestKind::Refresh, AsyncAction::RefreshFeeds);
+                }
+                interval
+            } else {
+                interval - elapsed
+            }
+        });
+
+        // The worker wakes the UI when it sends something, so handle everything that arrived
+        while let Ok(response) = self.async_action_result_rx.try_recv() {
+            let request_id = response.request_id;
+            let cancelled = response.event == AsyncEvent::Cancelled;
+            let result = self.async_action_tx.accept(response);
+            if cancelled || result.is_some() {
+                let added = matches!(result, Some(AsyncActionResult::AddPodcastResult(Ok(()))));
+                self.podcasts_model.discovery_dialog.subscribe_finished(request_id, added);
+            }
+            if let Some(result) = result {
+                self.handle_async_result(result);
+            }
+        }
+        if let Some(e) = self.player_wrapper.inner_player.take_error() {
+            error!("Player failed: {}", e);
+            let position = self.player_wrapper.inner_player.current_position();
+            self.playback_failed(e, position);
+        }
+        self.update_player_state();
+        self.advance_queue();
+        self.trim_silence();
+        self.track_listening();
+        self.advance_clip_preview();
+        if !ctx.wants_keyboard_input() && ctx.input(|i| i.key_pressed(egui::Key::B)) {
+            self.new_bookmark();
+        }
+
+        if self.database_failure.is_some() {
+            self.show_recovery_screen(ctx);
+            return;
+        }
 
         egui::SidePanel::left("podcasts_panel")
             .resizable(true)
@@ -362,6 +593,11 @@ impl eframe::App for MyEguiApp {
                 ui.vertical(|ui| {
                     ui.horizontal(|ui| {
                         ui.heading("Podcasts");
+                        if self.async_action_tx.in_flight(RequestKind::AddPodcast) {
+                            ui.spinner().on_hover_text("Adding podcast…");
+                        } else if self.async_action_tx.in_flight(RequestKind::Refresh) {
+                            ui.spinner().on_hover_text("Refreshing feeds…");
+                        }
                         if ui
                             .add(egui::Button::new("+"))
                             .on_hover_text("Add podcast")
@@ -369,6 +605,50 @@ impl eframe::App for MyEguiApp {
                         {
                             self.show_add_podcast = true;
                         }
+                        if ui
+                            .add(egui::Button::new("🔍"))
+                            .on_hover_text("Find podcasts")
+                            .clicked()
+                        {
+                            self.show_discovery = true;
+                        }
+                        if ui
+                            .add(egui::Button::new("📥"))
+                            .on_hover_text("Import listening history")
+                            .clicked()
+                        {
+                            self.show_import = true;
+                        }
+                        if let Some(podcast_id) = self.podcasts_model.current_podcast.id {
+                            if ui
+                                .add(egui::Button::new("🔑"))
+                                .on_hover_text("Private feed credentials")
+                                .clicked()
+                            {
+                                self.open_credentials_window(podcast_id);
+                            }
+                        }
+                        if ui
+                            .add(egui::Button::new("🔖"))
+                            .on_hover_text("Bookmarks")
+                            .clicked()
+                        {
+                            self.open_bookmarks_window();
+                        }
+                        if ui
+                            .add(egui::Button::new("📊"))
+                            .on_hover_text("Listening statistics")
+                            .clicked()
+                        {
+                            self.open_stats_window();
+                        }
+                        if ui
+                            .add(egui::Button::new("⚙"))
+                            .on_hover_text("Settings")
+                            .clicked()
+                        {
+                            self.open_settings_window();
+                        }
                     });
                 });
                 egui::ScrollArea::vertical()
@@ -377,40 +657,17 @@ impl eframe::App for MyEguiApp {
                         ui.with_layout(
                             egui::Layout::top_down(egui::Align::LEFT).with_cross_justify(true),
                             |ui| {
-                                if let Some(podcasts) = &self.podcasts_model.podcasts {
-                                    for p in podcasts {
-                                        if let Some(title) = &p.title {
-                                            if ui.add(egui::Link::new(title)).clicked() {
-                                                if let (Some(link), Some(description)) = (&p.link, &p.description) {
-                                                    self.podcasts_model.current_podcast = podcasts_model::Podcast {
-                                                        id: Some(p.id),
-                                                        title: title.clone(),
-                                                        link: link.clone(),
-                                                        description: description.clone()
-                                                    };
-
-                                                    let _ = self.async_action_tx.send(
-                                                        AsyncAction::GetEpisodes(link.clone(), p.id),
-                                                    );
-                                                    let _ = self.async_action_tx.send(
-                                                        AsyncAction::GetAllEpisodeStates(p.id),
-                                                    );
-                                                } else {
-                                                    error!("Podcast data is incomplete: id={}, title={:?}, link={:?}", p.id, p.title, p.link);
-                                                    self.error = "This podcast has incomplete data and cannot be loaded.".to_string();
-                                                    self.show_error = true;
-                                                }
-                                            }
-                                        }
-                                    }
-                                } else {
-                                    let _ = self.async_action_tx.send(AsyncAction::GetPodcasts);
-                                }
+                                self.show_podcast_list(ui);
                             },
                         );
                     });
             });
 
+        let now_playing_artwork_url = self.podcasts_model.current_episode.as_ref()
+            .and_then(|episode| self.episode_artwork_url(episode));
+        self.request_artwork(now_playing_artwork_url.as_deref());
+        let now_playing_artwork = self.artwork(now_playing_artwork_url.as_deref());
+
         egui::TopBottomPanel::bottom("bottom_panel")
             .resizable(false)
             .min_height(70.0)
@@ -418,63 +675,113 @@ impl eframe::App for MyEguiApp {
                 ui.with_layout(egui::Layout::bottom_up(egui::Align::Center), |ui| {
                     ui.add_space(10.0);
 
-                    if self.player_wrapper.player_state == PlayerState::Paused && ui.add(egui::Button::new("▶")).clicked() {
-                            self.player_wrapper.inner_player.play();
-                            self.player_wrapper.player_state = PlayerState::Playing;
+                    ui.horizontal(|ui| {
+                        if ui.add(egui::Button::new("⏪"))
+                            .on_hover_text(format!("Back {} s", self.config.skip_back_seconds))
+                            .clicked()
+                        {
+                            self.player_wrapper.inner_player.seek_relative(-(self.config.skip_back_seconds as f64));
                         }
 
-                    if (self.player_wrapper.player_state == PlayerState::Playing
-                        || self.player_state == PlayerState::Open)
-                        && ui.add(egui::Button::new("⏸")).clicked() {
-                            self.player_wrapper.inner_player.pause();
-                            self.player_wrapper.player_state = PlayerState::Paused;
+                        if self.player_wrapper.player_state.is_stopped() && ui.add(egui::Button::new("▶")).clicked() {
+                                self.resume_playback();
+                            }
+
+                        if (self.player_wrapper.player_state.is_active()
+                            || self.player_state == PlayerState::Open)
+                            && ui.add(egui::Button::new("⏸")).clicked() {
+                                self.pause_playback();
+                        }
 
-                            if let (Some(podcast_id), Some(episode)) = (
-                                self.podcasts_model.current_podcast.id,
-                                &self.podcasts_model.current_episode
-                            ) {
-                                if let Some(episode_link) = &episode.link {
-                                    let current_position = self.player_wrapper.inner_player.current_position();
+                        if ui.add(egui::Button::new("⏩"))
+                            .on_hover_text(format!("Forward {} s", self.config.skip_forward_seconds))
+                            .clicked()
+                        {
+                            self.player_wrapper.inner_player.seek_relative(self.config.skip_forward_seconds as f64);
+                        }
 
-                                    // Update local state immediately for real-time display
-                                    self.podcasts_model.episode_states.insert(episode_link.clone(), current_position);
+                        let can_bookmark = self.podcasts_model.current_episode.is_some();
+                        if ui.add_enabled(can_bookmark, egui::Button::new("🔖"))
+                            .on_hover_text("Bookmark this moment (B)")
+                            .clicked()
+                        {
+                            self.new_bookmark();
+                        }
+                        if ui.add_enabled(can_bookmark, egui::Button::new("✂"))
+                            .on_hover_text("Export a clip")
+                            .clicked()
+                        {
+                            self.open_clip_window();
+                        }
 
-                                    // Send to async handler to save to database
-                                    if let Err(e) = self.async_action_tx.send(AsyncAction::SaveEpisodeState(
-                                        current_position,
-                                        podcast_id,
-                                        episode_link.clone()
-                                    )) {
-                                        error!("Failed to save episode state: {}", e);
-                                    }
-                                } else {
-                                    warn!("Cannot save episode state: episode link is missing");
-                                }
-                            } else {
-                                warn!("Cannot save episode state: podcast or episode not properly selected");
-                            }
-                    }
+                        ui.label("🔊");
+                        let volume = ui.add_enabled(
+                            self.player_wrapper.inner_player.supports_volume(),
+                            egui::Slider::new(&mut self.player_wrapper.volume, 0.0..=2.0).show_value(false),
+                        ).on_disabled_hover_text("This audio backend can't change the volume");
+                        if volume.changed() {
+                            self.player_wrapper.inner_player.set_volume(self.player_wrapper.volume);
+                        }
+                    });
 
                     ui.add_space(5.0);
+                    // Added before the timeline, as the panel is laid out bottom up
+                    self.show_clip_selector(ui);
 
                     let timeline_add = ui.add(Timeline::new(
                         self.player_wrapper.inner_player.current_position(),
                         self.player_wrapper.inner_player.duration(),
                         &mut self.player_wrapper.seek_position
                     ));
+                    self.show_buffered_ranges(ui, timeline_add.rect);
                     if timeline_add.clicked() || timeline_add.drag_stopped() {
                         self.player_wrapper.inner_player.seek(self.player_wrapper.seek_position);
                     }
 
                     if let Some(current_episode) = &self.podcasts_model.current_episode {
-                        ui.label(current_episode.title.as_deref().unwrap_or("Unknown Episode"));
+                        ui.horizontal(|ui| {
+                            if let Some(artwork) = now_playing_artwork {
+                                ui::thumbnail(ui, artwork, NOW_PLAYING_ARTWORK_SIZE);
+                            }
+                            ui.label(current_episode.title.as_deref().unwrap_or("Unknown Episode"));
+                            let trimmed = self.player_wrapper.trimmer.trimmed_seconds;
+                            if trimmed >= 1.0 {
+                                ui.weak(format!("· {} of silence trimmed", utils::format_position(trimmed)));
+                            }
+                            self.show_silence_detection(ui);
+                            self.show_stream_status(ui);
+                        });
+                    }
+                    if !self.podcasts_model.queue.is_empty() {
+                        ui.horizontal(|ui| {
+                            ui.weak(format!("{} more queued", self.podcasts_model.queue.len()));
+                            if ui.small_button("Clear").clicked() {
+                                self.podcasts_model.queue.clear();
+                            }
+                        });
                     }
                 });
             });
 
+        self.show_episode_details(ctx);
+
         egui::CentralPanel::default().show(ctx, |ui| {
-            ui.vertical(|ui| {
-                ui.heading("Episodes");
+            if self.podcasts_model.inbox_open {
+                self.show_inbox(ui);
+                return;
+            }
+            ui.horizontal(|ui| {
+                ui.heading(self.episodes_heading());
+                let has_episodes = self.podcasts_model.episodes.as_ref().is_some_and(|episodes| !episodes.is_empty());
+                if self.podcasts_model.current_playlist.is_some() && has_episodes
+                    && ui.add(egui::Button::new("▶ Play all")).clicked()
+                {
+                    self.play_all(self.podcasts_model.episodes.clone().unwrap_or_default());
+                }
+                if let Some(progress) = self.async_action_tx.progress(RequestKind::Episodes) {
+                    ui.spinner();
+                    ui.weak(progress.describe());
+                }
             });
             if let Some(episodes) = &self.podcasts_model.episodes {
                 egui::ScrollArea::horizontal().show(ui, |ui| {
@@ -518,30 +825,18 @@ impl eframe::App for MyEguiApp {
                                 });
                                 row.col(|ui| {
                                     if self.podcasts_model.current_episode == Some(episodes[row_index].clone())
-                                        && self.player_wrapper.player_state == PlayerState::Playing {
+                                        && self.player_wrapper.player_state.is_active() {
                                         if ui.add(egui::Button::new("⏸").min_size(eframe::egui::Vec2::new(15.0, 15.0)).fill(eframe::egui::Color32::from_rgb(0, 155, 255))).clicked() {
-                                            self.player_wrapper.inner_player.pause();
+                                            let saved = self.player_wrapper.control.pause(
+                                                self.player_wrapper.inner_player.as_mut(),
+                                                self.podcasts_model.current_episode.as_ref(),
+                                                std::time::Instant::now(),
+                                            );
                                             self.player_wrapper.player_state = PlayerState::Paused;
-
-                                            if let (Some(podcast_id), Some(episode)) = (
-                                                self.podcasts_model.current_podcast.id,
-                                                &self.podcasts_model.current_episode
-                                            ) {
-                                                if let Some(episode_link) = &episode.link {
-                                                    let current_position = self.player_wrapper.inner_player.current_position();
-
-                                                    // Update local state immediately for real-time display
-                                                    self.podcasts_model.episode_states.insert(episode_link.clone(), current_position);
-
-                                                    // Send to async handler to save to database
-                                                    if let Err(e) = self.async_action_tx.send(AsyncAction::SaveEpisodeState(
-                                                        current_position,
-                                                        podcast_id,
-                                                        episode_link.clone()
-                                                    )) {
-                                                        error!("Failed to save episode state: {}", e);
-                                                    }
-                                                }
+                                            if let Some(saved) = saved {
+                                                // Update local state immediately for real-time display
+                                                self.podcasts_model.episode_states.insert(saved.ep_link.clone(), saved.position);
+                                                self.async_action_tx.send(saved.into_action());
                                             }
                                         }
                                     } else if ui.add(egui::Button::new("▶").min_size(eframe::egui::Vec2::new(15.0, 15.0))).clicked() {
@@ -550,34 +845,24 @@ impl eframe::App for MyEguiApp {
                                             .map(|current| current.link == selected_episode.link)
                                             .unwrap_or(false);
 
-                                        if is_same_episode && self.player_wrapper.player_state == PlayerState::Paused {
-                                            // Same episode - just resume playback from current position
-                                            self.player_wrapper.inner_player.play();
-                                            self.player_wrapper.player_state = PlayerState::Playing;
+                                        let resumable = matches!(self.player_wrapper.player_state, PlayerState::Paused | PlayerState::Ended);
+                                        if is_same_episode && resumable {
+                                            // Same episode - just resume playback from current position,
+                                            // or from the start once it has ended
+                                            self.player_wrapper.player_state = self.player_wrapper.control.resume(
+                                                self.player_wrapper.inner_player.as_mut(),
+                                                &self.player_wrapper.player_state,
+                                            );
                                             info!("Resumed playback of: {}", selected_episode.title.as_deref().unwrap_or("Unknown"));
                                         } else {
                                             // Different episode or no current episode - save current state and load new episode
-                                            if self.podcasts_model.current_episode.is_some() {
-                                                if let (Some(podcast_id), Some(episode)) = (
-                                                    self.podcasts_model.current_podcast.id,
-                                                    &self.podcasts_model.current_episode
-                                                ) {
-                                                    if let Some(episode_link) = &episode.link {
-                                                        let current_position = self.player_wrapper.inner_player.current_position();
-
-                                                        // Update local state immediately for real-time display
-                                                        self.podcasts_model.episode_states.insert(episode_link.clone(), current_position);
-
-                                                        // Send to async handler to save to database
-                                                        if let Err(e) = self.async_action_tx.send(AsyncAction::SaveEpisodeState(
-                                                            current_position,
-                                                            podcast_id,
-                                                            episode_link.clone()
-                                                        )) {
-                                                            error!("Failed to save episode state: {}", e);
-                                                        }
-                                                    }
-                                                }
+                                            let saved = SavedPosition::of(
+                                                self.player_wrapper.inner_player.as_ref(),
+                                                self.podcasts_model.current_episode.as_ref(),
+                                            );
+                                            if let Some(saved) = saved {
+                                                self.podcasts_model.episode_states.insert(saved.ep_link.clone(), saved.position);
+                                                self.async_action_tx.send(saved.into_action());
                                             }
 
                                             // Set new current episode
@@ -585,9 +870,7 @@ impl eframe::App for MyEguiApp {
 
                                             // Load the episode state and start playback
                                             if let Some(episode_link) = &selected_episode.link {
-                                                if let Err(e) = self.async_action_tx.send(AsyncAction::LoadEpisodeState(episode_link.clone())) {
-                                                    error!("Failed to load episode state: {}", e);
-                                                }
+                                                self.async_action_tx.send_tracked(RequestKind::PlaybackPosition, AsyncAction::LoadEpisodeState(episode_link.clone()));
                                             } else {
                                                 error!("Episode link is missing for episode at index {}", row_index);
                                             }
@@ -602,19 +885,24 @@ impl eframe::App for MyEguiApp {
                                             .map(|ep| ep.link.as_ref() == Some(episode_link))
                                             .unwrap_or(false);
 
-                                        if is_current_episode && self.player_wrapper.player_state == PlayerState::Playing {
+                                        if is_current_episode && self.player_wrapper.player_state.is_active() {
                                             // Show real-time position and status for playing episode
                                             let current_time = self.player_wrapper.inner_player.current_position();
+                                            let status = match self.player_wrapper.player_state {
+                                                PlayerState::Loading => "Loading",
+                                                PlayerState::Buffering => "Buffering",
+                                                _ => "Playing",
+                                            };
                                             ui.colored_label(
                                                 egui::Color32::from_rgb(0, 155, 255),
-                                                format!("Playing: {}", format_time(current_time))
+                                                format!("{}: {}", status, utils::format_position(current_time))
                                             );
-                                        } else if is_current_episode && self.player_wrapper.player_state == PlayerState::Paused {
+                                        } else if is_current_episode && self.player_wrapper.player_state.is_stopped() {
                                             // Show real-time position for paused current episode
                                             let current_time = self.player_wrapper.inner_player.current_position();
                                             ui.colored_label(
                                                 egui::Color32::from_rgb(255, 165, 0),
-                                                format_time(current_time)
+                                                utils::format_position(current_time)
                                             );
                                         } else {
                                             // Show saved state for other episodes
@@ -622,7 +910,7 @@ impl eframe::App for MyEguiApp {
                                                 .copied()
                                                 .unwrap_or(0.0);
                                             if pause_time > 0.0 {
-                                                ui.label(format_time(pause_time));
+                                                ui.label(utils::format_position(pause_time));
                                             } else {
                                                 ui.label("Not started");
                                             }
@@ -632,7 +920,15 @@ impl eframe::App for MyEguiApp {
                                     }
                                 });
                                 row.col(|ui| {
-                                    ui.label(episodes[row_index].title.as_deref().unwrap_or("Unknown Episode"));
+                                    let episode = &episodes[row_index];
+                                    let selected = self.podcasts_model.episode_details.as_ref()
+                                        .is_some_and(|details| details.episode.link == episode.link);
+                                    if ui.selectable_label(selected, episode.title.as_deref().unwrap_or("Unknown Episode"))
+                                        .on_hover_text("Show notes")
+                                        .clicked()
+                                    {
+                                        self.podcasts_model.episode_details = (!selected).then(|| EpisodeDetails::new(episode.clone()));
+                                    }
                                 });
                             });
                         });
@@ -667,29 +963,45 @@ impl eframe::App for MyEguiApp {
                                 .hint_text("Podcast description"),
                             );
 
+                            ui.checkbox(&mut self.podcasts_model.podcast_dialog.private_feed, "Private feed");
+                            if self.podcasts_model.podcast_dialog.private_feed {
+                                ui.add(
+                                    egui::TextEdit::singleline(
+                                        &mut self.podcasts_model.podcast_dialog.username,
+                                    )
+                                    .hint_text("Username"),
+                                );
+                                ui.add(
+                                    egui::TextEdit::singleline(
+                                        &mut self.podcasts_model.podcast_dialog.password,
+                                    )
+                                    .password(true)
+                                    .hint_text("Password"),
+                                );
+                                ui.add(
+                                    egui::TextEdit::multiline(
+                                        &mut self.podcasts_model.podcast_dialog.headers,
+                                    )
+                                    .desired_rows(2)
+                                    .hint_text("Extra headers, one \"Name: value\" per line"),
+                                );
+                            }
+
                             ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
                                 if ui.add(egui::Button::new("Close")).clicked() {
-                                    self.podcasts_model.podcast_dialog.link = String::new();
-                                    self.podcasts_model.podcast_dialog.title = String::new();
-                                    self.podcasts_model.podcast_dialog.description = String::new();
+                                    self.podcasts_model.podcast_dialog = Default::default();
                                     self.show_add_podcast = false;
                                 }
                                 if ui.add(egui::Button::new("Add")).clicked() {
                                     self.async_action_tx
-                                        .send(AsyncAction::AddPodcast(
+                                        .send_tracked(RequestKind::AddPodcast, AsyncAction::AddPodcast(
                                             self.podcasts_model.podcast_dialog.title.clone(),
                                             self.podcasts_model.podcast_dialog.link.clone(),
                                             self.podcasts_model.podcast_dialog.description.clone(),
-                                        ))
-                                        .unwrap_or_else(|e| error!("{:?}", e.to_string()));
-
-                                    self.async_action_tx
-                                        .send(AsyncAction::GetPodcasts)
-                                        .unwrap_or_else(|e| error!("{:?}", e.to_string()));
+                                            self.podcasts_model.podcast_dialog.credentials(),
+                                        ));
 
-                                    self.podcasts_model.podcast_dialog.link = String::new();
-                                    self.podcasts_model.podcast_dialog.title = String::new();
-                                    self.podcasts_model.podcast_dialog.description = String::new();
+                                    self.podcasts_model.podcast_dialog = Default::default();
                                     self.show_add_podcast = false;
                                 }
                             });
@@ -698,113 +1010,1526 @@ impl eframe::App for MyEguiApp {
                 });
         }
 
-        if self.show_error {
-            egui::Window::new("Error")
+        if let Some(dialog) = &mut self.podcasts_model.feed_choice_dialog {
+            let mut close = false;
+            egui::Window::new("Choose a feed")
                 .collapsible(false)
                 .resizable(true)
                 .show(ctx, |ui| {
-                    ui.label(self.error.clone());
+                    ui.label("This page links to several feeds. Pick the one to subscribe to:");
+                    for (index, candidate) in dialog.candidates.iter().enumerate() {
+                        let label = match &candidate.title {
+                            Some(title) => format!("{} ({})", title, candidate.url),
+                            None => candidate.url.clone(),
+                        };
+                        ui.radio_value(&mut dialog.selected, index, label);
+                    }
+
                     ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
-                        if ui.add(egui::Button::new("Ok")).clicked() {
-                            self.show_error = false;
-                            self.error = String::new();
+                        if ui.add(egui::Button::new("Close")).clicked() {
+                            close = true;
                         }
-                    })
+                        if ui.add(egui::Button::new("Add")).clicked() {
+                            if let Some(candidate) = dialog.candidates.get(dialog.selected) {
+                                self.async_action_tx
+                                    .send_tracked(RequestKind::AddPodcast, AsyncAction::AddPodcast(
+                                        dialog.title.clone(),
+                                        candidate.url.clone(),
+                                        dialog.description.clone(),
+                                        dialog.credentials.clone(),
+                                    ));
+                            }
+                            close = true;
+                        }
+                    });
                 });
-        }
-
-        ctx.request_repaint();
-    }
-
-    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
-        // Save current episode state before closing
-        if self.player_wrapper.player_state == PlayerState::Playing ||
-           self.player_wrapper.player_state == PlayerState::Paused {
-            if let (Some(podcast_id), Some(episode)) = (
-                self.podcasts_model.current_podcast.id,
-                &self.podcasts_model.current_episode
-            ) {
-                if let Some(episode_link) = &episode.link {
-                    let current_position = self.player_wrapper.inner_player.current_position();
-
-                    // Final save before app closes
-                    if let Err(e) = self.async_action_tx.send(AsyncAction::SaveEpisodeState(
-                        current_position,
-                        podcast_id,
-                        episode_link.clone()
-                    )) {
-                        error!("Failed to save final episode state: {}", e);
-                    } else {
-                        info!("Final save on app close: {:.1}s for '{}'",
-                            current_position,
-                            episode.title.as_deref().unwrap_or("Unknown")
-                        );
-                    }
-                }
+            if close {
+                self.podcasts_model.feed_choice_dialog = None;
             }
         }
-    }
-}
 
-async fn handle_add_podcast(
-    data_provider: &DataProvider,
-    title: String,
-    link: String,
-    description: String,
-) -> RustcastResult<()> {
-    // Validate input data
-    utils::validate_podcast_data(&title, &link, &description)?;
+        if self.show_discovery {
+            self.show_discovery_window(ctx);
+        }
 
-    // Validate the RSS feed
-    let response = utils::safe_network_request(&link)?;
-    let content = response.into_string()
-        .map_err(|e| RustcastError::Network(error::NetworkError::InvalidResponse(e.to_string())))?;
+        if self.show_credentials {
+            self.show_credentials_window(ctx);
+        }
 
-    let _channel = utils::safe_rss_parse(&content)?;
+        self.show_settings_window(ctx, frame);
+        self.show_edit_podcast_window(ctx);
+        self.show_unsubscribe_window(ctx);
+        self.show_stats_window(ctx);
+        self.show_bookmark_window(ctx);
+        self.show_bookmarks_window(ctx);
+        self.show_clip_window(ctx);
+        self.show_playback_failure_window(ctx);
+        self.show_tag_window(ctx);
+        self.show_playlist_window(ctx);
+
+        if self.show_import {
+            egui::Window::new("Import listening history")
+                .collapsible(false)
+                .resizable(true)
+                .show(ctx, |ui| {
+                    ui.with_layout(
+                        egui::Layout::top_down_justified(egui::Align::Center),
+                        |ui| {
+                            egui::ComboBox::from_id_source("import_source")
+                                .selected_text(self.import_source.label())
+                                .show_ui(ui, |ui| {
+                                    for source in ImportSource::ALL {
+                                        ui.selectable_value(&mut self.import_source, source, source.label());
+                                    }
+                                });
+                            ui.add(
+                                egui::TextEdit::singleline(&mut self.import_path)
+                                    .hint_text("Path to database file"),
+                            );
 
-    // If we get here, the feed is valid, so add it to the database
-    data_provider.add_podcast(title, link, description).await
+                            ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+                                if ui.add(egui::Button::new("Close")).clicked() {
+                                    self.import_path = String::new();
+                                    self.show_import = false;
+                                }
+                                if let Some(progress) = self.async_action_tx.progress(RequestKind::Import) {
+                                    ui.spinner();
+                                    ui.label(format!("Importing... {}", progress.describe()));
+                                } else if ui.add(egui::Button::new("Import")).clicked() {
+                                    self.async_action_tx
+                                        .send_tracked(RequestKind::Import, AsyncAction::ImportHistory(
+                                            self.import_source,
+                                            self.import_path.trim().to_string(),
+                                        ));
+                                }
+                            });
+                        },
+                    );
+                });
+        }
+
+        if let Some(report) = &self.import_report {
+            let mut close = false;
+            egui::Window::new("Import finished")
+                .collapsible(false)
+                .resizable(true)
+                .show(ctx, |ui| {
+                    ui.label(format!("Podcasts added: {}", report.podcasts_created));
+                    ui.label(format!("Podcasts already subscribed: {}", report.podcasts_existing));
+                    ui.label(format!("Episode states imported: {}", report.episodes_imported));
+
+                    if !report.unmatched.is_empty() {
+                        ui.separator();
+                        ui.strong(format!("Unmatched items: {}", report.unmatched.len()));
+                        egui::ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                            for item in &report.unmatched {
+                                ui.label(format!("{} – {}: {}",
+                                    item.podcast_title,
+                                    item.episode_title.as_deref().unwrap_or("(podcast)"),
+                                    item.reason
+                                ));
+                            }
+                        });
+                    }
+
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+                        if ui.add(egui::Button::new("Ok")).clicked() {
+                            close = true;
+                        }
+                    })
+                });
+            if close {
+                self.import_report = None;
+            }
+        }
+
+        if let Some(notice) = &self.notice {
+            let mut close = false;
+            egui::Window::new("Notice")
+                .collapsible(false)
+                .resizable(true)
+                .show(ctx, |ui| {
+                    ui.label(notice);
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+                        if ui.add(egui::Button::new("Ok")).clicked() {
+                            close = true;
+                        }
+                    })
+                });
+            if close {
+                self.notice = None;
+            }
+        }
+
+        if self.show_error {
+            egui::Window::new("Error")
+                .collapsible(false)
+                .resizable(true)
+                .show(ctx, |ui| {
+                    ui.label(self.error.clone());
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+                        if ui.add(egui::Button::new("Ok")).clicked() {
+                            self.show_error = false;
+                            self.error = String::new();
+                        }
+                    })
+                });
+        }
+
+        if let Some(delay) = repaint_after(&self.player_wrapper.player_state, next_refresh) {
+            ctx.request_repaint_after(delay);
+        }
+    }
+
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        self.end_listening_session();
+        // Save current episode state before closing
+        if self.player_wrapper.player_state.is_active() ||
+           self.player_wrapper.player_state.is_stopped() {
+            let saved = SavedPosition::of(
+                self.player_wrapper.inner_player.as_ref(),
+                self.podcasts_model.current_episode.as_ref(),
+            );
+            if let Some(saved) = saved {
+                // Final save before app closes
+                info!("Final save on app close: {:.1}s for '{}'", saved.position, self.podcasts_model.current_episode.as_ref().and_then(|e| e.title.as_deref()).unwrap_or("Unknown"));
+                self.async_action_tx.send(saved.into_action());
+            }
+        }
+    }
+}
+
+/// Answers the UI's requests until it goes away
+async fn handle_actions(
+    data_provider: DataProvider,
+    mut network: NetworkContext,
+    mut config: Config,
+    rx: &mut UnboundedReceiver<AsyncRequest>,
+    tx: &UnboundedSender<AsyncResponse>,
+    waker: &UiWaker,
+) {
+    let credentials = network.credentials.clone();
+    let in_flight: InFlight = Default::default();
+
+    // Database actions are quick and handled in order; anything that waits on the network
+    // runs in its own task so a slow feed never holds up saving playback state.
+    while let Some(AsyncRequest { id, action }) = rx.recv().await {
+        let ctx = ActionContext::new(id, tx.clone(), waker.clone());
+        match action {
+            AsyncAction::AddPodcast(title, link, description, feed_credentials) => {
+                let data_provider = data_provider.clone();
+                let network = network.clone();
+                let task_ctx = ctx.clone();
+                ctx.spawn(&in_flight, async move {
+                    match handle_add_podcast(&data_provider, &network, &task_ctx, title.clone(), link, description.clone(), feed_credentials.clone()).await {
+                        Ok(AddPodcastOutcome::Added) => {
+                            info!("Successfully added podcast: {}", title);
+                            AsyncActionResult::AddPodcastResult(Ok(()))
+                        }
+                        Ok(AddPodcastOutcome::ChooseFeed(candidates)) => {
+                            info!("Found {} feeds for podcast '{}', asking which one to add", candidates.len(), title);
+                            AsyncActionResult::FeedCandidatesFound(title, description, feed_credentials, candidates)
+                        }
+                        Err(e) => {
+                            error!("Failed to add podcast '{}': {}", title, e);
+                            AsyncActionResult::AddPodcastResult(Err(e.user_friendly_message()))
+                        }
+                    }
+                });
+            }
+            AsyncAction::GetPodcasts => {
+                let podcasts = data_provider.get_podcasts().await.map_err(|e| {
+                    error!("Failed to load podcasts: {}", e);
+                    RustcastError::from(e).user_friendly_message()
+                });
+                ctx.finish(AsyncActionResult::PodcastsUpdate(podcasts));
+            }
+            AsyncAction::GetEpisodes(link, podcast_id) => {
+                let data_provider = data_provider.clone();
+                let network = network.clone();
+                let task_ctx = ctx.clone();
+                ctx.spawn(&in_flight, async move {
+                    let refresh = match handle_get_episodes(&data_provider, &network, &task_ctx, &link, podcast_id).await {
+                        Ok(refresh) => {
+                            info!("Successfully loaded {} episodes for podcast {}", refresh.episodes.len(), podcast_id);
+                            Ok(EpisodesRefresh {
+                                podcast_id,
+                                episodes: refresh.episodes,
+                                refresh_error: None,
+                                moved: refresh.moved_to.map(|new_url| (link, new_url)),
+                            })
+                        }
+                        Err(e @ RustcastError::Network(NetworkError::Cancelled)) => Err(e.to_string()),
+                        Err(e) => {
+                            error!("Failed to load episodes for podcast {}: {}", podcast_id, e);
+                            // Still try to load cached episodes from database
+                            match data_provider.get_all_episodes(podcast_id).await {
+                                Ok(cached_episodes) => {
+                                    warn!("Using cached episodes for podcast {}", podcast_id);
+                                    Ok(EpisodesRefresh {
+                                        podcast_id,
+                                        episodes: cached_episodes,
+                                        refresh_error: Some(e.user_friendly_message()),
+                                        moved: None,
+                                    })
+                                }
+                                Err(db_err) => {
+                                    error!("Failed to load cached episodes: {}", db_err);
+                                    Err(e.user_friendly_message())
+                                }
+                            }
+                        }
+                    };
+                    AsyncActionResult::EpisodesUpdate(refresh)
+                });
+            }
+            AsyncAction::SaveEpisodeState(progress, podcast_id, link) => {
+                let saved = match data_provider.upsert_episode_state(progress, podcast_id, &link).await {
+                    Ok(_) => {
+                        info!("Saved episode state: progress={:.1}s, podcast_id={}", progress, podcast_id);
+                        Ok(())
+                    }
+                    Err(e) => {
+                        error!("Failed to save episode state: {}", e);
+                        Err(RustcastError::from(e).user_friendly_message())
+                    }
+                };
+                ctx.finish(AsyncActionResult::EpisodeStateSaved(saved));
+            }
+            AsyncAction::LoadEpisodeState(link) => {
+                let position = match data_provider.get_episode_state(&link).await {
+                    Ok(Some(state)) => {
+                        info!("Loaded episode state: time={:.1}s for link={}", state.time, link);
+                        Ok(state.time)
+                    }
+                    Ok(None) => {
+                        info!("No saved state found for episode: {}", link);
+                        Ok(0.0)
+                    }
+                    Err(e) => {
+                        error!("Failed to load episode state for {}: {}", link, e);
+                        Err(RustcastError::from(e).user_friendly_message())
+                    }
+                };
+                ctx.finish(AsyncActionResult::EpisodeStateUpdate(position));
+            }
+            AsyncAction::GetAllEpisodeStates(podcast_id) => {
+                let states = match data_provider.get_all_episode_states(podcast_id).await {
+                    Ok(states) => {
+                        info!("Loaded {} episode states for podcast {}", states.len(), podcast_id);
+                        Ok(states)
+                    }
+                    Err(e) => {
+                        error!("Failed to load episode states for podcast {}: {}", podcast_id, e);
+                        Err(RustcastError::from(e).user_friendly_message())
+                    }
+                };
+                ctx.finish(AsyncActionResult::AllEpisodeStatesUpdate(states));
+            }
+            AsyncAction::ImportHistory(source, path) => {
+                let data_provider = data_provider.clone();
+                let network = network.clone();
+                let task_ctx = ctx.clone();
+                ctx.spawn(&in_flight, async move {
+                    match handle_import_history(&data_provider, &network, &task_ctx, source, &path).await {
+                        Ok(report) => {
+                            info!("Imported {} episode states from {}, {} items unmatched",
+                                report.episodes_imported, path, report.unmatched.len());
+                            AsyncActionResult::ImportHistoryResult(Ok(report))
+                        }
+                        Err(e) => {
+                            error!("Failed to import listening history from {}: {}", path, e);
+                            AsyncActionResult::ImportHistoryResult(Err(e.user_friendly_message()))
+                        }
+                    }
+                });
+            }
+            AsyncAction::SearchDirectory(term) => {
+                let network = network.clone();
+                let task_ctx = ctx.clone();
+                ctx.spawn(&in_flight, async move {
+                    let searched = match network.http() {
+                        Ok(http) => directory::search(http, &network.directory_config, &term, &task_ctx.cancel).await,
+                        Err(e) => Err(e),
+                    };
+                    match searched {
+                        Ok(results) => {
+                            info!("Directory search for '{}' returned {} results", term, results.len());
+                            AsyncActionResult::DirectorySearchResult(Ok(results))
+                        }
+                        Err(e) => {
+                            error!("Directory search for '{}' failed: {}", term, e);
+                            AsyncActionResult::DirectorySearchResult(Err(e.user_friendly_message()))
+                        }
+                    }
+                });
+            }
+            AsyncAction::UnlockCredentials(passphrase) => {
+                // Deriving the key is deliberately slow, keep it off the async workers
+                let credentials = credentials.clone();
+                tokio::task::spawn_blocking(move || {
+                    let result = match credentials.write() {
+                        Ok(mut store) => store.unlock(&passphrase),
+                        Err(_) => Err(RustcastError::Credentials(error::CredentialsError::Corrupted)),
+                    };
+                    let unlocked = match result {
+                        Ok(()) => {
+                            info!("Unlocked private feed credentials");
+                            Ok(())
+                        }
+                        Err(e) => {
+                            warn!("Failed to unlock private feed credentials: {}", e);
+                            Err(e.user_friendly_message())
+                        }
+                    };
+                    ctx.finish(AsyncActionResult::CredentialsUnlocked(unlocked));
+                });
+            }
+            AsyncAction::GetCredentials(podcast_id) => {
+                ctx.finish(AsyncActionResult::CredentialsLoaded(
+                    podcast_id,
+                    credentials::credentials_for(&credentials, podcast_id),
+                ));
+            }
+            AsyncAction::SaveCredentials(podcast_id, feed_credentials) => {
+                let result = match credentials.write() {
+                    Ok(mut store) => store.set(podcast_id, feed_credentials),
+                    Err(_) => Err(RustcastError::Credentials(error::CredentialsError::Corrupted)),
+                };
+                let saved = match result {
+                    Ok(()) => {
+                        info!("Saved credentials for podcast {}", podcast_id);
+                        Ok(())
+                    }
+                    Err(e) => {
+                        error!("Failed to save credentials for podcast {}: {}", podcast_id, e);
+                        Err(e.user_friendly_message())
+                    }
+                };
+                ctx.finish(AsyncActionResult::CredentialsSaved(saved));
+            }
+            AsyncAction::RefreshFeeds => {
+                let data_provider = data_provider.clone();
+                let network = network.clone();
+                let task_ctx = ctx.clone();
+                ctx.spawn(&in_flight, async move {
+                    let refreshed = handle_refresh_feeds(&data_provider, &network, &task_ctx).await.map_err(|e| {
+                        error!("Failed to refresh feeds: {}", e);
+                        e.user_friendly_message()
+                    });
+                    AsyncActionResult::FeedsRefreshed(refreshed)
+                });
+            }
+            AsyncAction::UpdatePodcast(podcast_id, edit) => {
+                let saved = match handle_update_podcast(&data_provider, podcast_id, edit).await {
+                    Ok(()) => {
+                        info!("Updated podcast {}", podcast_id);
+                        Ok(())
+                    }
+                    Err(e) => {
+                        error!("Failed to update podcast {}: {}", podcast_id, e);
+                        Err(e.user_friendly_message())
+                    }
+                };
+                ctx.finish(AsyncActionResult::PodcastSaved(saved));
+            }
+            AsyncAction::ReorderPodcasts(podcast_ids) => {
+                let podcasts = match data_provider.set_podcast_order(&podcast_ids).await {
+                    Ok(()) => data_provider.get_podcasts().await,
+                    Err(e) => Err(e),
+                }.map_err(|e| {
+                    error!("Failed to reorder podcasts: {}", e);
+                    RustcastError::from(e).user_friendly_message()
+                });
+                ctx.finish(AsyncActionResult::PodcastsUpdate(podcasts));
+            }
+            AsyncAction::Unsubscribe(podcast_id, keep_history) => {
+                let removed = match handle_unsubscribe(&data_provider, &credentials, &config, podcast_id, keep_history).await {
+                    Ok(()) => {
+                        info!("Unsubscribed from podcast {} (keep history: {})", podcast_id, keep_history);
+                        Ok(())
+                    }
+                    Err(e) => {
+                        error!("Failed to unsubscribe from podcast {}: {}", podcast_id, e);
+                        Err(e.user_friendly_message())
+                    }
+                };
+                ctx.finish(AsyncActionResult::Unsubscribed(podcast_id, removed));
+            }
+            AsyncAction::ApplyConfig(new_config) => {
+                let mut applied = Ok(());
+                // Requests already running keep the client they started with
+                let changed = new_config.http_timeout_seconds != config.http_timeout_seconds || new_config.user_agent != config.user_agent;
+                if changed || network.http.is_err() {
+                    match HttpClient::new(new_config.http_timeout(), &new_config.user_agent) {
+                        Ok(http) => {
+                            info!("Updated HTTP client: timeout={:?}, user agent={}", new_config.http_timeout(), new_config.user_agent);
+                            network.http = Ok(http);
+                        }
+                        Err(e) => {
+                            error!("Failed to update HTTP client: {}", e);
+                            applied = Err(e.user_friendly_message());
+                            // A working client is kept; without one, the reason is brought up to date
+                            if network.http.is_err() {
+                                network.http = Err(http_client_failure(&e));
+                            }
+                        }
+                    }
+                }
+                network.directory_config = DirectoryConfig::from_config(&new_config);
+                config = new_config;
+                ctx.finish(AsyncActionResult::ConfigApplied(applied));
+            }
+            AsyncAction::GetGroups => {
+                let groups = handle_get_groups(&data_provider, &config).await.map_err(|e| {
+                    error!("Failed to load podcast groups: {}", e);
+                    e.user_friendly_message()
+                });
+                ctx.finish(AsyncActionResult::GroupsUpdate(groups));
+            }
+            AsyncAction::CreateTag(name, podcast_id) => {
+                let created = handle_create_tag(&data_provider, &name, podcast_id).await;
+                finish_tag_change(&data_provider, &config, &ctx, created).await;
+            }
+            AsyncAction::RenameTag(tag_id, name) => {
+                let renamed = handle_rename_tag(&data_provider, tag_id, &name).await;
+                finish_tag_change(&data_provider, &config, &ctx, renamed).await;
+            }
+            AsyncAction::DeleteTag(tag_id) => {
+                let deleted = data_provider.delete_tag(tag_id).await.map_err(RustcastError::from);
+                finish_tag_change(&data_provider, &config, &ctx, deleted).await;
+            }
+            AsyncAction::TagPodcast(podcast_id, tag_id) => {
+                let tagged = data_provider.tag_podcast(podcast_id, tag_id).await.map_err(RustcastError::from);
+                finish_tag_change(&data_provider, &config, &ctx, tagged).await;
+            }
+            AsyncAction::UntagPodcast(podcast_id, tag_id) => {
+                let untagged = data_provider.untag_podcast(podcast_id, tag_id).await.map_err(RustcastError::from);
+                finish_tag_change(&data_provider, &config, &ctx, untagged).await;
+            }
+            AsyncAction::GetTagEpisodes(tag_id) => {
+                let episodes = match handle_get_tag_episodes(&data_provider, tag_id).await {
+                    Ok(episodes) => {
+                        info!("Loaded {} episodes tagged {}", episodes.episodes.len(), tag_id);
+                        Ok(episodes)
+                    }
+                    Err(e) => {
+                        error!("Failed to load episodes tagged {}: {}", tag_id, e);
+                        Err(e.user_friendly_message())
+                    }
+                };
+                ctx.finish(AsyncActionResult::TagEpisodesUpdate(episodes));
+            }
+            AsyncAction::GetPlaylists => {
+                let playlists = handle_get_playlists(&data_provider).await.map_err(|e| {
+                    error!("Failed to load playlists: {}", e);
+                    e.user_friendly_message()
+                });
+                ctx.finish(AsyncActionResult::PlaylistsUpdate(playlists));
+            }
+            AsyncAction::SavePlaylist(playlist_id, name, query) => {
+                let playlists = match handle_save_playlist(&data_provider, playlist_id, &name, &query).await {
+                    Ok(()) => handle_get_playlists(&data_provider).await,
+                    Err(e) => Err(e),
+                }.map_err(|e| {
+                    error!("Failed to save playlist {:?}: {}", name, e);
+                    e.user_friendly_message()
+                });
+                ctx.finish(AsyncActionResult::PlaylistsUpdate(playlists));
+            }
+            AsyncAction::DeletePlaylist(playlist_id) => {
+                let playlists = match data_provider.delete_playlist(playlist_id).await {
+                    Ok(()) => handle_get_playlists(&data_provider).await,
+                    Err(e) => Err(e.into()),
+                }.map_err(|e| {
+                    error!("Failed to delete playlist {}: {}", playlist_id, e);
+                    e.user_friendly_message()
+                });
+                ctx.finish(AsyncActionResult::PlaylistsUpdate(playlists));
+            }
+            AsyncAction::GetPlaylistEpisodes(playlist_id) => {
+                let episodes = match handle_get_playlist_episodes(&data_provider, playlist_id).await {
+                    Ok(episodes) => {
+                        info!("Playlist {} holds {} episodes", playlist_id, episodes.episodes.len());
+                        Ok(episodes)
+                    }
+                    Err(e) => {
+                        error!("Failed to load the episodes of playlist {}: {}", playlist_id, e);
+                        Err(e.user_friendly_message())
+                    }
+                };
+                ctx.finish(AsyncActionResult::PlaylistEpisodesUpdate(episodes));
+            }
+            AsyncAction::GetInbox => {
+                let inbox = data_provider.get_inbox().await.map_err(|e| {
+                    error!("Failed to load the inbox: {}", e);
+                    RustcastError::from(e).user_friendly_message()
+                });
+                ctx.finish(AsyncActionResult::InboxUpdate(inbox));
+            }
+            AsyncAction::ArchiveEpisodes(links) => {
+                let inbox = match data_provider.archive_episodes(&links).await {
+                    Ok(()) => data_provider.get_inbox().await,
+                    Err(e) => Err(e),
+                }.map_err(|e| {
+                    error!("Failed to archive {} episodes: {}", links.len(), e);
+                    RustcastError::from(e).user_friendly_message()
+                });
+                ctx.finish(AsyncActionResult::InboxUpdate(inbox));
+            }
+            AsyncAction::MarkPlayed(episodes) => {
+                let inbox = match data_provider.mark_episodes_played(&episodes).await {
+                    Ok(()) => data_provider.get_inbox().await,
+                    Err(e) => Err(e),
+                }.map_err(|e| {
+                    error!("Failed to mark {} episodes as played: {}", episodes.len(), e);
+                    RustcastError::from(e).user_friendly_message()
+                });
+                ctx.finish(AsyncActionResult::InboxUpdate(inbox));
+            }
+            AsyncAction::DownloadEpisode(podcast_id, link) => {
+                let network = network.clone();
+                let download_dir = config.download_dir.clone();
+                let task_ctx = ctx.clone();
+                ctx.spawn(&in_flight, async move {
+                    let downloaded = match handle_download_episode(&network, &task_ctx, &download_dir, podcast_id, &link).await {
+                        Ok(file) => {
+                            info!("Downloaded {} to {}", link, file.display());
+                            Ok(())
+                        }
+                        Err(e) => {
+                            error!("Failed to download {}: {}", link, e);
+                            Err(e.user_friendly_message())
+                        }
+                    };
+                    AsyncActionResult::EpisodeDownloaded(link, downloaded)
+                });
+            }
+            AsyncAction::LogSession(session) => {
+                let logged = data_provider.add_listening_session(session.into_active_model()).await.map_err(|e| {
+                    error!("Failed to log a listening session: {}", e);
+                    RustcastError::from(e).user_friendly_message()
+                });
+                ctx.finish(AsyncActionResult::SessionLogged(logged));
+            }
+            AsyncAction::GetListeningStats => {
+                let stats = handle_get_listening_stats(&data_provider).await.map_err(|e| {
+                    error!("Failed to load listening statistics: {}", e);
+                    e.user_friendly_message()
+                });
+                ctx.finish(AsyncActionResult::ListeningStatsUpdate(stats));
+            }
+            AsyncAction::ExportHistory(file) => {
+                let exported = match handle_export_history(&data_provider, &file).await {
+                    Ok(()) => {
+                        info!("Exported listening history to {}", file.display());
+                        Ok(file)
+                    }
+                    Err(e) => {
+                        error!("Failed to export listening history: {}", e);
+                        Err(e.user_friendly_message())
+                    }
+                };
+                ctx.finish(AsyncActionResult::HistoryExported(exported));
+            }
+            AsyncAction::LoadArtwork(url) => {
+                let network = network.clone();
+                let artwork_dir = config.artwork_dir();
+                let task_ctx = ctx.clone();
+                ctx.spawn(&in_flight, async move {
+                    let thumbnail = match network.http() {
+                        Ok(http) => artwork::thumbnail(http, &task_ctx.cancel, &artwork_dir, &url).await,
+                        Err(e) => Err(e),
+                    };
+                    let thumbnail = thumbnail.map_err(|e| {
+                        warn!("Failed to load artwork {}: {}", url, e);
+                        e.user_friendly_message()
+                    });
+                    AsyncActionResult::ArtworkLoaded(url, thumbnail)
+                });
+            }
+            AsyncAction::GetBookmarks => {
+                let bookmarks = data_provider.get_bookmarks().await.map_err(|e| {
+                    error!("Failed to load bookmarks: {}", e);
+                    RustcastError::from(e).user_friendly_message()
+                });
+                ctx.finish(AsyncActionResult::BookmarksUpdate(bookmarks));
+            }
+            AsyncAction::AddBookmark(bookmark) => {
+                let added = match bookmarks::validate_range(bookmark.position, bookmark.end_position) {
+                    Ok(()) => data_provider.add_bookmark(bookmark.into_active_model()).await.map_err(RustcastError::from),
+                    Err(e) => Err(e),
+                };
+                finish_bookmark_change(&data_provider, &ctx, added).await;
+            }
+            AsyncAction::UpdateBookmark(bookmark_id, end_position, note) => {
+                let updated = match data_provider.get_bookmark(bookmark_id).await {
+                    Ok(Some(bookmark)) => match bookmarks::validate_range(bookmark.position, end_position) {
+                        Ok(()) => data_provider.update_bookmark(bookmark_id, end_position, note.trim()).await.map_err(RustcastError::from),
+                        Err(e) => Err(e),
+                    },
+                    Ok(None) => Err(RustcastError::Database(error::DatabaseError::DataNotFound(format!("bookmark {}", bookmark_id)))),
+                    Err(e) => Err(e.into()),
+                };
+                finish_bookmark_change(&data_provider, &ctx, updated).await;
+            }
+            AsyncAction::DeleteBookmark(bookmark_id) => {
+                let deleted = data_provider.delete_bookmark(bookmark_id).await.map_err(RustcastError::from);
+                finish_bookmark_change(&data_provider, &ctx, deleted).await;
+            }
+            AsyncAction::ExportBookmarks(file) => {
+                let exported = match handle_export_bookmarks(&data_provider, &file).await {
+                    Ok(()) => {
+                        info!("Exported bookmarks to {}", file.display());
+                        Ok(file)
+                    }
+                    Err(e) => {
+                        error!("Failed to export bookmarks: {}", e);
+                        Err(e.user_friendly_message())
+                    }
+                };
+                ctx.finish(AsyncActionResult::BookmarksExported(exported));
+            }
+            AsyncAction::FindEpisode(link) => {
+                let episode = data_provider.get_episode_by_link(&link).await.map(|episode| episode.map(Box::new)).map_err(|e| {
+                    error!("Failed to look up episode {}: {}", link, e);
+                    RustcastError::from(e).user_friendly_message()
+                });
+                ctx.finish(AsyncActionResult::EpisodeFound(link, episode));
+            }
+            AsyncAction::ExportClip(request) => {
+                let network = network.clone();
+                let download_dir = config.download_dir.clone();
+                let task_ctx = ctx.clone();
+                ctx.spawn(&in_flight, async move {
+                    let clip_request = request.clone();
+                    let exported = decode_episode(&network, &task_ctx, &download_dir, request.podcast_id, &request.ep_link,
+                        move |source, cancel| clip::export_clip(source, &clip_request, cancel)).await;
+                    let exported = match exported {
+                        Ok(seconds) => {
+                            info!("Exported a {:.1}s clip of {} to {}", seconds, request.ep_link, request.file.display());
+                            Ok((request.file.clone(), seconds))
+                        }
+                        Err(e) => {
+                            error!("Failed to export a clip of {}: {}", request.ep_link, e);
+                            Err(e.user_friendly_message())
+                        }
+                    };
+                    AsyncActionResult::ClipExported(exported)
+                });
+            }
+            AsyncAction::DetectSilences(podcast_id, link, min_seconds) => {
+                let network = network.clone();
+                let download_dir = config.download_dir.clone();
+                let task_ctx = ctx.clone();
+                ctx.spawn(&in_flight, async move {
+                    let detected = decode_episode(&network, &task_ctx, &download_dir, podcast_id, &link,
+                        move |source, cancel| silence::detect(source, min_seconds, cancel)).await;
+                    let detected = match detected {
+                        Ok(silences) => {
+                            info!("Found {} silences in {}", silences.len(), link);
+                            Ok(silences)
+                        }
+                        Err(e) => {
+                            error!("Failed to find silences in {}: {}", link, e);
+                            Err(e.user_friendly_message())
+                        }
+                    };
+                    AsyncActionResult::SilencesDetected(link, detected)
+                });
+            }
+            AsyncAction::ProbeEnclosure(podcast_id, link, url) => {
+                let network = network.clone();
+                let task_ctx = ctx.clone();
+                ctx.spawn(&in_flight, async move {
+                    let probed = handle_probe_enclosure(&network, &task_ctx, podcast_id, &url).await.map_err(|e| {
+                        error!("Enclosure {} can't be played: {}", url, e);
+                        e.user_friendly_message()
+                    });
+                    AsyncActionResult::EnclosureProbed(link, probed)
+                });
+            }
+            AsyncAction::RetryDatabase | AsyncAction::OpenDatabase(_) | AsyncAction::RestoreDatabaseBackup => {
+                warn!("Database is already open, ignoring {:?}", action);
+            }
+            AsyncAction::Cancel(request_id) => {
+                if let Some(cancel) = in_flight.lock().ok().and_then(|in_flight| in_flight.get(&request_id).cloned()) {
+                    info!("Cancelling request {}", request_id);
+                    cancel.cancel();
+                }
+            }
+        }
+    }
+}
+
+/// Opens the database, and if that fails keeps reporting why to the UI and waits for it to
+/// pick a way out. Returns `None` if the UI goes away in the meantime.
+async fn open_database(
+    mut location: DatabaseLocation,
+    rx: &mut UnboundedReceiver<AsyncRequest>,
+    tx: &UnboundedSender<AsyncResponse>,
+    waker: &UiWaker,
+) -> Option<DatabaseConnection> {
+    let mut ctx = ActionContext::new(0, tx.clone(), waker.clone());
+    let mut recovering = false;
+
+    loop {
+        match database::open(&location).await {
+            Ok(db) => {
+                if recovering {
+                    info!("Opened database {} after recovery", location.file.display());
+                    ctx.finish(AsyncActionResult::DatabaseReady(location.file.clone()));
+                }
+                return Some(db);
+            }
+            Err(e) => {
+                error!("Failed to open database {}: {}", location.file.display(), e);
+                ctx.finish(AsyncActionResult::DatabaseUnavailable(DatabaseFailure {
+                    error: format!("{}\n\n{}", e.user_friendly_message(), e),
+                    file: location.file.clone(),
+                    latest_backup: location.latest_backup(),
+                }));
+            }
+        }
+        recovering = true;
+
+        // Nothing but recovery makes sense without a database
+        loop {
+            let AsyncRequest { id, action } = rx.recv().await?;
+            ctx = ActionContext::new(id, tx.clone(), waker.clone());
+            match action {
+                AsyncAction::RetryDatabase => break,
+                AsyncAction::OpenDatabase(file) => {
+                    // A file picked by hand wins over the environment
+                    location.file = file;
+                    location.url_override = None;
+                    break;
+                }
+                AsyncAction::RestoreDatabaseBackup => match database::restore_latest_backup(&location) {
+                    Ok(_) => break,
+                    Err(e) => {
+                        error!("Failed to restore database backup: {}", e);
+                        ctx.finish(AsyncActionResult::DatabaseUnavailable(DatabaseFailure {
+                            error: e.user_friendly_message(),
+                            file: location.file.clone(),
+                            latest_backup: location.latest_backup(),
+                        }));
+                    }
+                },
+                action => {
+                    warn!("Ignoring {:?} while the database is unavailable", action);
+                    ctx.abandon();
+                }
+            }
+        }
+    }
+}
+
+fn http_client_failure(error: &RustcastError) -> String {
+    format!(
+        "Rustcast couldn't set up its network connection: {}\n\nCheck the user agent and timeout in the settings.",
+        error.user_friendly_message()
+    )
+}
+
+enum AddPodcastOutcome {
+    Added,
+    ChooseFeed(Vec<FeedCandidate>),
+}
+
+/// What the network-bound handlers need, cheap to clone into their tasks
+#[derive(Clone)]
+struct NetworkContext {
+    /// The client, or why none could be built from the settings
+    http: Result<HttpClient, String>,
+    credentials: SharedCredentials,
+    directory_config: DirectoryConfig,
+}
+
+impl NetworkContext {
+    fn http(&self) -> RustcastResult<&HttpClient> {
+        self.http.as_ref().map_err(|reason| RustcastError::Network(NetworkError::Unavailable(reason.clone())))
+    }
+}
+
+async fn handle_add_podcast(
+    data_provider: &DataProvider,
+    network: &NetworkContext,
+    ctx: &ActionContext,
+    title: String,
+    link: String,
+    description: String,
+    feed_credentials: Option<FeedCredentials>,
+) -> RustcastResult<AddPodcastOutcome> {
+    // Validate input data
+    utils::validate_podcast_data(&title, &link, &description)?;
+
+    let feed_credentials = feed_credentials.filter(|c| !c.is_empty());
+    if feed_credentials.is_some() && !network.credentials.read().map(|store| store.is_unlocked()).unwrap_or(false) {
+        return Err(RustcastError::Credentials(error::CredentialsError::Locked));
+    }
+
+    // Apple Podcasts pages don't link their feed, so ask the lookup API instead
+    let mut link = match feed_discovery::apple_podcasts_id(&link) {
+        Some(itunes_id) => directory::lookup_feed_url(network.http()?, &network.directory_config, &itunes_id, &ctx.cancel).await?,
+        None => link,
+    };
+
+    let mut document = network.http()?.fetch_document(&link, feed_credentials.as_ref(), &ctx.cancel, ctx.download_progress()).await?;
+
+    // A show's homepage usually announces its feed through <link rel="alternate">
+    if feed_discovery::is_html(&document.content_type, &document.content) {
+        let mut candidates = feed_discovery::find_feed_links(&document.content, &document.url);
+        match candidates.len() {
+            0 => {
+                return Err(RustcastError::Rss(RssError::InvalidFeed(
+                    "The page does not link to a podcast feed".to_string()
+                )));
+            }
+            1 => {
+                link = candidates.remove(0).url;
+                document = network.http()?.fetch_document(&link, feed_credentials.as_ref(), &ctx.cancel, ctx.download_progress()).await?;
+            }
+            _ => return Ok(AddPodcastOutcome::ChooseFeed(candidates)),
+        }
+    }
+
+    // Validate the RSS feed
+    let channel = utils::safe_rss_parse(&document.content)?;
+
+    // Subscribe to where the feed lives now rather than a URL that is already being redirected
+    if let Some(redirect) = document.permanent_redirect() {
+        info!("Feed {} permanently moved to {}, subscribing to the new URL", link, redirect.to);
+        link = redirect.to.clone();
+    }
+
+    // If we get here, the feed is valid, so add it to the database
+    let podcast = data_provider.add_podcast(title, link, description).await
         .map_err(RustcastError::from)?;
+    data_provider.set_podcast_image(podcast.id, utils::channel_image(&channel)).await?;
 
-    Ok(())
+    if feed_credentials.is_some() {
+        network.credentials.write()
+            .map_err(|_| RustcastError::Credentials(error::CredentialsError::Corrupted))?
+            .set(podcast.id, feed_credentials)?;
+    }
+
+    Ok(AddPodcastOutcome::Added)
+}
+
+struct FeedRefresh {
+    episodes: Vec<episode::Model>,
+    moved_to: Option<String>,
 }
 
 async fn handle_get_episodes(
     data_provider: &DataProvider,
+    network: &NetworkContext,
+    ctx: &ActionContext,
     link: &str,
     podcast_id: i32,
-) -> RustcastResult<Vec<episode::Model>> {
+) -> RustcastResult<FeedRefresh> {
+    let feed_credentials = credentials::credentials_for(&network.credentials, podcast_id);
+
     // Fetch and parse RSS feed
-    let response = utils::safe_network_request(link)?;
-    let content = response.into_string()
-        .map_err(|e| RustcastError::Network(error::NetworkError::InvalidResponse(e.to_string())))?;
+    let document = network.http()?.fetch_document(link, feed_credentials.as_ref(), &ctx.cancel, ctx.download_progress()).await?;
+    let mut channel = utils::safe_rss_parse(&document.content)?;
+    ctx.progress(Progress::Parsed(channel.items().len()));
+    let mut moved_to = None;
+
+    // Publishers announce host migrations through permanent redirects or <itunes:new-feed-url>.
+    // Temporary redirects are followed above but never persisted.
+    if let Some(redirect) = document.permanent_redirect().filter(|r| utils::feed_url_changed(link, &r.to)) {
+        let validated = match move_credentials(link, &redirect.to, feed_credentials.as_ref()) {
+            Err(e) => Err(e),
+            Ok(_) if redirect.to == document.url => Ok(()),
+            Ok(credentials) => match network.http()?.fetch_document(&redirect.to, credentials, &ctx.cancel, ctx.download_progress()).await {
+                Ok(d) => utils::safe_rss_parse(&d.content).map(|_| ()),
+                Err(e) => Err(e),
+            },
+        };
 
-    let channel = utils::safe_rss_parse(&content)?;
+        match validated {
+            Err(e @ RustcastError::Network(NetworkError::Cancelled)) => return Err(e),
+            Ok(()) => {
+                data_provider.move_podcast_feed(podcast_id, link, &redirect.to, &format!("http-{}", redirect.status)).await?;
+                info!("Podcast {} feed permanently redirected ({}) to {}", podcast_id, redirect.status, redirect.to);
+                moved_to = Some(redirect.to.clone());
+            }
+            Err(e) => warn!("Ignoring permanent redirect of {} to {}: {}", link, redirect.to, e),
+        }
+    } else if let Some(new_url) = channel.itunes_ext()
+        .and_then(|ext| ext.new_feed_url())
+        .map(|url| url.trim().to_string())
+        .filter(|url| !url.is_empty() && utils::feed_url_changed(link, url))
+    {
+        // A move named by the feed itself is what a hijacked feed would announce; a locked feed
+        // only moves through its host's redirect
+        let validated = match move_credentials(link, &new_url, feed_credentials.as_ref()) {
+            Ok(_) if utils::channel_locked(&channel) => Err(RustcastError::Rss(RssError::InvalidFeed(
+                "the feed is locked against moving to another host".to_string()
+            ))),
+            Err(e) => Err(e),
+            Ok(credentials) => match network.http()?.fetch_document(&new_url, credentials, &ctx.cancel, ctx.download_progress()).await {
+                Ok(d) => utils::safe_rss_parse(&d.content).map(|c| (d, c)),
+                Err(e) => Err(e),
+            },
+        };
 
-    // Clear old episodes and add new ones
-    data_provider.delete_episodes_by_podcast_id(podcast_id).await
-        .map_err(RustcastError::from)?;
+        match validated {
+            Err(e @ RustcastError::Network(NetworkError::Cancelled)) => return Err(e),
+            Ok((new_document, new_channel)) => {
+                let new_url = new_document.permanent_redirect()
+                    .map(|r| r.to.clone())
+                    .unwrap_or(new_url);
+                data_provider.move_podcast_feed(podcast_id, link, &new_url, "itunes:new-feed-url").await?;
+                info!("Podcast {} feed moved to {} via itunes:new-feed-url", podcast_id, new_url);
+                channel = new_channel;
+                moved_to = Some(new_url);
+            }
+            Err(e) => warn!("Ignoring itunes:new-feed-url {} for {}: {}", new_url, link, e),
+        }
+    }
 
-    data_provider.add_episodes(channel.items().to_vec(), podcast_id).await?;
+    data_provider.replace_episodes(channel.items().to_vec(), podcast_id).await?;
+    data_provider.set_podcast_image(podcast_id, utils::channel_image(&channel)).await?;
 
     // Return the updated episodes
-    data_provider.get_all_episodes(podcast_id).await
-        .map_err(RustcastError::from)
+    let episodes = data_provider.get_all_episodes(podcast_id).await
+        .map_err(RustcastError::from)?;
+
+    Ok(FeedRefresh {
+        episodes,
+        moved_to,
+    })
+}
+
+/// Credentials to check a feed's new URL with: only those `forwards_credentials` lets through
+/// to it. A feed with credentials never moves from https to plain http, where every later
+/// refresh would send them in the clear.
+fn move_credentials<'a>(link: &str, target: &str, credentials: Option<&'a FeedCredentials>) -> RustcastResult<Option<&'a FeedCredentials>> {
+    let Some(credentials) = credentials else {
+        return Ok(None);
+    };
+    let target = url::Url::parse(target).map_err(|_| RustcastError::network_invalid_url(target))?;
+    let Ok(configured) = url::Url::parse(link) else {
+        return Ok(None);
+    };
+    if configured.scheme() == "https" && target.scheme() != "https" {
+        return Err(RustcastError::Network(NetworkError::RequestFailed(
+            "the feed has credentials and would move to plain http".to_string()
+        )));
+    }
+    Ok(Some(credentials).filter(|_| http::forwards_credentials(&configured, &target)))
+}
+
+async fn handle_update_podcast(data_provider: &DataProvider, podcast_id: i32, edit: PodcastEdit) -> RustcastResult<()> {
+    let link = edit.link.trim();
+    utils::validate_podcast_url(link)?;
+    loudness::validate_podcast_gain(edit.gain_db)?;
+
+    let podcast = data_provider.get_podcast(podcast_id).await?
+        .ok_or_else(|| RustcastError::Database(error::DatabaseError::DataNotFound(format!("podcast {}", podcast_id))))?;
+    // A feed URL changed by hand is kept in the history like any other move
+    match podcast.link.as_deref() {
+        Some(old_link) if utils::feed_url_changed(old_link, link) =>
+            data_provider.move_podcast_feed(podcast_id, old_link, link, "edited").await?,
+        _ => {}
+    }
+
+    data_provider.update_podcast(podcast_id, &edit).await?;
+    Ok(())
+}
+
+async fn handle_unsubscribe(
+    data_provider: &DataProvider,
+    credentials: &SharedCredentials,
+    config: &Config,
+    podcast_id: i32,
+    keep_history: bool,
+) -> RustcastResult<()> {
+    data_provider.unsubscribe_podcast(podcast_id, keep_history).await?;
+
+    if !keep_history {
+        // A locked store can't be changed; the orphaned entry is harmless as ids aren't reused
+        if let Ok(mut store) = credentials.write() {
+            if store.is_unlocked() {
+                store.set(podcast_id, None)?;
+            }
+        }
+    }
+
+    downloads::remove_podcast_downloads(&config.download_dir, podcast_id).await
+}
+
+/// Podcasts with an unplayed episode published within this many days count as having new episodes
+const NEW_EPISODE_DAYS: i64 = 7;
+
+async fn handle_get_groups(data_provider: &DataProvider, config: &Config) -> RustcastResult<PodcastGroups> {
+    let tags = data_provider.get_tags().await?
+        .into_iter()
+        .map(|(tag, podcast_ids)| TagGroup { tag, podcast_ids })
+        .collect();
+
+    let since = utils::unix_timestamp() - NEW_EPISODE_DAYS * 24 * 60 * 60;
+    let mut new_episodes: Vec<i32> = data_provider.get_unplayed_episode_dates().await?
+        .into_iter()
+        .filter(|(_, pub_date)| pub_date.as_deref().and_then(utils::pub_date_timestamp).is_some_and(|t| t >= since))
+        .map(|(podcast_id, _)| podcast_id)
+        .collect();
+    new_episodes.sort_unstable();
+    new_episodes.dedup();
+
+    Ok(PodcastGroups {
+        tags,
+        new_episodes,
+        in_progress: data_provider.get_podcasts_in_progress().await?,
+        downloaded: downloads::downloaded_podcasts(&config.download_dir).await,
+    })
+}
+
+/// Tag names are trimmed and have to be unique
+async fn validate_tag_name(data_provider: &DataProvider, name: &str, tag_id: Option<i32>) -> RustcastResult<String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(RustcastError::Database(error::DatabaseError::ConstraintViolation(
+            "A tag needs a name.".to_string()
+        )));
+    }
+    match data_provider.find_tag(name).await? {
+        Some(existing) if Some(existing.id) != tag_id => Err(RustcastError::Database(
+            error::DatabaseError::ConstraintViolation(format!("There already is a tag named \"{}\".", name))
+        )),
+        _ => Ok(name.to_string()),
+    }
+}
+
+async fn handle_create_tag(data_provider: &DataProvider, name: &str, podcast_id: Option<i32>) -> RustcastResult<()> {
+    let name = validate_tag_name(data_provider, name, None).await?;
+    let tag = data_provider.create_tag(&name).await?;
+    if let Some(podcast_id) = podcast_id {
+        data_provider.tag_podcast(podcast_id, tag.id).await?;
+    }
+    Ok(())
 }
 
-fn format_time(seconds: f64) -> String {
-    if seconds <= 0.0 {
-        return "Not started".to_string();
+async fn handle_rename_tag(data_provider: &DataProvider, tag_id: i32, name: &str) -> RustcastResult<()> {
+    let name = validate_tag_name(data_provider, name, Some(tag_id)).await?;
+    data_provider.rename_tag(tag_id, &name).await?;
+    Ok(())
+}
+
+/// Answers a change to the tags with the groups as they are now, or with why it failed
+async fn finish_tag_change(data_provider: &DataProvider, config: &Config, ctx: &ActionContext, changed: RustcastResult<()>) {
+    let groups = match changed {
+        Ok(()) => handle_get_groups(data_provider, config).await,
+        Err(e) => Err(e),
+    }.map_err(|e| {
+        error!("Failed to change tags: {}", e);
+        e.user_friendly_message()
+    });
+    ctx.finish(AsyncActionResult::GroupsUpdate(groups));
+}
+
+async fn handle_get_tag_episodes(data_provider: &DataProvider, tag_id: i32) -> RustcastResult<TagEpisodes> {
+    let podcast_ids = data_provider.get_tagged_podcast_ids(tag_id).await?;
+    let mut episodes = data_provider.get_episodes_of_podcasts(&podcast_ids).await?;
+    episodes.sort_by_key(|e| std::cmp::Reverse(e.pub_date.as_deref().and_then(utils::pub_date_timestamp)));
+
+    Ok(TagEpisodes {
+        tag_id,
+        episodes,
+        episode_states: data_provider.get_episode_states_of_podcasts(&podcast_ids).await?,
+    })
+}
+
+/// Playlists whose query can't be read are left out rather than hiding all the others
+async fn handle_get_playlists(data_provider: &DataProvider) -> RustcastResult<Vec<Playlist>> {
+    Ok(data_provider.get_playlists().await?
+        .into_iter()
+        .filter_map(|model| Playlist::try_from(model)
+            .map_err(|e| warn!("Skipping playlist: {}", e))
+            .ok())
+        .collect())
+}
+
+async fn handle_save_playlist(data_provider: &DataProvider, playlist_id: Option<i32>, name: &str, query: &PlaylistQuery) -> RustcastResult<()> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(RustcastError::Database(error::DatabaseError::ConstraintViolation(
+            "A playlist needs a name.".to_string()
+        )));
+    }
+    let query = serde_json::to_string(query)
+        .map_err(|e| RustcastError::Database(error::DatabaseError::QueryFailed(e.to_string())))?;
+
+    data_provider.save_playlist(playlist_id, name, &query).await?;
+    Ok(())
+}
+
+async fn handle_get_playlist_episodes(data_provider: &DataProvider, playlist_id: i32) -> RustcastResult<PlaylistEpisodes> {
+    let playlist: Playlist = data_provider.get_playlist(playlist_id).await?
+        .ok_or_else(|| RustcastError::Database(error::DatabaseError::DataNotFound(format!("playlist {}", playlist_id))))?
+        .try_into()?;
+
+    let states = data_provider.get_every_episode_state().await?;
+    let podcast_tags = data_provider.get_podcast_tags().await?;
+    let episodes = playlist.query.evaluate(
+        data_provider.get_every_episode().await?,
+        &EpisodeFacts { states: &states, podcast_tags: &podcast_tags },
+    );
+
+    Ok(PlaylistEpisodes {
+        playlist_id,
+        episodes,
+        episode_states: states.into_iter().map(|(link, state)| (link, state.time)).collect(),
+    })
+}
+
+async fn handle_download_episode(
+    network: &NetworkContext,
+    ctx: &ActionContext,
+    download_dir: &std::path::Path,
+    podcast_id: i32,
+    link: &str,
+) -> RustcastResult<std::path::PathBuf> {
+    let file = downloads::episode_file(download_dir, podcast_id, link);
+    if file.exists() {
+        return Ok(file);
     }
+    tokio::fs::create_dir_all(downloads::podcast_dir(download_dir, podcast_id)).await
+        .map_err(|e| RustcastError::Download(error::DownloadError::Io(e.to_string())))?;
 
-    let total_seconds = seconds as i64;
-    let hours = total_seconds / 3600;
-    let minutes = (total_seconds % 3600) / 60;
-    let secs = total_seconds % 60;
+    let feed_credentials = credentials::credentials_for(&network.credentials, podcast_id);
+    network.http()?.download(link, feed_credentials.as_ref(), &ctx.cancel, &file, ctx.download_progress()).await?;
+    Ok(file)
+}
 
-    if hours > 0 {
-        format!("{}:{:02}:{:02}", hours, minutes, secs)
+/// Fails when the server won't serve the enclosure or serves a type the player can't decode.
+/// Anything less certain, like a timeout, is left for the player to run into.
+async fn handle_probe_enclosure(
+    network: &NetworkContext,
+    ctx: &ActionContext,
+    podcast_id: i32,
+    url: &str,
+) -> RustcastResult<()> {
+    let feed_credentials = credentials::credentials_for(&network.credentials, podcast_id);
+    match network.http()?.probe(url, feed_credentials.as_ref(), &ctx.cancel).await {
+        Ok(content_type) => player::check_playable(content_type.as_deref()).map_err(RustcastError::Player),
+        Err(RustcastError::Network(NetworkError::RequestFailed(msg))) =>
+            Err(RustcastError::Player(PlayerError::OpenFailed(msg))),
+        Err(e) => {
+            warn!("Couldn't check enclosure {}: {}", url, e);
+            Ok(())
+        }
+    }
+}
+
+/// Decodes an episode on a blocking thread: the downloaded file if there is one, otherwise
+/// a temporary copy of the stream that's removed again afterwards
+async fn decode_episode<T: Send + 'static>(
+    network: &NetworkContext,
+    ctx: &ActionContext,
+    download_dir: &std::path::Path,
+    podcast_id: i32,
+    link: &str,
+    decode: impl FnOnce(&std::path::Path, &tokio_util::sync::CancellationToken) -> RustcastResult<T> + Send + 'static,
+) -> RustcastResult<T> {
+    let downloaded = downloads::episode_file(download_dir, podcast_id, link);
+    let (source, temporary) = if downloaded.exists() {
+        (downloaded, false)
     } else {
-        format!("{}:{:02}", minutes, secs)
+        let name = downloaded.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+        let file = std::env::temp_dir().join(format!("rustcast-{}-{}", ctx.request_id, name));
+        let feed_credentials = credentials::credentials_for(&network.credentials, podcast_id);
+        network.http()?.download(link, feed_credentials.as_ref(), &ctx.cancel, &file, ctx.download_progress()).await?;
+        (file, true)
+    };
+
+    ctx.progress(Progress::Decoding);
+    let decode_source = source.clone();
+    let cancel = ctx.cancel.clone();
+    let decoded = tokio::task::spawn_blocking(move || decode(&decode_source, &cancel)).await
+        .map_err(|e| RustcastError::Export(error::ExportError::Decode(e.to_string())));
+    if temporary {
+        if let Err(e) = tokio::fs::remove_file(&source).await {
+            warn!("Failed to remove {}: {}", source.display(), e);
+        }
+    }
+    decoded?
+}
+
+/// Titles of every stored podcast by id, so history of unsubscribed ones still has a name
+async fn podcast_titles(data_provider: &DataProvider) -> RustcastResult<std::collections::HashMap<i32, String>> {
+    Ok(data_provider.get_all_podcasts().await?
+        .into_iter()
+        .filter_map(|podcast| podcast.display_title().map(|title| (podcast.id, title.to_string())))
+        .collect())
+}
+
+async fn handle_get_listening_stats(data_provider: &DataProvider) -> RustcastResult<ListeningStats> {
+    let sessions = data_provider.get_listening_sessions().await?;
+    Ok(history::listening_stats(&sessions, &podcast_titles(data_provider).await?))
+}
+
+async fn handle_export_history(data_provider: &DataProvider, file: &std::path::Path) -> RustcastResult<()> {
+    let sessions = data_provider.get_listening_sessions().await?;
+    let csv = history::history_csv(&sessions, &podcast_titles(data_provider).await?);
+    tokio::fs::write(file, csv).await
+        .map_err(|e| RustcastError::Export(error::ExportError::Io(format!("{}: {}", file.display(), e))))
+}
+
+/// Answers a change to the bookmarks with all of them as they are now, or with why it failed
+async fn finish_bookmark_change(data_provider: &DataProvider, ctx: &ActionContext, changed: RustcastResult<()>) {
+    let bookmarks = match changed {
+        Ok(()) => data_provider.get_bookmarks().await.map_err(RustcastError::from),
+        Err(e) => Err(e),
+    }.map_err(|e| {
+        error!("Failed to change bookmarks: {}", e);
+        e.user_friendly_message()
+    });
+    ctx.finish(AsyncActionResult::BookmarksUpdate(bookmarks));
+}
+
+async fn handle_export_bookmarks(data_provider: &DataProvider, file: &std::path::Path) -> RustcastResult<()> {
+    let bookmarks = data_provider.get_bookmarks().await?;
+    let markdown = bookmarks::bookmarks_markdown(&bookmarks, &podcast_titles(data_provider).await?);
+    tokio::fs::write(file, markdown).await
+        .map_err(|e| RustcastError::Export(error::ExportError::Io(format!("{}: {}", file.display(), e))))
+}
+
+/// Refreshes every subscribed feed in turn, skipping the ones that fail
+async fn handle_refresh_feeds(
+    data_provider: &DataProvider,
+    network: &NetworkContext,
+    ctx: &ActionContext,
+) -> RustcastResult<Vec<EpisodesRefresh>> {
+    let podcasts = data_provider.get_podcasts().await?;
+    let mut refreshed = Vec::new();
+
+    for podcast in podcasts {
+        let Some(link) = podcast.link else {
+            continue;
+        };
+
+        match handle_get_episodes(data_provider, network, ctx, &link, podcast.id).await {
+            Ok(refresh) => refreshed.push(EpisodesRefresh {
+                podcast_id: podcast.id,
+                episodes: refresh.episodes,
+                refresh_error: None,
+                moved: refresh.moved_to.map(|new_url| (link, new_url)),
+            }),
+            Err(e @ RustcastError::Network(NetworkError::Cancelled)) => return Err(e),
+            Err(e) => warn!("Failed to refresh podcast {}: {}", podcast.id, e),
+        }
+    }
+
+    info!("Refreshed {} feeds", refreshed.len());
+    Ok(refreshed)
+}
+
+async fn handle_import_history(
+    data_provider: &DataProvider,
+    network: &NetworkContext,
+    ctx: &ActionContext,
+    source: ImportSource,
+    path: &str,
+) -> RustcastResult<ImportReport> {
+    let imported = importer::read_history(source, path).await?;
+    ctx.progress(Progress::Parsed(imported.len()));
+    let mut podcasts = data_provider.get_podcasts().await?;
+    let mut report = ImportReport::default();
+
+    for imported_podcast in imported {
+        let existing = podcasts.iter()
+            .find(|p| p.link.as_deref()
+                .map(|link| utils::same_feed_url(link, &imported_podcast.feed_url))
+                .unwrap_or(false))
+            .cloned();
+
+        let podcast = match existing {
+            Some(podcast) => {
+                report.podcasts_existing += 1;
+                podcast
+            }
+            None => {
+                let podcast = data_provider.add_podcast(
+                    imported_podcast.title.clone(),
+                    imported_podcast.feed_url.clone(),
+                    imported_podcast.description.clone(),
+                ).await?;
+                podcasts.push(podcast.clone());
+                report.podcasts_created += 1;
+                podcast
+            }
+        };
+
+        if imported_podcast.episodes.is_empty() {
+            continue;
+        }
+
+        // Episodes have to be known locally before their state can be matched
+        let feed_url = podcast.link.clone().unwrap_or_default();
+        let (episodes, refresh_error) = match handle_get_episodes(data_provider, network, ctx, &feed_url, podcast.id).await {
+            Ok(refresh) => (refresh.episodes, None),
+            Err(e) => {
+                warn!("Failed to refresh '{}' during import: {}", imported_podcast.title, e);
+                (data_provider.get_all_episodes(podcast.id).await?, Some(e.user_friendly_message()))
+            }
+        };
+
+        for imported_episode in imported_podcast.episodes {
+            match importer::match_episode(&imported_episode, &episodes).and_then(|e| e.link.as_ref()) {
+                Some(link) => {
+                    data_provider.import_episode_state(
+                        imported_episode.position,
+                        imported_episode.finished,
+                        podcast.id,
+                        link,
+                    ).await?;
+                    report.episodes_imported += 1;
+                }
+                None => report.unmatched.push(UnmatchedItem {
+                    podcast_title: imported_podcast.title.clone(),
+                    episode_title: Some(imported_episode.title),
+                    reason: refresh_error.clone().unwrap_or_else(||
+                        "No episode with the same GUID or enclosure URL in the feed".to_string()
+                    ),
+                }),
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::time::Duration;
+    use crate::protocol::RequestId;
+    use super::*;
+
+    fn request(id: RequestId, action: AsyncAction) -> AsyncRequest {
+        AsyncRequest { id, action }
+    }
+
+    fn finished(response: AsyncResponse) -> (RequestId, AsyncActionResult) {
+        match response.event {
+            AsyncEvent::Finished(result) => (response.request_id, result),
+            event => panic!("Expected a result, got {:?}", event),
+        }
+    }
+
+    #[test]
+    fn idle_ui_is_not_redrawn() {
+        for state in [PlayerState::Paused, PlayerState::Ended, PlayerState::Error] {
+            assert_eq!(repaint_after(&state, None), None);
+        }
+    }
+
+    #[test]
+    fn active_player_is_redrawn_steadily() {
+        for state in [PlayerState::Playing, PlayerState::Loading, PlayerState::Buffering] {
+            assert_eq!(repaint_after(&state, None), Some(PLAYING_REPAINT_INTERVAL));
+        }
+    }
+
+    #[test]
+    fn next_refresh_gets_a_frame() {
+        let hour = Duration::from_secs(3600);
+        assert_eq!(repaint_after(&PlayerState::Paused, Some(hour)), Some(hour));
+        assert_eq!(repaint_after(&PlayerState::Playing, Some(hour)), Some(PLAYING_REPAINT_INTERVAL));
+
+        let soon = Duration::from_millis(100);
+        assert_eq!(repaint_after(&PlayerState::Playing, Some(soon)), Some(soon));
+    }
+
+    #[tokio::test]
+    async fn worker_keeps_the_library_without_an_http_client() {
+        let file = std::env::temp_dir().join(format!("rustcast-worker-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&file);
+        let location = DatabaseLocation {
+            file: file.clone(),
+            url_override: None,
+            backup_dir: std::env::temp_dir(),
+        };
+        let data_provider = DataProvider::new(database::open(&location).await.unwrap());
+        let error = RustcastError::Network(NetworkError::RequestFailed("no TLS backend".to_string()));
+        let network = NetworkContext {
+            http: Err(http_client_failure(&error)),
+            credentials: CredentialStore::shared(file.with_extension("credentials")),
+            directory_config: DirectoryConfig::from_config(&Config::default()),
+        };
+
+        let (action_tx, mut action_rx) = unbounded_channel();
+        let (result_tx, mut result_rx) = unbounded_channel();
+        action_tx.send(request(1, AsyncAction::GetPodcasts)).unwrap();
+        action_tx.send(request(2, AsyncAction::SearchDirectory("rust".to_string()))).unwrap();
+        action_tx.send(request(3, AsyncAction::LoadEpisodeState("https://example.com/1.mp3".to_string()))).unwrap();
+        action_tx.send(request(4, AsyncAction::ApplyConfig(Config::default()))).unwrap();
+        drop(action_tx);
+        handle_actions(data_provider, network, Config::default(), &mut action_rx, &result_tx, &UiWaker::default()).await;
+
+        let mut results = HashMap::new();
+        while results.len() < 4 {
+            let response = result_rx.recv().await.unwrap();
+            if matches!(response.event, AsyncEvent::Finished(_)) {
+                let (id, result) = finished(response);
+                results.insert(id, result);
+            }
+        }
+        let _ = std::fs::remove_file(&file);
+
+        // The database answers as usual, only the search needed the network
+        assert_eq!(results[&1], AsyncActionResult::PodcastsUpdate(Ok(Vec::new())));
+        assert!(matches!(&results[&2], AsyncActionResult::DirectorySearchResult(Err(e)) if e.contains("couldn't set up its network connection")));
+        assert_eq!(results[&3], AsyncActionResult::EpisodeStateUpdate(Ok(0.0)));
+        // Settings a client can be built with bring the network back
+        assert_eq!(results[&4], AsyncActionResult::ConfigApplied(Ok(())));
+    }
+
+    fn feed_credentials() -> FeedCredentials {
+        FeedCredentials {
+            username: "user".to_string(),
+            password: "secret".to_string(),
+            headers: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn feed_moves_take_credentials_only_to_their_origin() {
+        let credentials = feed_credentials();
+        let link = "https://feeds.example.com/private.xml";
+        let moved = |target| move_credentials(link, target, Some(&credentials));
+
+        assert_eq!(moved("https://feeds.example.com/moved.xml").unwrap(), Some(&credentials));
+        assert_eq!(moved("https://cdn.example.com/private.xml").unwrap(), None);
+        assert!(moved("http://feeds.example.com/private.xml").is_err());
+        assert_eq!(move_credentials(link, "http://feeds.example.com/private.xml", None).unwrap(), None);
+        // Upgrading to https keeps them
+        let upgraded = move_credentials("http://feeds.example.com/private.xml", "https://feeds.example.com/private.xml", Some(&credentials));
+        assert_eq!(upgraded.unwrap(), Some(&credentials));
+    }
+
+    #[test]
+    fn switching_scheme_changes_the_feed_url() {
+        assert!(!utils::feed_url_changed("https://example.com/feed/", "https://Example.com/feed"));
+        assert!(utils::feed_url_changed("https://example.com/feed", "http://example.com/feed"));
+        assert!(utils::feed_url_changed("http://example.com/feed", "https://example.com/feed"));
+        assert!(utils::feed_url_changed("https://example.com/feed", "https://example.org/feed"));
+    }
+
+    #[test]
+    fn reads_podcast_locked() {
+        let feed = |locked: &str| format!(
+            r#"<rss version="2.0" xmlns:podcast="https://podcastindex.org/namespace/1.0"><channel><title>T</title>{}</channel></rss>"#,
+            locked
+        );
+        let locked = |xml: String| utils::channel_locked(&utils::safe_rss_parse(&xml).unwrap());
+
+        assert!(locked(feed("<podcast:locked owner=\"a@example.com\">yes</podcast:locked>")));
+        assert!(!locked(feed("<podcast:locked>no</podcast:locked>")));
+        assert!(!locked(feed("")));
     }
 }
@@ -0,0 +1,203 @@
+use std::time::{Duration, Instant};
+use crate::entity::episode;
+use crate::player::PlayerBackend;
+use crate::protocol::AsyncAction;
+use crate::PlayerState;
+
+/// How often the position of a playing episode is saved
+pub const AUTO_SAVE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Where an episode was left, to be stored under its link
+#[derive(Debug, PartialEq, Clone)]
+pub struct SavedPosition {
+    pub podcast_id: i32,
+    pub ep_link: String,
+    pub position: f64,
+}
+
+impl SavedPosition {
+    /// Where the player is in `episode`, if there is one with a link to save it under
+    pub fn of(player: &dyn PlayerBackend, episode: Option<&episode::Model>) -> Option<Self> {
+        let episode = episode?;
+        Some(SavedPosition {
+            podcast_id: episode.podcast_id,
+            ep_link: episode.link.clone()?,
+            position: player.current_position(),
+        })
+    }
+
+    pub fn into_action(self) -> AsyncAction {
+        AsyncAction::SaveEpisodeState(self.position, self.podcast_id, self.ep_link)
+    }
+}
+
+/// Where an episode starts playing: where it was saved, unless a timestamp in its show notes
+/// asked for a position of its own
+pub fn start_position(saved: f64, episode: &episode::Model, start_at: Option<(String, f64)>) -> f64 {
+    start_at
+        .filter(|(start_link, _)| Some(start_link) == episode.link.as_ref())
+        .map_or(saved, |(_, seconds)| seconds)
+}
+
+/// Opens, pauses and resumes episodes on a player and tells when their position is due to be
+/// saved
+#[derive(Debug)]
+pub struct PlaybackControl {
+    last_saved: Instant,
+}
+
+impl PlaybackControl {
+    pub fn new(now: Instant) -> Self {
+        PlaybackControl { last_saved: now }
+    }
+
+    /// Opens `url` and starts playing it from `position`
+    pub fn open(&mut self, player: &mut dyn PlayerBackend, url: &str, position: f64, now: Instant) -> PlayerState {
+        player.open(url);
+        player.seek(position);
+        player.play();
+        self.last_saved = now;
+        PlayerState::Loading
+    }
+
+    /// Plays on from where the player stopped, or from the start once the episode has ended
+    pub fn resume(&mut self, player: &mut dyn PlayerBackend, state: &PlayerState) -> PlayerState {
+        if *state == PlayerState::Ended {
+            player.seek(0.0);
+        }
+        player.play();
+        PlayerState::Playing
+    }
+
+    /// Pauses and returns the position to save
+    pub fn pause(&mut self, player: &mut dyn PlayerBackend, episode: Option<&episode::Model>, now: Instant) -> Option<SavedPosition> {
+        player.pause();
+        self.last_saved = now;
+        SavedPosition::of(player, episode)
+    }
+
+    /// The position to save while playing, once every `AUTO_SAVE_INTERVAL`
+    pub fn auto_save(&mut self, player: &dyn PlayerBackend, state: &PlayerState, episode: Option<&episode::Model>, now: Instant) -> Option<SavedPosition> {
+        if *state != PlayerState::Playing || now.duration_since(self.last_saved) < AUTO_SAVE_INTERVAL {
+            return None;
+        }
+        let saved = SavedPosition::of(player, episode)?;
+        self.last_saved = now;
+        Some(saved)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::player::{Call, ScriptedBackend};
+
+    const URL: &str = "https://example.com/episode.mp3";
+
+    fn episode(link: Option<&str>) -> episode::Model {
+        episode::Model {
+            id: 1,
+            podcast_id: 3,
+            title: Some("Episode".to_string()),
+            link: link.map(str::to_string),
+            description: None,
+            guid: None,
+            pub_date: None,
+            duration: None,
+            first_seen: None,
+            triaged: true,
+            image_url: None,
+            enclosure_type: None,
+            enclosures: None,
+        }
+    }
+
+    fn saved(position: f64) -> SavedPosition {
+        SavedPosition {
+            podcast_id: 3,
+            ep_link: URL.to_string(),
+            position,
+        }
+    }
+
+    #[test]
+    fn opens_at_the_saved_position() {
+        let now = Instant::now();
+        let mut control = PlaybackControl::new(now);
+        let mut backend = ScriptedBackend::default();
+        let episode = episode(Some(URL));
+
+        let position = start_position(42.0, &episode, None);
+        let state = control.open(&mut backend, URL, position, now);
+
+        assert_eq!(state, PlayerState::Loading);
+        assert_eq!(backend.calls, [Call::Open(URL.to_string()), Call::Seek(42.0), Call::Play]);
+    }
+
+    #[test]
+    fn show_notes_timestamp_wins_over_the_saved_position() {
+        let episode = episode(Some(URL));
+
+        assert_eq!(start_position(42.0, &episode, Some((URL.to_string(), 90.0))), 90.0);
+        // A timestamp of another episode is left alone
+        assert_eq!(start_position(42.0, &episode, Some(("https://example.com/other.mp3".to_string(), 90.0))), 42.0);
+    }
+
+    #[test]
+    fn saves_while_playing_every_interval() {
+        let start = Instant::now();
+        let mut control = PlaybackControl::new(start);
+        let mut backend = ScriptedBackend::default();
+        let episode = episode(Some(URL));
+        control.open(&mut backend, URL, 0.0, start);
+
+        backend.position = 4.0;
+        let at = |seconds| start + Duration::from_secs(seconds);
+        assert_eq!(control.auto_save(&backend, &PlayerState::Playing, Some(&episode), at(4)), None);
+
+        backend.position = 5.5;
+        assert_eq!(control.auto_save(&backend, &PlayerState::Playing, Some(&episode), at(6)), Some(saved(5.5)));
+        // The interval starts over from the save
+        assert_eq!(control.auto_save(&backend, &PlayerState::Playing, Some(&episode), at(7)), None);
+        assert_eq!(control.auto_save(&backend, &PlayerState::Playing, Some(&episode), at(11)), Some(saved(5.5)));
+    }
+
+    #[test]
+    fn does_not_save_unless_playing_an_episode_with_a_link() {
+        let start = Instant::now();
+        let later = start + AUTO_SAVE_INTERVAL * 2;
+        let backend = ScriptedBackend::default();
+
+        let mut control = PlaybackControl::new(start);
+        assert_eq!(control.auto_save(&backend, &PlayerState::Paused, Some(&episode(Some(URL))), later), None);
+        assert_eq!(control.auto_save(&backend, &PlayerState::Buffering, Some(&episode(Some(URL))), later), None);
+        assert_eq!(control.auto_save(&backend, &PlayerState::Playing, Some(&episode(None)), later), None);
+        assert_eq!(control.auto_save(&backend, &PlayerState::Playing, None, later), None);
+    }
+
+    #[test]
+    fn pausing_saves_the_position() {
+        let now = Instant::now();
+        let mut control = PlaybackControl::new(now);
+        let mut backend = ScriptedBackend {
+            position: 12.0,
+            ..Default::default()
+        };
+
+        assert_eq!(control.pause(&mut backend, Some(&episode(Some(URL))), now), Some(saved(12.0)));
+        assert_eq!(backend.calls, [Call::Pause]);
+    }
+
+    #[test]
+    fn resumes_from_the_start_once_ended() {
+        let mut control = PlaybackControl::new(Instant::now());
+
+        let mut backend = ScriptedBackend::default();
+        assert_eq!(control.resume(&mut backend, &PlayerState::Paused), PlayerState::Playing);
+        assert_eq!(backend.calls, [Call::Play]);
+
+        let mut backend = ScriptedBackend::default();
+        assert_eq!(control.resume(&mut backend, &PlayerState::Ended), PlayerState::Playing);
+        assert_eq!(backend.calls, [Call::Seek(0.0), Call::Play]);
+    }
+}
@@ -0,0 +1,384 @@
+use std::time::{Duration, Instant};
+use log::{info, warn};
+use url2audio::Player;
+use crate::audio::SymphoniaBackend;
+use crate::error::PlayerError;
+
+/// How close to its end an episode counts as played through, as the player may stop short of
+/// the reported duration
+const ENDED_MARGIN_SECONDS: f64 = 1.0;
+/// Environment variable naming the backend to use: `symphonia` unless it says `url2audio` or
+/// `null`
+const BACKEND_VARIABLE: &str = "RUSTCAST_AUDIO_BACKEND";
+/// How long an opened stream may take to report its duration before it counts as failed
+const OPEN_TIMEOUT: Duration = Duration::from_secs(20);
+/// How long playback may sit at the same position before it counts as buffering
+const BUFFERING_AFTER: Duration = Duration::from_millis(1500);
+/// How long playback may sit at the same position before the stream counts as stalled
+const STALL_TIMEOUT: Duration = Duration::from_secs(20);
+/// How long a seek may take to land, and how close it has to land
+const SEEK_TIMEOUT: Duration = Duration::from_secs(10);
+const SEEK_TOLERANCE_SECONDS: f64 = 5.0;
+/// Enclosure types the player can't decode, by prefix
+const UNSUPPORTED_TYPES: [&str; 5] = ["audio/opus", "audio/webm", "audio/x-ms-wma", "audio/x-ms-wax", "application/vnd.rn-realmedia"];
+/// Video in an MP4 container, whose audio track the player can play on its own
+const AUDIO_PLAYABLE_VIDEO: [&str; 3] = ["video/mp4", "video/x-m4v", "video/quicktime"];
+
+/// What the app needs from an audio player. Positions are seconds on the episode's own
+/// timeline; a duration of 0 means it isn't known yet.
+pub trait PlayerBackend {
+    /// Starts loading the media at the URL, replacing whatever was open
+    fn open(&mut self, url: &str);
+    fn play(&mut self);
+    fn pause(&mut self);
+    fn seek(&mut self, seconds: f64);
+
+    fn seek_relative(&mut self, seconds: f64) {
+        let position = self.current_position();
+        self.seek((position + seconds).max(0.0));
+    }
+
+    fn current_position(&self) -> f64;
+    fn duration(&self) -> f64;
+
+    /// Whether `set_volume` has any effect
+    fn supports_volume(&self) -> bool;
+    /// Linear volume, above 1.0 for a boost
+    fn set_volume(&mut self, volume: f32);
+    /// The podcast's volume offset in dB and the loudness to normalize to in LUFS, if any.
+    /// Ignored by backends that can't change the volume.
+    fn set_loudness(&mut self, _podcast_gain_db: f64, _target: Option<f64>) {}
+
+    /// Whether `set_speed` has any effect
+    fn supports_speed(&self) -> bool;
+    /// Playback rate, 1.0 for normal speed
+    fn set_speed(&mut self, speed: f64);
+
+    /// Whether playback reached the end of the media
+    fn is_finished(&self) -> bool {
+        let duration = self.duration();
+        duration > 0.0 && self.current_position() >= duration - ENDED_MARGIN_SECONDS
+    }
+
+    /// Whether opened media hasn't started yet
+    fn is_loading(&self) -> bool {
+        false
+    }
+
+    /// Whether playback is waiting for more of the stream
+    fn is_buffering(&self) -> bool {
+        false
+    }
+
+    /// The latest failure, reported once. Backends that find failures by watching playback do
+    /// it here, so it's called on every frame.
+    fn take_error(&mut self) -> Option<PlayerError>;
+}
+
+/// Rejects enclosure types known not to play. Types that say nothing about the content, like
+/// `application/octet-stream`, are given the benefit of the doubt. MP4 video plays as audio.
+pub fn check_playable(mime_type: Option<&str>) -> Result<(), PlayerError> {
+    let Some(mime_type) = mime_type.map(|t| t.trim().to_ascii_lowercase()).filter(|t| !t.is_empty()) else {
+        return Ok(());
+    };
+    let unsupported_video = mime_type.starts_with("video/") && !AUDIO_PLAYABLE_VIDEO.contains(&mime_type.as_str());
+    if unsupported_video || UNSUPPORTED_TYPES.iter().any(|unsupported| mime_type.starts_with(unsupported)) {
+        return Err(PlayerError::UnsupportedFormat(mime_type));
+    }
+    Ok(())
+}
+
+/// The backend chosen by `RUSTCAST_AUDIO_BACKEND`
+pub fn backend_from_env() -> Box<dyn PlayerBackend> {
+    match std::env::var(BACKEND_VARIABLE).as_deref() {
+        Ok("null") => {
+            info!("Playing without audio output");
+            Box::new(NullBackend::default())
+        }
+        Ok("url2audio") => Box::new(Url2AudioBackend::new()),
+        _ => Box::new(SymphoniaBackend::new()),
+    }
+}
+
+/// Plays through url2audio, which streams from URLs to the default output device.
+///
+/// url2audio doesn't report failures: a stream that can't be opened or decoded, or that
+/// stops arriving, just never moves on. They're told apart by watching the position.
+pub struct Url2AudioBackend {
+    player: Player,
+    /// When the open media was opened, until its duration is known
+    opened_at: Option<Instant>,
+    playing: bool,
+    /// Position last seen while playing and when it was first seen
+    progress: Option<(f64, Instant)>,
+    /// Target of the last seek and when it was asked for, until the position gets there
+    seeking: Option<(f64, Instant)>,
+    /// The failure of the open media has been reported
+    failed: bool,
+}
+
+impl Url2AudioBackend {
+    pub fn new() -> Self {
+        Url2AudioBackend {
+            player: Player::new(),
+            opened_at: None,
+            playing: false,
+            progress: None,
+            seeking: None,
+            failed: false,
+        }
+    }
+
+    /// Restarts the stall timer after anything that legitimately holds the position
+    fn reset_progress(&mut self) {
+        self.progress = None;
+    }
+}
+
+impl Default for Url2AudioBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PlayerBackend for Url2AudioBackend {
+    fn open(&mut self, url: &str) {
+        self.player.open(url);
+        self.opened_at = Some(Instant::now());
+        self.seeking = None;
+        self.failed = false;
+        self.reset_progress();
+    }
+
+    fn play(&mut self) {
+        self.player.play();
+        self.playing = true;
+        self.reset_progress();
+    }
+
+    fn pause(&mut self) {
+        self.player.pause();
+        self.playing = false;
+    }
+
+    fn seek(&mut self, seconds: f64) {
+        self.player.seek(seconds);
+        self.seeking = Some((seconds, Instant::now()));
+        self.reset_progress();
+    }
+
+    fn seek_relative(&mut self, seconds: f64) {
+        let target = (self.current_position() + seconds).max(0.0);
+        self.player.seek_relative(seconds);
+        self.seeking = Some((target, Instant::now()));
+        self.reset_progress();
+    }
+
+    fn current_position(&self) -> f64 {
+        self.player.current_position()
+    }
+
+    fn duration(&self) -> f64 {
+        self.player.duration()
+    }
+
+    fn supports_volume(&self) -> bool {
+        false
+    }
+
+    fn set_volume(&mut self, _volume: f32) {}
+
+    fn supports_speed(&self) -> bool {
+        false
+    }
+
+    fn set_speed(&mut self, _speed: f64) {}
+
+    fn is_loading(&self) -> bool {
+        self.opened_at.is_some() && !self.failed && self.duration() <= 0.0
+    }
+
+    /// Kept up to date by `take_error`
+    fn is_buffering(&self) -> bool {
+        self.playing && !self.failed && self.progress.is_some_and(|(_, since)| since.elapsed() >= BUFFERING_AFTER)
+    }
+
+    fn take_error(&mut self) -> Option<PlayerError> {
+        if self.failed || self.opened_at.is_none() {
+            return None;
+        }
+
+        if self.duration() <= 0.0 {
+            if self.opened_at.is_some_and(|opened| opened.elapsed() >= OPEN_TIMEOUT) {
+                warn!("The stream reported no duration {}s after opening", OPEN_TIMEOUT.as_secs());
+                self.failed = true;
+                return Some(PlayerError::OpenFailed("the stream could not be opened or decoded".to_string()));
+            }
+            return None;
+        }
+
+        if !self.playing || self.is_finished() {
+            // Paused, the position may only move once playback resumes
+            self.seeking = self.seeking.map(|(target, _)| (target, Instant::now()));
+            self.reset_progress();
+            return None;
+        }
+
+        let position = self.current_position();
+        if let Some((target, since)) = self.seeking {
+            if (position - target).abs() <= SEEK_TOLERANCE_SECONDS {
+                self.seeking = None;
+            } else if since.elapsed() >= SEEK_TIMEOUT {
+                warn!("Seeking to {:.1}s ended up at {:.1}s", target, position);
+                self.seeking = None;
+                return Some(PlayerError::SeekFailed(format!("the stream stayed at {:.1}s instead of {:.1}s", position, target)));
+            }
+        }
+        match self.progress {
+            Some((last, since)) if last == position => {
+                if since.elapsed() >= STALL_TIMEOUT {
+                    warn!("Playback stalled at {:.1}s", position);
+                    self.failed = true;
+                    return Some(PlayerError::PlaybackFailed("the stream stopped delivering audio".to_string()));
+                }
+            }
+            _ => self.progress = Some((position, Instant::now())),
+        }
+        None
+    }
+}
+
+/// Plays to nowhere: the position runs with the clock, times the speed, while playing. For
+/// machines without an audio device and for trying the app out without sound.
+pub struct NullBackend {
+    /// Position when playback last started or stopped
+    position: f64,
+    playing_since: Option<Instant>,
+    speed: f64,
+}
+
+impl Default for NullBackend {
+    fn default() -> Self {
+        NullBackend {
+            position: 0.0,
+            playing_since: None,
+            speed: 1.0,
+        }
+    }
+}
+
+impl PlayerBackend for NullBackend {
+    fn open(&mut self, _url: &str) {
+        self.position = 0.0;
+        self.playing_since = None;
+    }
+
+    fn play(&mut self) {
+        self.playing_since.get_or_insert_with(Instant::now);
+    }
+
+    fn pause(&mut self) {
+        self.position = self.current_position();
+        self.playing_since = None;
+    }
+
+    fn seek(&mut self, seconds: f64) {
+        self.position = seconds.max(0.0);
+        if self.playing_since.is_some() {
+            self.playing_since = Some(Instant::now());
+        }
+    }
+
+    fn current_position(&self) -> f64 {
+        self.position + self.playing_since.map_or(0.0, |since| since.elapsed().as_secs_f64() * self.speed)
+    }
+
+    fn duration(&self) -> f64 {
+        0.0
+    }
+
+    fn supports_volume(&self) -> bool {
+        false
+    }
+
+    fn set_volume(&mut self, _volume: f32) {}
+
+    fn supports_speed(&self) -> bool {
+        true
+    }
+
+    fn set_speed(&mut self, speed: f64) {
+        // Time played so far counts at the old speed
+        self.position = self.current_position();
+        if self.playing_since.is_some() {
+            self.playing_since = Some(Instant::now());
+        }
+        self.speed = speed;
+    }
+
+    fn take_error(&mut self) -> Option<PlayerError> {
+        None
+    }
+}
+
+/// A call made on a `ScriptedBackend`
+#[cfg(test)]
+#[derive(Debug, PartialEq, Clone)]
+pub enum Call {
+    Open(String),
+    Play,
+    Pause,
+    Seek(f64),
+}
+
+/// Records what it's asked to do and reports the position and duration a test sets
+#[cfg(test)]
+#[derive(Debug, Default)]
+pub struct ScriptedBackend {
+    pub calls: Vec<Call>,
+    pub position: f64,
+    pub duration: f64,
+}
+
+#[cfg(test)]
+impl PlayerBackend for ScriptedBackend {
+    fn open(&mut self, url: &str) {
+        self.calls.push(Call::Open(url.to_string()));
+    }
+
+    fn play(&mut self) {
+        self.calls.push(Call::Play);
+    }
+
+    fn pause(&mut self) {
+        self.calls.push(Call::Pause);
+    }
+
+    fn seek(&mut self, seconds: f64) {
+        self.calls.push(Call::Seek(seconds));
+    }
+
+    fn current_position(&self) -> f64 {
+        self.position
+    }
+
+    fn duration(&self) -> f64 {
+        self.duration
+    }
+
+    fn supports_volume(&self) -> bool {
+        false
+    }
+
+    fn set_volume(&mut self, _volume: f32) {}
+
+    fn supports_speed(&self) -> bool {
+        false
+    }
+
+    fn set_speed(&mut self, _speed: f64) {}
+
+    fn take_error(&mut self) -> Option<PlayerError> {
+        None
+    }
+}
@@ -0,0 +1,319 @@
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use crate::entity::{episode, episode_state, smart_playlist};
+use crate::error::{DatabaseError, RustcastError, RustcastResult};
+use crate::utils;
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlayState {
+    #[default]
+    Any,
+    Unplayed,
+    InProgress,
+    Finished,
+}
+
+impl PlayState {
+    pub const ALL: [PlayState; 4] = [PlayState::Any, PlayState::Unplayed, PlayState::InProgress, PlayState::Finished];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            PlayState::Any => "Any",
+            PlayState::Unplayed => "Unplayed",
+            PlayState::InProgress => "In progress",
+            PlayState::Finished => "Finished",
+        }
+    }
+
+    fn matches(&self, state: Option<&episode_state::Model>) -> bool {
+        let (time, finished) = state.map_or((0.0, false), |s| (s.time, s.finished));
+        match self {
+            PlayState::Any => true,
+            PlayState::Unplayed => !finished && time <= 0.0,
+            PlayState::InProgress => !finished && time > 0.0,
+            PlayState::Finished => finished,
+        }
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlaylistSort {
+    #[default]
+    Newest,
+    Oldest,
+    Shortest,
+    Longest,
+    LastPlayed,
+}
+
+impl PlaylistSort {
+    pub const ALL: [PlaylistSort; 5] = [
+        PlaylistSort::Newest,
+        PlaylistSort::Oldest,
+        PlaylistSort::Shortest,
+        PlaylistSort::Longest,
+        PlaylistSort::LastPlayed,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            PlaylistSort::Newest => "Newest first",
+            PlaylistSort::Oldest => "Oldest first",
+            PlaylistSort::Shortest => "Shortest first",
+            PlaylistSort::Longest => "Longest first",
+            PlaylistSort::LastPlayed => "Last played first",
+        }
+    }
+}
+
+/// Which episodes a smart playlist holds and in what order. Stored as JSON, so fields added
+/// later need a default.
+#[derive(Debug, Default, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlaylistQuery {
+    pub state: PlayState,
+    pub min_minutes: Option<u32>,
+    pub max_minutes: Option<u32>,
+    /// Podcasts to take episodes from, together with those of `tag_ids`; both empty means all
+    pub podcast_ids: Vec<i32>,
+    pub tag_ids: Vec<i32>,
+    pub sort: PlaylistSort,
+    pub limit: Option<usize>,
+}
+
+/// Everything a query looks at besides the episodes themselves
+pub struct EpisodeFacts<'a> {
+    /// Playback state by episode link
+    pub states: &'a HashMap<String, episode_state::Model>,
+    /// Tags by podcast
+    pub podcast_tags: &'a HashMap<i32, Vec<i32>>,
+}
+
+impl PlaylistQuery {
+    fn matches(&self, episode: &episode::Model, facts: &EpisodeFacts) -> bool {
+        let from_source = (self.podcast_ids.is_empty() && self.tag_ids.is_empty())
+            || self.podcast_ids.contains(&episode.podcast_id)
+            || facts.podcast_tags.get(&episode.podcast_id)
+                .is_some_and(|tags| tags.iter().any(|tag| self.tag_ids.contains(tag)));
+        if !from_source {
+            return false;
+        }
+
+        let state = episode.link.as_ref().and_then(|link| facts.states.get(link));
+        if !self.state.matches(state) {
+            return false;
+        }
+
+        // Episodes of unknown length can't be said to fit a duration range
+        if self.min_minutes.is_some() || self.max_minutes.is_some() {
+            let Some(seconds) = episode.duration else {
+                return false;
+            };
+            if self.min_minutes.is_some_and(|min| seconds < min as i32 * 60) {
+                return false;
+            }
+            if self.max_minutes.is_some_and(|max| seconds > max as i32 * 60) {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// The episodes that match, in playlist order
+    pub fn evaluate(&self, episodes: Vec<episode::Model>, facts: &EpisodeFacts) -> Vec<episode::Model> {
+        let mut matching: Vec<episode::Model> = episodes.into_iter()
+            .filter(|episode| self.matches(episode, facts))
+            .collect();
+
+        let published = |e: &episode::Model| e.pub_date.as_deref().and_then(utils::pub_date_timestamp);
+        let last_played = |e: &episode::Model| e.link.as_ref()
+            .and_then(|link| facts.states.get(link))
+            .and_then(|state| state.last_played);
+        // Episodes missing what is sorted on go last in every order
+        match self.sort {
+            PlaylistSort::Newest => matching.sort_by_key(|e| std::cmp::Reverse(published(e))),
+            PlaylistSort::Oldest => matching.sort_by_key(|e| (published(e).is_none(), published(e))),
+            PlaylistSort::Shortest => matching.sort_by_key(|e| (e.duration.is_none(), e.duration)),
+            PlaylistSort::Longest => matching.sort_by_key(|e| std::cmp::Reverse(e.duration)),
+            PlaylistSort::LastPlayed => matching.sort_by_key(|e| std::cmp::Reverse(last_played(e))),
+        }
+
+        if let Some(limit) = self.limit {
+            matching.truncate(limit);
+        }
+        matching
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Playlist {
+    pub id: i32,
+    pub name: String,
+    pub query: PlaylistQuery,
+}
+
+impl TryFrom<smart_playlist::Model> for Playlist {
+    type Error = RustcastError;
+
+    fn try_from(model: smart_playlist::Model) -> RustcastResult<Self> {
+        let query = serde_json::from_str(&model.query).map_err(|e| RustcastError::Database(
+            DatabaseError::QueryFailed(format!("playlist {} has an unreadable query: {}", model.id, e))
+        ))?;
+
+        Ok(Playlist {
+            id: model.id,
+            name: model.name,
+            query,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn episode(id: i32, podcast_id: i32, link: Option<&str>, pub_date: Option<&str>, duration: Option<i32>) -> episode::Model {
+        episode::Model {
+            id,
+            podcast_id,
+            title: Some(format!("Episode {}", id)),
+            link: link.map(str::to_string),
+            description: None,
+            guid: None,
+            pub_date: pub_date.map(str::to_string),
+            duration,
+            first_seen: None,
+            triaged: true,
+            image_url: None,
+            enclosure_type: None,
+            enclosures: None,
+        }
+    }
+
+    fn state(link: &str, time: f64, finished: bool, last_played: Option<i64>) -> (String, episode_state::Model) {
+        (link.to_string(), episode_state::Model {
+            id: 0,
+            time,
+            finished,
+            podcast_id: 0,
+            ep_link: link.to_string(),
+            last_played,
+        })
+    }
+
+    /// Five episodes of three podcasts; 3 has no duration, 4 no publication date and 5 neither
+    /// a link nor a date that can be read
+    fn episodes() -> Vec<episode::Model> {
+        vec![
+            episode(1, 1, Some("a"), Some("Mon, 01 Jan 2024 10:00:00 +0000"), Some(600)),
+            episode(2, 1, Some("b"), Some("Thu, 01 Feb 2024 10:00:00 +0000"), Some(3600)),
+            episode(3, 2, Some("c"), Some("Fri, 01 Mar 2024 10:00:00 +0000"), None),
+            episode(4, 3, Some("d"), None, Some(1800)),
+            episode(5, 2, None, Some("sometime"), Some(1200)),
+        ]
+    }
+
+    fn evaluate(query: PlaylistQuery) -> Vec<i32> {
+        let states = HashMap::from([
+            state("b", 120.0, false, Some(200)),
+            state("c", 0.0, true, Some(300)),
+            state("d", 0.0, false, None),
+        ]);
+        let podcast_tags = HashMap::from([(2, vec![7]), (3, vec![7, 8])]);
+        let facts = EpisodeFacts {
+            states: &states,
+            podcast_tags: &podcast_tags,
+        };
+
+        query.evaluate(episodes(), &facts).into_iter().map(|e| e.id).collect()
+    }
+
+    fn sorted(sort: PlaylistSort) -> Vec<i32> {
+        evaluate(PlaylistQuery { sort, ..Default::default() })
+    }
+
+    #[test]
+    fn filters_by_state() {
+        let by_state = |state| {
+            let mut ids = evaluate(PlaylistQuery { state, ..Default::default() });
+            ids.sort();
+            ids
+        };
+
+        assert_eq!(by_state(PlayState::Any), [1, 2, 3, 4, 5]);
+        assert_eq!(by_state(PlayState::Unplayed), [1, 4, 5]);
+        assert_eq!(by_state(PlayState::InProgress), [2]);
+        assert_eq!(by_state(PlayState::Finished), [3]);
+    }
+
+    #[test]
+    fn filters_by_duration() {
+        let by_duration = |min_minutes, max_minutes| {
+            let mut ids = evaluate(PlaylistQuery { min_minutes, max_minutes, ..Default::default() });
+            ids.sort();
+            ids
+        };
+
+        // Episode 3 has no duration, so it only shows up without a range
+        assert_eq!(by_duration(None, None), [1, 2, 3, 4, 5]);
+        assert_eq!(by_duration(Some(20), None), [2, 4, 5]);
+        assert_eq!(by_duration(None, Some(20)), [1, 5]);
+        assert_eq!(by_duration(Some(20), Some(30)), [4, 5]);
+        assert_eq!(by_duration(Some(10), Some(10)), [1]);
+    }
+
+    #[test]
+    fn filters_by_podcast_and_tag() {
+        let from = |podcast_ids: Vec<i32>, tag_ids: Vec<i32>| {
+            let mut ids = evaluate(PlaylistQuery { podcast_ids, tag_ids, ..Default::default() });
+            ids.sort();
+            ids
+        };
+
+        assert_eq!(from(vec![1], vec![]), [1, 2]);
+        assert_eq!(from(vec![], vec![7]), [3, 4, 5]);
+        assert_eq!(from(vec![], vec![8]), [4]);
+        assert_eq!(from(vec![1], vec![8]), [1, 2, 4]);
+        assert_eq!(from(vec![9], vec![9]), Vec::<i32>::new());
+    }
+
+    #[test]
+    fn sorts_with_missing_values_last() {
+        assert_eq!(sorted(PlaylistSort::Newest), [3, 2, 1, 4, 5]);
+        assert_eq!(sorted(PlaylistSort::Oldest), [1, 2, 3, 4, 5]);
+        assert_eq!(sorted(PlaylistSort::Shortest), [1, 5, 4, 2, 3]);
+        assert_eq!(sorted(PlaylistSort::Longest), [2, 4, 5, 1, 3]);
+        assert_eq!(sorted(PlaylistSort::LastPlayed), [3, 2, 1, 4, 5]);
+    }
+
+    #[test]
+    fn limits_after_sorting() {
+        let query = PlaylistQuery {
+            state: PlayState::Unplayed,
+            sort: PlaylistSort::Shortest,
+            limit: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(evaluate(query), [1, 5]);
+    }
+
+    #[test]
+    fn reads_stored_queries() {
+        let stored = |query: &str| Playlist::try_from(smart_playlist::Model {
+            id: 1,
+            name: "Short ones".to_string(),
+            query: query.to_string(),
+        });
+
+        // Fields added later fall back to their defaults
+        let playlist = stored(r#"{"state": "unplayed", "max_minutes": 20}"#).unwrap();
+        assert_eq!(playlist.query, PlaylistQuery {
+            state: PlayState::Unplayed,
+            max_minutes: Some(20),
+            ..Default::default()
+        });
+        assert!(stored("not json").is_err());
+    }
+}
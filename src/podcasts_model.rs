@@ -1,4 +1,12 @@
-use crate::entity::{episode, podcast};
+use std::collections::HashMap;
+use crate::credentials::FeedCredentials;
+use crate::directory::DirectoryResult;
+use crate::enclosure::EnclosurePreference;
+use crate::feed_discovery::FeedCandidate;
+use crate::entity::{bookmark, episode, podcast};
+use crate::playlist::{Playlist, PlaylistQuery};
+use crate::protocol::{PodcastGroups, RequestId};
+use crate::show_notes::{self, Block};
 
 #[derive(Default, PartialEq, Debug, Clone)]
 pub struct Podcast {
@@ -10,29 +18,269 @@ pub struct Podcast {
 
 pub struct PodcastsModel {
     pub podcasts: Option<Vec<podcast::Model>>,
+    /// The podcasts were asked for; after a failed load they wait for a retry
+    pub podcasts_requested: bool,
     pub current_podcast: Podcast,
     pub podcast_dialog: PodcastDialog,
+    pub discovery_dialog: DiscoveryDialog,
+    pub feed_choice_dialog: Option<FeedChoiceDialog>,
+    pub credentials_dialog: CredentialsDialog,
+    pub edit_dialog: Option<EditPodcastDialog>,
+    pub unsubscribe_dialog: Option<UnsubscribeDialog>,
+    pub tag_dialog: Option<TagDialog>,
+    pub groups: PodcastGroups,
+    /// Tag whose episodes are shown instead of those of `current_podcast`
+    pub current_tag: Option<i32>,
+    pub playlists: Vec<Playlist>,
+    pub playlist_dialog: Option<PlaylistDialog>,
+    /// Playlist whose episodes are shown instead of those of `current_podcast`
+    pub current_playlist: Option<i32>,
+    /// Playlist to play from the start as soon as its episodes arrive
+    pub play_when_loaded: Option<i32>,
+    /// Episodes to play once the current one ends
+    pub queue: std::collections::VecDeque<episode::Model>,
+    /// New episodes of every podcast that haven't been triaged yet
+    pub inbox: Vec<episode::Model>,
+    /// Whether the inbox is shown instead of the episode list
+    pub inbox_open: bool,
+    /// Links of the inbox episodes picked for a bulk action
+    pub inbox_selected: std::collections::HashSet<String>,
+    /// Links of the episodes being downloaded
+    pub downloading: std::collections::HashSet<String>,
     pub episodes: Option<Vec<episode::Model>>,
     pub current_episode: Option<episode::Model>,
     pub episode_states: std::collections::HashMap<String, f64>,
+    /// Episode shown in the detail pane
+    pub episode_details: Option<EpisodeDetails>,
+    /// Link of the episode being opened and where to start it instead of its saved position
+    pub start_at: Option<(String, f64)>,
+    /// Thumbnails by artwork URL
+    pub artwork: std::collections::HashMap<String, ArtworkState>,
+    pub bookmarks: Vec<bookmark::Model>,
+    pub bookmark_dialog: Option<BookmarkDialog>,
+    /// The list of every bookmark, when it's open
+    pub bookmarks_window: Option<BookmarksWindow>,
 }
 
-#[derive(Default, PartialEq, Debug, Clone)]
+// No `Debug`: the dialog holds the password of private feeds
+#[derive(Default, PartialEq, Clone)]
 pub struct PodcastDialog {
     pub title: String,
     pub link: String,
-    pub description: String
+    pub description: String,
+    pub private_feed: bool,
+    pub username: String,
+    pub password: String,
+    /// `Name: value` lines, one header per line
+    pub headers: String,
+}
+
+impl PodcastDialog {
+    pub fn credentials(&self) -> Option<FeedCredentials> {
+        if !self.private_feed {
+            return None;
+        }
+
+        Some(FeedCredentials {
+            username: self.username.trim().to_string(),
+            password: self.password.clone(),
+            headers: FeedCredentials::parse_headers(&self.headers),
+        }).filter(|c| !c.is_empty())
+    }
+}
+
+#[derive(Default, PartialEq, Debug, Clone)]
+pub struct DiscoveryDialog {
+    pub query: String,
+    pub results: Vec<DirectoryResult>,
+    /// Feeds being added, by the request adding them
+    pub subscribing: HashMap<RequestId, String>,
+    /// Feeds added from this dialog that may not be in the podcast list yet
+    pub subscribed: Vec<String>,
+}
+
+impl DiscoveryDialog {
+    /// Settles the row of a subscribe request once the worker answered it
+    pub fn subscribe_finished(&mut self, request_id: RequestId, added: bool) {
+        if let Some(feed_url) = self.subscribing.remove(&request_id) {
+            if added {
+                self.subscribed.push(feed_url);
+            }
+        }
+    }
+}
+
+/// Feeds found on a web page the user pasted, waiting for them to pick one
+#[derive(PartialEq, Debug, Clone)]
+pub struct FeedChoiceDialog {
+    pub title: String,
+    pub description: String,
+    pub credentials: Option<FeedCredentials>,
+    pub candidates: Vec<FeedCandidate>,
+    pub selected: usize,
+}
+
+/// Unlocking the credential store and editing the credentials of the current podcast
+#[derive(Default, PartialEq, Clone)]
+pub struct CredentialsDialog {
+    pub podcast_id: Option<i32>,
+    pub passphrase: String,
+    pub unlocked: bool,
+    pub busy: bool,
+    pub username: String,
+    pub password: String,
+    pub headers: String,
+}
+
+/// Editing a podcast that is already subscribed; only title, feed URL and description apply
+#[derive(PartialEq, Clone)]
+pub struct EditPodcastDialog {
+    pub podcast_id: i32,
+    /// Title from the feed, used again when the title is cleared
+    pub feed_title: String,
+    pub dialog: PodcastDialog,
+    /// Volume offset in dB
+    pub gain_db: i32,
+    pub enclosure_preference: EnclosurePreference,
+    pub busy: bool,
+}
+
+impl EditPodcastDialog {
+    pub fn new(podcast: podcast::Model) -> Self {
+        EditPodcastDialog {
+            podcast_id: podcast.id,
+            feed_title: podcast.title.clone().unwrap_or_default(),
+            gain_db: podcast.gain_db,
+            enclosure_preference: EnclosurePreference::of(&podcast),
+            dialog: podcast.into(),
+            busy: false,
+        }
+    }
+
+    /// Title to store as the override, `None` if it's the feed's own
+    pub fn title_override(&self) -> Option<String> {
+        let title = self.dialog.title.trim();
+        (!title.is_empty() && title != self.feed_title).then(|| title.to_string())
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct UnsubscribeDialog {
+    pub podcast_id: i32,
+    pub title: String,
+    pub keep_history: bool,
+    pub busy: bool,
+}
+
+/// Naming a new tag, or renaming the one in `tag_id`
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct TagDialog {
+    pub tag_id: Option<i32>,
+    pub name: String,
+    /// Podcast to add to a new tag once it's created
+    pub podcast_id: Option<i32>,
+    pub busy: bool,
+}
+
+/// Creating a smart playlist, or editing the one in `playlist_id`
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct PlaylistDialog {
+    pub playlist_id: Option<i32>,
+    pub name: String,
+    pub query: PlaylistQuery,
+    pub busy: bool,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum ArtworkState {
+    Loading,
+    /// PNG thumbnail
+    Ready(std::sync::Arc<[u8]>),
+    /// Couldn't be fetched; not tried again until the app restarts
+    Unavailable,
+}
+
+/// An episode with its show notes parsed once, rather than on every frame
+#[derive(Debug, PartialEq, Clone)]
+pub struct EpisodeDetails {
+    pub episode: episode::Model,
+    pub notes: Vec<Block>,
+}
+
+impl EpisodeDetails {
+    pub fn new(episode: episode::Model) -> Self {
+        let notes = show_notes::parse(episode.description.as_deref().unwrap_or_default());
+        EpisodeDetails { episode, notes }
+    }
+}
+
+/// Writing a new bookmark, or changing the one in `bookmark_id`
+#[derive(Debug, PartialEq, Clone)]
+pub struct BookmarkDialog {
+    pub bookmark_id: Option<i32>,
+    pub podcast_id: i32,
+    pub ep_link: String,
+    pub episode_title: Option<String>,
+    pub position: f64,
+    pub end_position: Option<f64>,
+    pub note: String,
+    pub busy: bool,
+}
+
+impl From<&bookmark::Model> for BookmarkDialog {
+    fn from(bookmark: &bookmark::Model) -> Self {
+        BookmarkDialog {
+            bookmark_id: Some(bookmark.id),
+            podcast_id: bookmark.podcast_id,
+            ep_link: bookmark.ep_link.clone(),
+            episode_title: bookmark.episode_title.clone(),
+            position: bookmark.position,
+            end_position: bookmark.end_position,
+            note: bookmark.note.clone(),
+            busy: false,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct BookmarksWindow {
+    pub export_path: String,
+    pub exporting: bool,
 }
 
 impl PodcastsModel {
     pub fn new() -> Self {
         PodcastsModel {
             podcasts: Default::default(),
+            podcasts_requested: false,
             current_podcast: Default::default(),
             podcast_dialog: Default::default(),
+            discovery_dialog: Default::default(),
+            feed_choice_dialog: None,
+            credentials_dialog: Default::default(),
+            edit_dialog: None,
+            unsubscribe_dialog: None,
+            tag_dialog: None,
+            groups: Default::default(),
+            current_tag: None,
+            playlists: Vec::new(),
+            playlist_dialog: None,
+            current_playlist: None,
+            play_when_loaded: None,
+            queue: Default::default(),
+            inbox: Vec::new(),
+            inbox_open: false,
+            inbox_selected: Default::default(),
+            downloading: Default::default(),
             episodes: Default::default(),
             current_episode: Default::default(),
             episode_states: std::collections::HashMap::new(),
+            episode_details: None,
+            start_at: None,
+            artwork: Default::default(),
+            bookmarks: Vec::new(),
+            bookmark_dialog: None,
+            bookmarks_window: None,
         }
     }
 }
@@ -40,9 +288,10 @@ impl PodcastsModel {
 impl From<podcast::Model> for PodcastDialog {
     fn from(p: podcast::Model) -> Self {
         PodcastDialog {
-            link: p.link.unwrap(),
-            title: p.title.unwrap(),
-            description: p.description.unwrap()
+            title: p.display_title().unwrap_or_default().to_string(),
+            link: p.link.unwrap_or_default(),
+            description: p.description.unwrap_or_default(),
+            ..Default::default()
         }
     }
 }
@@ -0,0 +1,539 @@
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, OnceLock};
+use eframe::egui;
+use log::error;
+use tokio::sync::mpsc::UnboundedSender;
+use tokio_util::sync::CancellationToken;
+use crate::bookmarks::NewBookmark;
+use crate::clip::ClipRequest;
+use crate::config::Config;
+use crate::credentials::{FeedCredentials, Passphrase};
+use crate::directory::DirectoryResult;
+use crate::enclosure::EnclosurePreference;
+use crate::entity::{bookmark, episode, podcast, tag};
+use crate::feed_discovery::FeedCandidate;
+use crate::history::{ListeningStats, SessionRecord};
+use crate::importer::{ImportReport, ImportSource};
+use crate::playlist::{Playlist, PlaylistQuery};
+use crate::silence::Silence;
+
+/// Identifies one request from the UI so its progress and result can be told apart from others
+pub type RequestId = u64;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum AsyncAction {
+    AddPodcast(String, String, String, Option<FeedCredentials>),
+    GetPodcasts,
+    GetEpisodes(String, i32),
+    SaveEpisodeState(f64, i32, String),
+    LoadEpisodeState(String),
+    GetAllEpisodeStates(i32),
+    ImportHistory(ImportSource, String),
+    SearchDirectory(String),
+    UnlockCredentials(Passphrase),
+    GetCredentials(i32),
+    SaveCredentials(i32, Option<FeedCredentials>),
+    RefreshFeeds,
+    UpdatePodcast(i32, PodcastEdit),
+    ReorderPodcasts(Vec<i32>),
+    Unsubscribe(i32, bool),
+    ApplyConfig(Config),
+    GetGroups,
+    /// Creates a tag, adding the given podcast to it straight away
+    CreateTag(String, Option<i32>),
+    RenameTag(i32, String),
+    DeleteTag(i32),
+    TagPodcast(i32, i32),
+    UntagPodcast(i32, i32),
+    GetTagEpisodes(i32),
+    GetPlaylists,
+    /// Adds a playlist, or changes the one with the given id
+    SavePlaylist(Option<i32>, String, PlaylistQuery),
+    DeletePlaylist(i32),
+    GetPlaylistEpisodes(i32),
+    GetInbox,
+    /// Takes the episodes with these links out of the inbox
+    ArchiveEpisodes(Vec<String>),
+    /// Marks episodes, given as podcast and link, as played
+    MarkPlayed(Vec<(i32, String)>),
+    DownloadEpisode(i32, String),
+    LogSession(SessionRecord),
+    GetListeningStats,
+    /// Writes the listening history as CSV to the given file
+    ExportHistory(PathBuf),
+    /// Fetches the thumbnail of the artwork at the URL
+    LoadArtwork(String),
+    GetBookmarks,
+    AddBookmark(NewBookmark),
+    /// Changes the end and note of a bookmark
+    UpdateBookmark(i32, Option<f64>, String),
+    DeleteBookmark(i32),
+    /// Writes every bookmark as Markdown to the given file
+    ExportBookmarks(PathBuf),
+    /// Looks up the stored episode with the link
+    FindEpisode(String),
+    /// Saves part of an episode as an audio file, downloading the episode first if needed
+    ExportClip(ClipRequest),
+    /// Finds the pauses in an episode, given as podcast and link, at least this many seconds
+    /// long. The whole episode is fetched, unless it's downloaded, and decoded first.
+    DetectSilences(i32, String, f64),
+    /// Checks that an episode, given as podcast and link, can be streamed from the URL of the
+    /// version chosen to play
+    ProbeEnclosure(i32, String, String),
+    RetryDatabase,
+    OpenDatabase(PathBuf),
+    RestoreDatabaseBackup,
+    Cancel(RequestId),
+}
+
+/// Podcast details the user can change; a `None` title goes back to the feed's own
+#[derive(Debug, PartialEq, Clone)]
+pub struct PodcastEdit {
+    pub title_override: Option<String>,
+    pub link: String,
+    pub description: String,
+    pub gain_db: i32,
+    pub enclosure_preference: EnclosurePreference,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct AsyncRequest {
+    pub id: RequestId,
+    pub action: AsyncAction,
+}
+
+/// Episodes of a podcast after a refresh
+#[derive(Debug, PartialEq, Clone)]
+pub struct EpisodesRefresh {
+    pub podcast_id: i32,
+    pub episodes: Vec<episode::Model>,
+    /// Why the feed couldn't be fetched, in which case `episodes` are the ones already stored
+    pub refresh_error: Option<String>,
+    /// Old and new feed URL if the feed moved during this refresh
+    pub moved: Option<(String, String)>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct TagGroup {
+    pub tag: tag::Model,
+    pub podcast_ids: Vec<i32>,
+}
+
+/// What the side panel groups podcasts by, besides their own list
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct PodcastGroups {
+    pub tags: Vec<TagGroup>,
+    pub new_episodes: Vec<i32>,
+    pub in_progress: Vec<i32>,
+    pub downloaded: Vec<i32>,
+}
+
+/// Episodes of every podcast with a tag, newest first
+#[derive(Debug, PartialEq, Clone)]
+pub struct TagEpisodes {
+    pub tag_id: i32,
+    pub episodes: Vec<episode::Model>,
+    pub episode_states: HashMap<String, f64>,
+}
+
+/// Episodes a smart playlist holds right now, in playlist order
+#[derive(Debug, PartialEq, Clone)]
+pub struct PlaylistEpisodes {
+    pub playlist_id: i32,
+    pub episodes: Vec<episode::Model>,
+    pub episode_states: HashMap<String, f64>,
+}
+
+/// Why the database couldn't be opened, sent instead of any other result until it can
+#[derive(Debug, PartialEq, Clone)]
+pub struct DatabaseFailure {
+    pub error: String,
+    pub file: PathBuf,
+    pub latest_backup: Option<PathBuf>,
+}
+
+/// Final outcome of an action; errors are already user-friendly messages
+#[derive(Debug, PartialEq, Clone)]
+pub enum AsyncActionResult {
+    PodcastsUpdate(Result<Vec<podcast::Model>, String>),
+    EpisodesUpdate(Result<EpisodesRefresh, String>),
+    AddPodcastResult(Result<(), String>),
+    FeedCandidatesFound(String, String, Option<FeedCredentials>, Vec<FeedCandidate>),
+    EpisodeStateSaved(Result<(), String>),
+    EpisodeStateUpdate(Result<f64, String>),
+    AllEpisodeStatesUpdate(Result<HashMap<String, f64>, String>),
+    ImportHistoryResult(Result<ImportReport, String>),
+    DirectorySearchResult(Result<Vec<DirectoryResult>, String>),
+    CredentialsUnlocked(Result<(), String>),
+    CredentialsLoaded(i32, Option<FeedCredentials>),
+    CredentialsSaved(Result<(), String>),
+    /// Feeds that could be refreshed; the others keep their stored episodes
+    FeedsRefreshed(Result<Vec<EpisodesRefresh>, String>),
+    PodcastSaved(Result<(), String>),
+    Unsubscribed(i32, Result<(), String>),
+    ConfigApplied(Result<(), String>),
+    GroupsUpdate(Result<PodcastGroups, String>),
+    TagEpisodesUpdate(Result<TagEpisodes, String>),
+    PlaylistsUpdate(Result<Vec<Playlist>, String>),
+    PlaylistEpisodesUpdate(Result<PlaylistEpisodes, String>),
+    InboxUpdate(Result<Vec<episode::Model>, String>),
+    /// Link of the episode and whether it could be saved
+    EpisodeDownloaded(String, Result<(), String>),
+    SessionLogged(Result<(), String>),
+    ListeningStatsUpdate(Result<ListeningStats, String>),
+    HistoryExported(Result<PathBuf, String>),
+    /// Artwork URL and its thumbnail as PNG
+    ArtworkLoaded(String, Result<Vec<u8>, String>),
+    BookmarksUpdate(Result<Vec<bookmark::Model>, String>),
+    BookmarksExported(Result<PathBuf, String>),
+    /// Link looked up and the episode, if it's still in its feed
+    EpisodeFound(String, Result<Option<Box<episode::Model>>, String>),
+    /// File the clip was written to and its length in seconds
+    ClipExported(Result<(PathBuf, f64), String>),
+    /// Link of the episode and its pauses
+    SilencesDetected(String, Result<Vec<Silence>, String>),
+    /// Link of the episode and why it can't be played, if it can't
+    EnclosureProbed(String, Result<(), String>),
+    DatabaseUnavailable(DatabaseFailure),
+    /// The database file in use once it opened after a failure
+    DatabaseReady(PathBuf),
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Progress {
+    Started,
+    Downloading { received: u64, total: Option<u64> },
+    /// Audio is being decoded, which has no measure of its own
+    Decoding,
+    Parsed(usize),
+}
+
+impl Progress {
+    pub fn describe(&self) -> String {
+        match self {
+            Progress::Started => "Connecting…".to_string(),
+            Progress::Downloading { received, total: Some(total) } if *total > 0 =>
+                format!("Downloading {}%", received * 100 / total),
+            Progress::Downloading { received, .. } => format!("Downloading {} KB", received / 1024),
+            Progress::Decoding => "Decoding…".to_string(),
+            Progress::Parsed(items) => format!("Processing {} items…", items),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum AsyncEvent {
+    Progress(Progress),
+    Finished(AsyncActionResult),
+    Cancelled,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct AsyncResponse {
+    pub request_id: RequestId,
+    pub event: AsyncEvent,
+}
+
+/// Operations the UI follows with a spinner
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum RequestKind {
+    Podcasts,
+    Episodes,
+    EpisodeStates,
+    PlaybackPosition,
+    Search,
+    Import,
+    AddPodcast,
+    Refresh,
+    Download,
+    Clip,
+    Silence,
+    Probe,
+}
+
+impl RequestKind {
+    /// Only the newest request of an exclusive kind matters; older ones are cancelled
+    fn is_exclusive(&self) -> bool {
+        !matches!(self, RequestKind::AddPodcast | RequestKind::Download)
+    }
+}
+
+#[derive(Default)]
+struct RequestTracker {
+    in_flight: HashMap<RequestId, (RequestKind, Progress)>,
+    latest: HashMap<RequestKind, RequestId>,
+}
+
+/// UI side of the action channel. Hands out request ids, remembers which requests are still
+/// running and filters out responses to requests that were superseded in the meantime.
+pub struct ActionSender {
+    tx: UnboundedSender<AsyncRequest>,
+    next_id: Cell<RequestId>,
+    requests: RefCell<RequestTracker>,
+}
+
+impl ActionSender {
+    pub fn new(tx: UnboundedSender<AsyncRequest>) -> Self {
+        ActionSender {
+            tx,
+            next_id: Cell::new(1),
+            requests: RefCell::new(RequestTracker::default()),
+        }
+    }
+
+    pub fn send(&self, action: AsyncAction) -> RequestId {
+        let id = self.next_id.get();
+        self.next_id.set(id + 1);
+
+        if let Err(e) = self.tx.send(AsyncRequest { id, action }) {
+            error!("Failed to send request {}: {}", id, e);
+        }
+        id
+    }
+
+    /// Sends an action whose progress is shown in the UI, cancelling the previous request of
+    /// the same kind if it's exclusive
+    pub fn send_tracked(&self, kind: RequestKind, action: AsyncAction) -> RequestId {
+        let id = self.send(action);
+
+        let superseded = {
+            let mut requests = self.requests.borrow_mut();
+            requests.in_flight.insert(id, (kind, Progress::Started));
+            requests.latest.insert(kind, id)
+                .filter(|previous| kind.is_exclusive() && requests.in_flight.contains_key(previous))
+        };
+        if let Some(previous) = superseded {
+            self.cancel(previous);
+        }
+        id
+    }
+
+    pub fn cancel(&self, id: RequestId) {
+        self.send(AsyncAction::Cancel(id));
+    }
+
+    /// Cancels the current request of an exclusive `kind`, if one is running
+    pub fn cancel_kind(&self, kind: RequestKind) {
+        let current = {
+            let requests = self.requests.borrow();
+            requests.latest.get(&kind).copied().filter(|id| requests.in_flight.contains_key(id))
+        };
+        if let Some(id) = current {
+            self.cancel(id);
+        }
+    }
+
+    pub fn in_flight(&self, kind: RequestKind) -> bool {
+        self.progress(kind).is_some()
+    }
+
+    /// Latest progress of the current request of `kind`, if one is running
+    pub fn progress(&self, kind: RequestKind) -> Option<Progress> {
+        let requests = self.requests.borrow();
+        if kind.is_exclusive() {
+            let id = requests.latest.get(&kind)?;
+            requests.in_flight.get(id).map(|(_, progress)| *progress)
+        } else {
+            requests.in_flight.values()
+                .find(|(k, _)| *k == kind)
+                .map(|(_, progress)| *progress)
+        }
+    }
+
+    /// Records a response and returns its result unless it answers a superseded request
+    pub fn accept(&self, response: AsyncResponse) -> Option<AsyncActionResult> {
+        let mut requests = self.requests.borrow_mut();
+        match response.event {
+            AsyncEvent::Progress(progress) => {
+                if let Some((_, current)) = requests.in_flight.get_mut(&response.request_id) {
+                    *current = progress;
+                }
+                None
+            }
+            AsyncEvent::Cancelled => {
+                requests.in_flight.remove(&response.request_id);
+                None
+            }
+            AsyncEvent::Finished(result) => match requests.in_flight.remove(&response.request_id) {
+                Some((kind, _)) if kind.is_exclusive()
+                    && requests.latest.get(&kind) != Some(&response.request_id) => None,
+                _ => Some(result),
+            },
+        }
+    }
+}
+
+/// Cancellation tokens of the requests the worker is still processing
+pub type InFlight = Arc<Mutex<HashMap<RequestId, CancellationToken>>>;
+
+/// Lets the worker wake the UI when it sends something; the egui context only exists once
+/// the window has been created, responses sent before that are picked up by the first frame
+#[derive(Clone, Default)]
+pub struct UiWaker(Arc<OnceLock<egui::Context>>);
+
+impl UiWaker {
+    pub fn set(&self, ctx: egui::Context) {
+        let _ = self.0.set(ctx);
+    }
+
+    fn wake(&self) {
+        if let Some(ctx) = self.0.get() {
+            ctx.request_repaint();
+        }
+    }
+}
+
+/// Worker side of one request: reports progress and the result back to the UI
+#[derive(Clone)]
+pub struct ActionContext {
+    pub request_id: RequestId,
+    pub cancel: CancellationToken,
+    tx: UnboundedSender<AsyncResponse>,
+    waker: UiWaker,
+}
+
+impl ActionContext {
+    pub fn new(request_id: RequestId, tx: UnboundedSender<AsyncResponse>, waker: UiWaker) -> Self {
+        ActionContext {
+            request_id,
+            cancel: CancellationToken::new(),
+            tx,
+            waker,
+        }
+    }
+
+    pub fn progress(&self, progress: Progress) {
+        self.send(AsyncEvent::Progress(progress));
+    }
+
+    /// Callback for `HttpClient::fetch_document` that reports download progress
+    pub fn download_progress(&self) -> impl Fn(u64, Option<u64>) + '_ {
+        |received, total| self.progress(Progress::Downloading { received, total })
+    }
+
+    pub fn finish(&self, result: AsyncActionResult) {
+        self.send(AsyncEvent::Finished(result));
+    }
+
+    /// Tells the UI this request won't get a result, as if it had been cancelled
+    pub fn abandon(&self) {
+        self.send(AsyncEvent::Cancelled);
+    }
+
+    /// Runs a network-bound action in its own task so it can't hold up other actions, and
+    /// registers it in `in_flight` so `AsyncAction::Cancel` can abandon it
+    pub fn spawn<F>(self, in_flight: &InFlight, action: F)
+    where
+        F: std::future::Future<Output = AsyncActionResult> + Send + 'static,
+    {
+        if let Ok(mut in_flight) = in_flight.lock() {
+            in_flight.insert(self.request_id, self.cancel.clone());
+        }
+        let in_flight = in_flight.clone();
+
+        self.progress(Progress::Started);
+        tokio::spawn(async move {
+            let result = action.await;
+            if let Ok(mut in_flight) = in_flight.lock() {
+                in_flight.remove(&self.request_id);
+            }
+
+            if self.cancel.is_cancelled() {
+                self.send(AsyncEvent::Cancelled);
+            } else {
+                self.finish(result);
+            }
+        });
+    }
+
+    fn send(&self, event: AsyncEvent) {
+        let _ = self.tx.send(AsyncResponse {
+            request_id: self.request_id,
+            event,
+        });
+        self.waker.wake();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
+    use super::*;
+
+    fn sender() -> (ActionSender, UnboundedReceiver<AsyncRequest>) {
+        let (tx, rx) = unbounded_channel();
+        (ActionSender::new(tx), rx)
+    }
+
+    fn response(request_id: RequestId, event: AsyncEvent) -> AsyncResponse {
+        AsyncResponse { request_id, event }
+    }
+
+    fn episodes(podcast_id: i32) -> AsyncActionResult {
+        AsyncActionResult::EpisodesUpdate(Err(format!("podcast {}", podcast_id)))
+    }
+
+    #[test]
+    fn superseded_request_is_cancelled_and_its_result_dropped() {
+        let (sender, mut rx) = sender();
+        let first = sender.send_tracked(RequestKind::Episodes, AsyncAction::GetEpisodes("a".to_string(), 1));
+        let second = sender.send_tracked(RequestKind::Episodes, AsyncAction::GetEpisodes("b".to_string(), 2));
+
+        assert_eq!(rx.try_recv().unwrap().action, AsyncAction::GetEpisodes("a".to_string(), 1));
+        assert_eq!(rx.try_recv().unwrap().action, AsyncAction::GetEpisodes("b".to_string(), 2));
+        assert_eq!(rx.try_recv().unwrap().action, AsyncAction::Cancel(first));
+        assert!(rx.try_recv().is_err());
+
+        // The newer request answers first, then the older one finishes anyway
+        let progress = Progress::Downloading { received: 1024, total: None };
+        assert_eq!(sender.accept(response(second, AsyncEvent::Progress(progress))), None);
+        assert_eq!(sender.progress(RequestKind::Episodes), Some(progress));
+        assert_eq!(sender.accept(response(second, AsyncEvent::Finished(episodes(2)))), Some(episodes(2)));
+        assert_eq!(sender.accept(response(first, AsyncEvent::Finished(episodes(1)))), None);
+
+        assert!(!sender.in_flight(RequestKind::Episodes));
+        assert_eq!(sender.progress(RequestKind::Episodes), None);
+    }
+
+    #[test]
+    fn stale_result_does_not_end_the_newer_request() {
+        let (sender, _rx) = sender();
+        let first = sender.send_tracked(RequestKind::Episodes, AsyncAction::GetEpisodes("a".to_string(), 1));
+        let second = sender.send_tracked(RequestKind::Episodes, AsyncAction::GetEpisodes("b".to_string(), 2));
+
+        assert_eq!(sender.accept(response(first, AsyncEvent::Finished(episodes(1)))), None);
+        assert!(sender.in_flight(RequestKind::Episodes));
+        assert_eq!(sender.progress(RequestKind::Episodes), Some(Progress::Started));
+
+        assert_eq!(sender.accept(response(second, AsyncEvent::Finished(episodes(2)))), Some(episodes(2)));
+        assert!(!sender.in_flight(RequestKind::Episodes));
+    }
+
+    #[test]
+    fn finished_request_is_not_cancelled_by_the_next() {
+        let (sender, mut rx) = sender();
+        let first = sender.send_tracked(RequestKind::Episodes, AsyncAction::GetEpisodes("a".to_string(), 1));
+        assert_eq!(sender.accept(response(first, AsyncEvent::Finished(episodes(1)))), Some(episodes(1)));
+        sender.send_tracked(RequestKind::Episodes, AsyncAction::GetEpisodes("b".to_string(), 2));
+
+        let sent: Vec<AsyncAction> = std::iter::from_fn(|| rx.try_recv().ok()).map(|request| request.action).collect();
+        assert!(!sent.contains(&AsyncAction::Cancel(first)));
+    }
+
+    #[test]
+    fn requests_that_are_not_exclusive_run_side_by_side() {
+        let (sender, mut rx) = sender();
+        let first = sender.send_tracked(RequestKind::Download, AsyncAction::GetPodcasts);
+        let second = sender.send_tracked(RequestKind::Download, AsyncAction::GetPodcasts);
+        let sent: Vec<AsyncAction> = std::iter::from_fn(|| rx.try_recv().ok()).map(|request| request.action).collect();
+        assert_eq!(sent, [AsyncAction::GetPodcasts, AsyncAction::GetPodcasts]);
+
+        let done = AsyncActionResult::PodcastsUpdate(Ok(Vec::new()));
+        assert_eq!(sender.accept(response(first, AsyncEvent::Finished(done.clone()))), Some(done.clone()));
+        assert!(sender.in_flight(RequestKind::Download));
+        assert_eq!(sender.accept(response(second, AsyncEvent::Cancelled)), None);
+        assert!(!sender.in_flight(RequestKind::Download));
+    }
+}
@@ -0,0 +1,391 @@
+//! Turns episode show notes into a few kinds of blocks the UI knows how to draw. Only
+//! paragraphs, line breaks, lists, links and bold/italic text survive; every other tag is
+//! dropped, along with the content of scripts and styles.
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Inline {
+    Text { text: String, bold: bool, italic: bool },
+    Link { text: String, url: String },
+    /// A time in the episode like `12:34`, in seconds
+    Timestamp { text: String, seconds: f64 },
+    LineBreak,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Block {
+    Paragraph(Vec<Inline>),
+    Heading(Vec<Inline>),
+    /// A list entry with its bullet or number
+    ListItem(String, Vec<Inline>),
+}
+
+/// Tags whose content isn't text meant for the reader
+const SKIPPED_TAGS: [&str; 6] = ["script", "style", "iframe", "object", "noscript", "template"];
+const BLOCK_TAGS: [&str; 8] = ["p", "div", "blockquote", "section", "article", "pre", "table", "tr"];
+const HEADING_TAGS: [&str; 6] = ["h1", "h2", "h3", "h4", "h5", "h6"];
+
+pub fn parse(notes: &str) -> Vec<Block> {
+    if !notes.contains('<') {
+        return parse_plain(notes);
+    }
+
+    let mut builder = Builder::default();
+    let mut rest = notes;
+    while let Some(start) = rest.find('<') {
+        builder.text(&rest[..start]);
+        rest = &rest[start..];
+
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+        let Some(end) = tag_end(rest) else {
+            // A lone `<` is just text
+            builder.text("<");
+            rest = &rest[1..];
+            continue;
+        };
+        builder.tag(&rest[1..end]);
+        rest = &rest[end + 1..];
+    }
+    builder.text(rest);
+    builder.finish()
+}
+
+/// Notes without markup, where blank lines separate paragraphs
+fn parse_plain(notes: &str) -> Vec<Block> {
+    let mut builder = Builder::default();
+    for paragraph in notes.replace("\r\n", "\n").split("\n\n") {
+        for (index, line) in paragraph.lines().enumerate() {
+            if index > 0 {
+                builder.line_break();
+            }
+            builder.push_text(line);
+        }
+        builder.flush();
+    }
+    builder.finish()
+}
+
+/// Index of the `>` closing the tag `html` starts with, skipping over quoted attributes
+fn tag_end(html: &str) -> Option<usize> {
+    let mut quote = None;
+    for (index, c) in html.char_indices().skip(1) {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '>') => return Some(index),
+            (None, '<') => return None,
+            _ => {}
+        }
+    }
+    None
+}
+
+#[derive(Default)]
+struct Builder {
+    blocks: Vec<Block>,
+    inlines: Vec<Inline>,
+    bold: usize,
+    italic: usize,
+    heading: bool,
+    link: Option<(String, String)>,
+    /// Item counter of each open list, `None` for bulleted ones
+    lists: Vec<Option<usize>>,
+    marker: Option<String>,
+    skipping: Option<String>,
+}
+
+impl Builder {
+    fn tag(&mut self, tag: &str) {
+        let closing = tag.starts_with('/');
+        let tag = tag.trim_start_matches('/').trim_end_matches('/');
+        let name_end = tag.find(|c: char| c.is_whitespace()).unwrap_or(tag.len());
+        let name = tag[..name_end].to_ascii_lowercase();
+
+        if let Some(skipped) = &self.skipping {
+            if closing && *skipped == name {
+                self.skipping = None;
+            }
+            return;
+        }
+
+        match name.as_str() {
+            name if SKIPPED_TAGS.contains(&name) && !closing => self.skipping = Some(name.to_string()),
+            "br" => self.line_break(),
+            "b" | "strong" => self.bold = nest(self.bold, closing),
+            "i" | "em" => self.italic = nest(self.italic, closing),
+            "a" if closing => {
+                if let Some((text, url)) = self.link.take() {
+                    self.push_link(text, url);
+                }
+            }
+            "a" => {
+                if let Some(url) = attribute(&tag[name_end..], "href").filter(|url| is_safe_url(url)) {
+                    self.link = Some((String::new(), url));
+                }
+            }
+            "ul" | "ol" => {
+                self.flush();
+                if closing {
+                    self.lists.pop();
+                } else {
+                    self.lists.push((name == "ol").then_some(0));
+                }
+            }
+            "li" => {
+                self.flush();
+                if !closing {
+                    self.marker = Some(match self.lists.last_mut() {
+                        Some(Some(count)) => {
+                            *count += 1;
+                            format!("{}.", count)
+                        }
+                        _ => "•".to_string(),
+                    });
+                }
+            }
+            name if HEADING_TAGS.contains(&name) => {
+                self.flush();
+                self.heading = !closing;
+            }
+            name if BLOCK_TAGS.contains(&name) => self.flush(),
+            _ => {}
+        }
+    }
+
+    /// Text from between tags, with entities still in it
+    fn text(&mut self, html: &str) {
+        if self.skipping.is_none() && !html.is_empty() {
+            self.push_text(&decode_entities(html));
+        }
+    }
+
+    fn push_text(&mut self, text: &str) {
+        let at_line_start = matches!(self.inlines.last(), None | Some(Inline::LineBreak))
+            && self.link.as_ref().is_none_or(|(link_text, _)| link_text.is_empty());
+        let ends_with_space = self.pending_text().is_some_and(|t| t.ends_with(' '));
+        let mut collapsed = String::with_capacity(text.len());
+        let mut space = at_line_start || ends_with_space;
+        for c in text.chars() {
+            if c.is_whitespace() {
+                if !space {
+                    collapsed.push(' ');
+                }
+                space = true;
+            } else {
+                collapsed.push(c);
+                space = false;
+            }
+        }
+        if collapsed.is_empty() {
+            return;
+        }
+
+        if let Some((link_text, _)) = &mut self.link {
+            link_text.push_str(&collapsed);
+            return;
+        }
+        let (bold, italic) = (self.bold > 0 || self.heading, self.italic > 0);
+        for (part, seconds) in split_timestamps(&collapsed) {
+            match seconds {
+                Some(seconds) => self.inlines.push(Inline::Timestamp { text: part.to_string(), seconds }),
+                None => match self.inlines.last_mut() {
+                    Some(Inline::Text { text, bold: b, italic: i }) if *b == bold && *i == italic => text.push_str(part),
+                    _ => self.inlines.push(Inline::Text { text: part.to_string(), bold, italic }),
+                },
+            }
+        }
+    }
+
+    fn pending_text(&self) -> Option<&str> {
+        match (&self.link, self.inlines.last()) {
+            (Some((text, _)), _) if !text.is_empty() => Some(text),
+            (_, Some(Inline::Text { text, .. } | Inline::Link { text, .. } | Inline::Timestamp { text, .. })) => Some(text),
+            _ => None,
+        }
+    }
+
+    fn push_link(&mut self, text: String, url: String) {
+        let text = if text.trim().is_empty() { url.clone() } else { text };
+        self.inlines.push(Inline::Link { text, url });
+    }
+
+    fn line_break(&mut self) {
+        if !matches!(self.inlines.last(), None | Some(Inline::LineBreak)) {
+            self.inlines.push(Inline::LineBreak);
+        }
+    }
+
+    /// Ends the block being built
+    fn flush(&mut self) {
+        if let Some((text, url)) = self.link.take() {
+            self.push_link(text, url);
+        }
+        while matches!(self.inlines.last(), Some(Inline::LineBreak)) {
+            self.inlines.pop();
+        }
+        if let Some(Inline::Text { text, .. }) = self.inlines.last_mut() {
+            text.truncate(text.trim_end().len());
+        }
+        let inlines = std::mem::take(&mut self.inlines);
+        let marker = self.marker.take();
+        if inlines.is_empty() {
+            return;
+        }
+        self.blocks.push(match marker {
+            Some(marker) => Block::ListItem(marker, inlines),
+            None if self.heading => Block::Heading(inlines),
+            None => Block::Paragraph(inlines),
+        });
+    }
+
+    fn finish(mut self) -> Vec<Block> {
+        self.flush();
+        self.blocks
+    }
+}
+
+fn nest(depth: usize, closing: bool) -> usize {
+    if closing { depth.saturating_sub(1) } else { depth + 1 }
+}
+
+/// Value of an attribute in the rest of a start tag
+fn attribute(attributes: &str, name: &str) -> Option<String> {
+    let mut rest = attributes;
+    while let Some(index) = rest.to_ascii_lowercase().find(name) {
+        let before = rest[..index].chars().last();
+        let after = rest[index + name.len()..].trim_start();
+        rest = &rest[index + name.len()..];
+        if before.is_some_and(|c| !c.is_whitespace()) {
+            continue;
+        }
+        let Some(value) = after.strip_prefix('=') else {
+            continue;
+        };
+        let value = value.trim_start();
+        let value = match value.chars().next() {
+            Some(quote @ ('"' | '\'')) => value[1..].split(quote).next().unwrap_or_default(),
+            _ => value.split_whitespace().next().unwrap_or_default(),
+        };
+        return Some(decode_entities(value).trim().to_string());
+    }
+    None
+}
+
+/// Only links that open in a browser or mail client are kept
+fn is_safe_url(url: &str) -> bool {
+    let lower = url.to_ascii_lowercase();
+    ["http://", "https://", "mailto:"].iter().any(|scheme| lower.starts_with(scheme))
+}
+
+fn decode_entities(html: &str) -> String {
+    let mut decoded = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let entity = rest[1..].find(';')
+            .filter(|end| *end <= 10)
+            .and_then(|end| decode_entity(&rest[1..=end]).map(|c| (c, end + 2)));
+        match entity {
+            Some((c, length)) => {
+                decoded.push(c);
+                rest = &rest[length..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+fn decode_entity(entity: &str) -> Option<char> {
+    if let Some(number) = entity.strip_prefix('#') {
+        let code = match number.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => number.parse().ok()?,
+        };
+        return char::from_u32(code);
+    }
+    Some(match entity {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => ' ',
+        "ndash" => '–',
+        "mdash" => '—',
+        "hellip" => '…',
+        "lsquo" => '‘',
+        "rsquo" => '’',
+        "ldquo" => '“',
+        "rdquo" => '”',
+        "bull" => '•',
+        "middot" => '·',
+        "copy" => '©',
+        _ => return None,
+    })
+}
+
+/// Splits text around times like `1:02:03` or `12:34`, giving each time in seconds
+fn split_timestamps(text: &str) -> Vec<(&str, Option<f64>)> {
+    let mut parts = Vec::new();
+    let bytes = text.as_bytes();
+    let mut plain_start = 0;
+    let mut index = 0;
+    while index < bytes.len() {
+        let at_boundary = index == 0 || !bytes[index - 1].is_ascii_alphanumeric() && bytes[index - 1] != b':';
+        if bytes[index].is_ascii_digit() && at_boundary {
+            if let Some((length, seconds)) = timestamp_at(&bytes[index..]) {
+                if plain_start < index {
+                    parts.push((&text[plain_start..index], None));
+                }
+                parts.push((&text[index..index + length], Some(seconds)));
+                index += length;
+                plain_start = index;
+                continue;
+            }
+        }
+        index += 1;
+    }
+    if plain_start < text.len() {
+        parts.push((&text[plain_start..], None));
+    }
+    parts
+}
+
+/// Length and value of the timestamp `bytes` start with, if they do
+fn timestamp_at(bytes: &[u8]) -> Option<(usize, f64)> {
+    let mut fields = Vec::new();
+    let mut index = 0;
+    loop {
+        let digits = bytes[index..].iter().take_while(|b| b.is_ascii_digit()).count();
+        if digits == 0 || digits > 2 {
+            return None;
+        }
+        let value: u32 = std::str::from_utf8(&bytes[index..index + digits]).ok()?.parse().ok()?;
+        fields.push((value, digits));
+        index += digits;
+        if fields.len() < 3 && bytes.get(index) == Some(&b':') && bytes.get(index + 1).is_some_and(u8::is_ascii_digit) {
+            index += 1;
+        } else {
+            break;
+        }
+    }
+    if bytes.get(index).is_some_and(|b| b.is_ascii_alphanumeric() || *b == b':') {
+        return None;
+    }
+
+    // Everything after the leading field is two-digit minutes or seconds
+    if fields.len() < 2 || fields[1..].iter().any(|(value, digits)| *digits != 2 || *value >= 60) {
+        return None;
+    }
+    let seconds = fields.iter().fold(0, |total, (value, _)| total * 60 + value);
+    Some((index, seconds as f64))
+}
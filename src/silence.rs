@@ -0,0 +1,254 @@
+use std::path::Path;
+use tokio_util::sync::CancellationToken;
+use crate::decode;
+use crate::error::RustcastResult;
+
+/// Shortest pause that can be set to be trimmed
+pub const MIN_SILENCE_SECONDS: f64 = 0.5;
+pub const MAX_SILENCE_SECONDS: f64 = 5.0;
+/// How close to its target the player has to report a skip before it counts as done
+const LANDED_TOLERANCE_SECONDS: f64 = 1.0;
+/// Audio this far under full scale counts as silence
+const SILENCE_THRESHOLD_DB: f64 = -45.0;
+/// Loudness is judged over windows this long
+const WINDOW_SECONDS: f64 = 0.05;
+/// How much of each trimmed pause is still heard, so speech doesn't run together
+const KEPT_SILENCE_SECONDS: f64 = 0.3;
+
+/// A pause in an episode, in seconds on its own timeline
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Silence {
+    pub start: f64,
+    pub end: f64,
+}
+
+/// Finds stretches quieter than the threshold that last at least `min_seconds`, fed with
+/// audio as it's decoded
+struct SilenceDetector {
+    min_seconds: f64,
+    threshold: f64,
+    window_frames: usize,
+    /// Frames and summed square of the window being filled
+    frames: usize,
+    energy: f64,
+    /// Start of the window being filled
+    window_start: Option<f64>,
+    /// Start of the quiet run the windows so far belong to
+    quiet_since: Option<f64>,
+    silences: Vec<Silence>,
+}
+
+impl SilenceDetector {
+    fn new(min_seconds: f64) -> Self {
+        SilenceDetector {
+            min_seconds,
+            threshold: 10f64.powf(SILENCE_THRESHOLD_DB / 20.0),
+            window_frames: 0,
+            frames: 0,
+            energy: 0.0,
+            window_start: None,
+            quiet_since: None,
+            silences: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, time: f64, samples: &[i16], spec: decode::AudioSpec) {
+        if self.window_frames == 0 {
+            self.window_frames = ((spec.rate as f64 * WINDOW_SECONDS) as usize).max(1);
+        }
+        let channels = spec.channels.max(1) as usize;
+
+        for (index, frame) in samples.chunks_exact(channels).enumerate() {
+            let start = *self.window_start.get_or_insert(time + index as f64 / spec.rate as f64);
+            let mixed = frame.iter().map(|sample| *sample as f64 / 32768.0).sum::<f64>() / channels as f64;
+            self.energy += mixed * mixed;
+            self.frames += 1;
+
+            if self.frames == self.window_frames {
+                let quiet = (self.energy / self.frames as f64).sqrt() < self.threshold;
+                self.end_window(start, quiet);
+                self.frames = 0;
+                self.energy = 0.0;
+                self.window_start = None;
+            }
+        }
+    }
+
+    /// A pause running into the end of the episode is never closed, as nothing follows it
+    fn end_window(&mut self, start: f64, quiet: bool) {
+        match (quiet, self.quiet_since) {
+            (true, None) => self.quiet_since = Some(start),
+            (false, Some(since)) => {
+                self.quiet_since = None;
+                if start - since >= self.min_seconds {
+                    self.silences.push(Silence { start: since, end: start });
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Pauses of an episode at least `min_seconds` long, decoding all of it. Nothing is found
+/// before the end: a streamed episode has to be fetched whole first, and pauses are only
+/// trimmed once it's been decoded.
+///
+/// Blocks while it decodes, so it belongs on a blocking thread.
+pub fn detect(source: &Path, min_seconds: f64, cancel: &CancellationToken) -> RustcastResult<Vec<Silence>> {
+    let mut detector = SilenceDetector::new(min_seconds);
+    decode::decode(source, 0.0, cancel, |time, samples, spec| {
+        detector.push(time, samples, spec);
+        Ok(true)
+    })?;
+    Ok(detector.silences)
+}
+
+/// Skips through the pauses of the episode that's playing. Skipping is a seek within the
+/// episode, so positions stay on the episode's own timeline.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct SilenceTrimmer {
+    /// Link of the episode and its pauses, once they've been found
+    silences: Option<(String, Vec<Silence>)>,
+    /// Target of the skip in progress; the player reports the old position for a moment
+    skipping_to: Option<f64>,
+    /// Where the skip in progress started and its target, until the player gets there
+    unconfirmed: Option<(f64, f64)>,
+    /// Silence skipped since the episode was opened, counting skips the player carried out
+    pub trimmed_seconds: f64,
+}
+
+impl SilenceTrimmer {
+    /// Forgets the previous episode
+    pub fn reset(&mut self) {
+        *self = SilenceTrimmer::default();
+    }
+
+    pub fn set_silences(&mut self, ep_link: String, silences: Vec<Silence>) {
+        self.silences = Some((ep_link, silences));
+        self.skipping_to = None;
+        self.unconfirmed = None;
+    }
+
+    /// Where to continue from if `position` of the episode at `ep_link` is inside a pause.
+    /// The start and end of every pause are kept so speech isn't clipped; everything between
+    /// is skipped.
+    pub fn skip(&mut self, ep_link: &str, position: f64) -> Option<f64> {
+        let Some((link, silences)) = &self.silences else {
+            return None;
+        };
+        if link != ep_link {
+            return None;
+        }
+
+        let kept = KEPT_SILENCE_SECONDS / 2.0;
+        let index = silences.partition_point(|silence| silence.end <= position);
+        let target = silences.get(index)
+            .filter(|silence| position >= silence.start + kept && position < silence.end - kept)
+            .map(|silence| silence.end - kept);
+        match target {
+            Some(target) if self.skipping_to != Some(target) => {
+                self.skipping_to = Some(target);
+                self.unconfirmed = Some((position, target));
+                Some(target)
+            }
+            Some(_) => None,
+            None => {
+                self.skipping_to = None;
+                None
+            }
+        }
+    }
+
+    /// Counts the skip in progress once the player reports `position` at its target, and
+    /// returns where it went from and to. A skip whose seek never lands isn't counted.
+    pub fn landed(&mut self, position: f64) -> Option<(f64, f64)> {
+        let (from, to) = self.unconfirmed?;
+        if (position - to).abs() > LANDED_TOLERANCE_SECONDS {
+            return None;
+        }
+        self.unconfirmed = None;
+        self.trimmed_seconds += to - from;
+        Some((from, to))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trimmer() -> SilenceTrimmer {
+        let mut trimmer = SilenceTrimmer::default();
+        trimmer.set_silences("a".to_string(), vec![
+            Silence { start: 10.0, end: 14.0 },
+            Silence { start: 30.0, end: 31.0 },
+        ]);
+        trimmer
+    }
+
+    #[test]
+    fn skips_the_middle_of_a_pause() {
+        let mut trimmer = trimmer();
+
+        assert_eq!(trimmer.skip("a", 9.0), None);
+        // The first and last 150 ms of the pause are kept
+        assert_eq!(trimmer.skip("a", 10.1), None);
+        assert_eq!(trimmer.skip("a", 10.2), Some(13.85));
+        // The player still reports the old position for a moment
+        assert_eq!(trimmer.skip("a", 10.3), None);
+        assert_eq!(trimmer.skip("b", 30.5), None);
+    }
+
+    #[test]
+    fn counts_skips_once_they_land() {
+        let mut trimmer = trimmer();
+        trimmer.skip("a", 11.0);
+
+        assert_eq!(trimmer.trimmed_seconds, 0.0);
+        assert_eq!(trimmer.landed(11.2), None);
+        assert_eq!(trimmer.trimmed_seconds, 0.0);
+
+        assert_eq!(trimmer.landed(13.9), Some((11.0, 13.85)));
+        assert!((trimmer.trimmed_seconds - 2.85).abs() < 1e-9);
+        // Counted only once
+        assert_eq!(trimmer.landed(14.0), None);
+    }
+
+    #[test]
+    fn skip_that_never_lands_is_not_counted() {
+        let mut trimmer = trimmer();
+        trimmer.skip("a", 11.0);
+
+        // The seek failed and playback went on from where it was
+        for position in [11.5, 12.0, 12.5] {
+            assert_eq!(trimmer.landed(position), None);
+        }
+        assert_eq!(trimmer.trimmed_seconds, 0.0);
+
+        // The next pause replaces it
+        trimmer.skip("a", 30.4);
+        assert_eq!(trimmer.landed(30.85), Some((30.4, 30.85)));
+        assert!((trimmer.trimmed_seconds - 0.45).abs() < 1e-9);
+    }
+
+    #[test]
+    fn detects_pauses_between_sound() {
+        let spec = decode::AudioSpec { rate: 1000, channels: 1 };
+        let loud: Vec<i16> = (0..1000).map(|i| if i % 2 == 0 { 8000 } else { -8000 }).collect();
+        let quiet = vec![0; 1000];
+        let short_quiet = vec![0; 500];
+
+        let mut detector = SilenceDetector::new(1.0);
+        let mut time = 0.0;
+        // One second of sound, two of silence, one of sound, half a second of silence, sound
+        for (samples, seconds) in [(&loud, 1), (&quiet, 2), (&loud, 1), (&short_quiet, 1), (&loud, 1)] {
+            for _ in 0..seconds {
+                detector.push(time, samples, spec);
+                time += samples.len() as f64 / 1000.0;
+            }
+        }
+
+        assert_eq!(detector.silences.len(), 1);
+        let silence = detector.silences[0];
+        assert!((silence.start - 1.0).abs() < 0.06 && (silence.end - 3.0).abs() < 0.06, "{:?}", silence);
+    }
+}
@@ -0,0 +1,409 @@
+use std::collections::{HashMap, VecDeque};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use log::{debug, error, info, warn};
+use rand::RngCore;
+use url::Url;
+use crate::credentials::{self, FeedCredentials, SharedCredentials};
+use crate::http::{forwards_credentials, MAX_REDIRECTS};
+
+/// An upstream connection that sends nothing for this long counts as dropped
+const READ_TIMEOUT: Duration = Duration::from_secs(15);
+/// Times a dropped upstream connection is picked up again before the player is let down
+const MAX_RECONNECTS: u32 = 3;
+/// Throughput is averaged over this much of the recent past
+const THROUGHPUT_WINDOW: Duration = Duration::from_secs(3);
+const CHUNK_SIZE: usize = 64 * 1024;
+
+enum ProxiedStream {
+    Remote {
+        url: String,
+        podcast_id: i32,
+        health: StreamHealth,
+    },
+    /// A downloaded episode
+    File(PathBuf),
+}
+
+/// What the relay has seen of a stream
+#[derive(Debug, Default, Clone)]
+pub struct StreamHealth {
+    /// Size of the media, once the server has said
+    pub total_bytes: Option<u64>,
+    /// Byte ranges handed to the player, in order and merged
+    buffered: Vec<(u64, u64)>,
+    /// Chunks received within the throughput window
+    recent: VecDeque<(Instant, u64)>,
+    /// Dropped connections picked up again
+    pub reconnects: u32,
+}
+
+impl StreamHealth {
+    fn record(&mut self, offset: u64, bytes: u64) {
+        let now = Instant::now();
+        self.recent.push_back((now, bytes));
+        while self.recent.front().is_some_and(|(at, _)| now.duration_since(*at) > THROUGHPUT_WINDOW) {
+            self.recent.pop_front();
+        }
+
+        let (start, end) = (offset, offset + bytes);
+        let index = self.buffered.partition_point(|(_, range_end)| *range_end < start);
+        let mut merged = (start, end);
+        while let Some(range) = self.buffered.get(index).filter(|(range_start, _)| *range_start <= end) {
+            merged = (merged.0.min(range.0), merged.1.max(range.1));
+            self.buffered.remove(index);
+        }
+        self.buffered.insert(index, merged);
+    }
+
+    /// Bytes received per second over the last few seconds
+    pub fn bytes_per_second(&self) -> f64 {
+        let now = Instant::now();
+        let received: u64 = self.recent.iter()
+            .filter(|(at, _)| now.duration_since(*at) <= THROUGHPUT_WINDOW)
+            .map(|(_, bytes)| bytes)
+            .sum();
+        received as f64 / THROUGHPUT_WINDOW.as_secs_f64()
+    }
+
+    /// Buffered ranges as fractions of the whole media, empty while its size isn't known
+    pub fn buffered_fractions(&self) -> Vec<(f64, f64)> {
+        let Some(total) = self.total_bytes.filter(|total| *total > 0) else {
+            return Vec::new();
+        };
+        self.buffered.iter()
+            .map(|(start, end)| (*start as f64 / total as f64, (*end as f64 / total as f64).min(1.0)))
+            .collect()
+    }
+}
+
+/// Local HTTP relay for enclosures. The audio player only knows how to open plain URLs, so
+/// streams are served through `127.0.0.1`: the authentication of private feeds is added to the
+/// upstream request here, including range requests for seeking, dropped connections are picked
+/// up again where they broke off, and what arrives is recorded for the stream's health.
+/// Downloaded episodes are served from disk the same way.
+pub struct StreamProxy {
+    port: u16,
+    streams: Arc<Mutex<HashMap<String, ProxiedStream>>>,
+}
+
+impl StreamProxy {
+    pub fn start(credentials: SharedCredentials) -> std::io::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let port = listener.local_addr()?.port();
+        let streams: Arc<Mutex<HashMap<String, ProxiedStream>>> = Arc::new(Mutex::new(HashMap::new()));
+
+        let thread_streams = streams.clone();
+        std::thread::spawn(move || {
+            for connection in listener.incoming() {
+                let Ok(connection) = connection else {
+                    continue;
+                };
+                let streams = thread_streams.clone();
+                let credentials = credentials.clone();
+                std::thread::spawn(move || {
+                    if let Err(e) = relay(connection, &streams, &credentials) {
+                        warn!("Stream relay connection failed: {}", e);
+                    }
+                });
+            }
+        });
+
+        Ok(StreamProxy {
+            port,
+            streams,
+        })
+    }
+
+    /// URL the player should open: a relay URL, or the enclosure itself if the relay is
+    /// unavailable
+    pub fn stream_url(&self, podcast_id: i32, link: &str) -> String {
+        self.register(ProxiedStream::Remote {
+            url: link.to_string(),
+            podcast_id,
+            health: StreamHealth::default(),
+        }).unwrap_or_else(|| link.to_string())
+    }
+
+    /// What the relay has seen of the stream behind a URL from `stream_url`
+    pub fn health(&self, url: &str) -> Option<StreamHealth> {
+        let token = url.strip_prefix(&format!("http://127.0.0.1:{}/", self.port))?;
+        match self.streams.lock().ok()?.get(token)? {
+            ProxiedStream::Remote { health, .. } => Some(health.clone()),
+            ProxiedStream::File(_) => None,
+        }
+    }
+
+    /// URL serving a downloaded episode from disk
+    pub fn file_url(&self, file: &Path) -> Option<String> {
+        self.register(ProxiedStream::File(file.to_path_buf()))
+    }
+
+    fn register(&self, stream: ProxiedStream) -> Option<String> {
+        let mut token = [0u8; 16];
+        rand::thread_rng().fill_bytes(&mut token);
+        let token: String = token.iter().map(|b| format!("{:02x}", b)).collect();
+
+        match self.streams.lock() {
+            Ok(mut streams) => {
+                streams.insert(token.clone(), stream);
+                Some(format!("http://127.0.0.1:{}/{}", self.port, token))
+            }
+            Err(e) => {
+                error!("Stream relay is unavailable: {}", e);
+                None
+            }
+        }
+    }
+}
+
+fn relay(connection: TcpStream, streams: &Mutex<HashMap<String, ProxiedStream>>, credentials: &SharedCredentials) -> std::io::Result<()> {
+    let mut reader = BufReader::new(connection.try_clone()?);
+    let mut writer = connection;
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let token = request_line.split_whitespace().nth(1).unwrap_or("").trim_start_matches('/').to_string();
+
+    let mut range = None;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("range") {
+                range = Some(value.trim().to_string());
+            }
+        }
+    }
+
+    let target = streams.lock().ok()
+        .and_then(|streams| match streams.get(&token)? {
+            ProxiedStream::Remote { url, podcast_id, .. } => Some(Ok((url.clone(), *podcast_id))),
+            ProxiedStream::File(file) => Some(Err(file.clone())),
+        });
+    let (url, podcast_id) = match target {
+        Some(Ok(remote)) => remote,
+        Some(Err(file)) => return serve_file(writer, &file, range.as_deref()),
+        None => return writer.write_all(b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"),
+    };
+    let update_health = |update: &dyn Fn(&mut StreamHealth)| {
+        if let Ok(mut streams) = streams.lock() {
+            if let Some(ProxiedStream::Remote { health, .. }) = streams.get_mut(&token) {
+                update(health);
+            }
+        }
+    };
+
+    // Redirects are followed by `upstream`, which decides where the credentials may go
+    let agent = ureq::AgentBuilder::new()
+        .timeout_connect(Duration::from_secs(30))
+        .timeout_read(READ_TIMEOUT)
+        .redirects(0)
+        .build();
+    let feed_credentials = credentials::credentials_for(credentials, podcast_id);
+    let response = match upstream(&agent, &url, feed_credentials.as_ref(), range.as_deref()) {
+        Ok(response) => response,
+        Err(e) => {
+            warn!("Upstream request for an enclosure failed: {}", e);
+            return writer.write_all(b"HTTP/1.1 502 Bad Gateway\r\nContent-Length: 0\r\nConnection: close\r\n\r\n");
+        }
+    };
+
+    let mut head = format!("HTTP/1.1 {} {}\r\n", response.status(), response.status_text());
+    for name in ["content-type", "content-length", "content-range", "accept-ranges"] {
+        if let Some(value) = response.header(name) {
+            head.push_str(&format!("{}: {}\r\n", name, value));
+        }
+    }
+    head.push_str("Connection: close\r\n\r\n");
+    writer.write_all(head.as_bytes())?;
+    if !(200..300).contains(&response.status()) {
+        std::io::copy(&mut response.into_reader(), &mut writer)?;
+        return writer.flush();
+    }
+
+    // `bytes start-end/total` for a range, otherwise the whole media from its start
+    let content_range = response.header("content-range")
+        .and_then(|range| range.strip_prefix("bytes "))
+        .and_then(|range| range.split_once('/'))
+        .map(|(range, total)| (
+            range.split('-').next().and_then(|start| start.trim().parse::<u64>().ok()).unwrap_or(0),
+            total.trim().parse::<u64>().ok(),
+        ));
+    let length = response.header("content-length").and_then(|length| length.parse::<u64>().ok());
+    let (start, total) = content_range.unwrap_or((0, length));
+    update_health(&|health| health.total_bytes = health.total_bytes.or(total));
+
+    let mut body = response.into_reader();
+    let mut offset = start;
+    let mut reconnects = 0;
+    let mut chunk = vec![0; CHUNK_SIZE];
+    loop {
+        let failure = match body.read(&mut chunk) {
+            Ok(0) if length.is_none_or(|length| offset - start >= length) => break,
+            Ok(0) => std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "the server closed the connection early"),
+            Ok(read) => {
+                writer.write_all(&chunk[..read])?;
+                update_health(&|health| health.record(offset, read as u64));
+                offset += read as u64;
+                continue;
+            }
+            Err(e) => e,
+        };
+
+        // Pick the stream up where it broke off; the player only sees it slow down
+        if reconnects == MAX_RECONNECTS {
+            warn!("Giving up on {} at byte {}: {}", url, offset, failure);
+            return Err(failure);
+        }
+        reconnects += 1;
+        std::thread::sleep(Duration::from_secs(1 << (reconnects - 1)));
+        info!("Reconnecting to {} at byte {} after: {}", url, offset, failure);
+        match upstream(&agent, &url, feed_credentials.as_ref(), Some(&format!("bytes={}-", offset))) {
+            Ok(response) if response.status() == 206 => {
+                body = response.into_reader();
+                update_health(&|health| health.reconnects += 1);
+            }
+            Ok(response) => {
+                warn!("{} can't be resumed, the server answered {}", url, response.status());
+                return Err(failure);
+            }
+            Err(e) => warn!("Reconnecting to {} failed: {}", url, e),
+        }
+    }
+    writer.flush()
+}
+
+/// Requests the enclosure and follows its redirects. The feed's credentials only go where
+/// `HttpClient` would send them, so a CDN or tracking prefix on another origin never sees them.
+/// Error statuses come back as responses, to be passed on to the player.
+fn upstream(
+    agent: &ureq::Agent,
+    url: &str,
+    credentials: Option<&FeedCredentials>,
+    range: Option<&str>,
+) -> Result<ureq::Response, String> {
+    let configured = Url::parse(url).map_err(|e| format!("{} is not a valid URL: {}", url, e))?;
+    let mut current = configured.clone();
+
+    for _ in 0..=MAX_REDIRECTS {
+        let mut request = agent.get(current.as_str());
+        if let Some(credentials) = credentials.filter(|_| forwards_credentials(&configured, &current)) {
+            for (name, value) in credentials.request_headers() {
+                request = request.set(&name, &value);
+            }
+        }
+        if let Some(range) = range {
+            request = request.set("Range", range);
+        }
+
+        let response = match request.call() {
+            Ok(response) | Err(ureq::Error::Status(_, response)) => response,
+            Err(e) => return Err(e.to_string()),
+        };
+        let location = match response.status() {
+            301 | 302 | 303 | 307 | 308 => response.header("location").map(str::to_string),
+            _ => None,
+        };
+        let Some(location) = location else {
+            return Ok(response);
+        };
+        let next = current.join(&location).map_err(|_| format!("bad redirect to {}", location))?;
+        debug!("Redirect {} {} -> {}", response.status(), current, next);
+        current = next;
+    }
+    Err("too many redirects".to_string())
+}
+
+/// Sends the file, or the part a `bytes=start-end` range asks for
+fn serve_file(mut writer: TcpStream, path: &Path, range: Option<&str>) -> std::io::Result<()> {
+    let mut file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) => {
+            warn!("Downloaded episode {} can't be read: {}", path.display(), e);
+            return writer.write_all(b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n");
+        }
+    };
+    let size = file.metadata()?.len();
+
+    let requested = range
+        .and_then(|range| range.trim().strip_prefix("bytes="))
+        .and_then(|range| range.split_once('-'))
+        .and_then(|(start, end)| {
+            let start = start.trim().parse::<u64>().ok()?;
+            let end = end.trim().parse::<u64>().map_or(size.saturating_sub(1), |end| end.min(size.saturating_sub(1)));
+            Some((start, end))
+        });
+    let head = match requested {
+        Some((start, end)) if start > end || start >= size => {
+            let head = format!("HTTP/1.1 416 Range Not Satisfiable\r\nContent-Range: bytes */{}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", size);
+            return writer.write_all(head.as_bytes());
+        }
+        Some((start, end)) => {
+            file.seek(SeekFrom::Start(start))?;
+            format!(
+                "HTTP/1.1 206 Partial Content\r\nContent-Range: bytes {}-{}/{}\r\nContent-Length: {}\r\n",
+                start, end, size, end - start + 1,
+            )
+        }
+        None => format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\n", size),
+    };
+    let length = requested.map_or(size, |(start, end)| end - start + 1);
+
+    let content_type = match path.extension().and_then(|e| e.to_str()).unwrap_or("") {
+        "mp3" => "audio/mpeg",
+        "m4a" | "mp4" | "aac" => "audio/mp4",
+        "ogg" | "oga" => "audio/ogg",
+        "wav" => "audio/wav",
+        "flac" => "audio/flac",
+        _ => "application/octet-stream",
+    };
+    let head = format!("{}Content-Type: {}\r\nAccept-Ranges: bytes\r\nConnection: close\r\n\r\n", head, content_type);
+    writer.write_all(head.as_bytes())?;
+    std::io::copy(&mut file.take(length), &mut writer)?;
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_server::{self, header};
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn redirect_to_another_port_drops_credentials() {
+        let (target_url, target) = test_server::serve_body("audio/mpeg", "ID3").await;
+        let (start_url, start) = test_server::serve_once(format!(
+            "HTTP/1.1 302 Found\r\nLocation: {}/episode.mp3\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+            target_url
+        )).await;
+        let credentials = FeedCredentials {
+            username: "user".to_string(),
+            password: "secret".to_string(),
+            headers: vec![("X-Token".to_string(), "token".to_string())],
+        };
+
+        let response = tokio::task::spawn_blocking(move || {
+            let agent = ureq::AgentBuilder::new().redirects(0).build();
+            let response = upstream(&agent, &format!("{}/episode.mp3", start_url), Some(&credentials), Some("bytes=0-"))?;
+            Ok::<_, String>((response.status(), response.into_string().unwrap()))
+        }).await.unwrap().unwrap();
+
+        let first = start.await.unwrap();
+        assert!(header(&first, "Authorization").is_some());
+        assert_eq!(header(&first, "X-Token"), Some("token"));
+
+        // Same host, different port: a different origin
+        let second = target.await.unwrap();
+        assert_eq!(header(&second, "Authorization"), None);
+        assert_eq!(header(&second, "X-Token"), None);
+        assert_eq!(header(&second, "Range"), Some("bytes=0-"));
+
+        assert_eq!(response, (200, "ID3".to_string()));
+    }
+}
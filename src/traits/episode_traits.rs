@@ -1,4 +1,5 @@
 use sea_orm::ActiveValue;
+use crate::enclosure;
 use crate::entity::episode;
 use crate::error::{RustcastError, RustcastResult};
 
@@ -8,12 +9,19 @@ impl episode::ActiveModel {
             .ok_or_else(|| RustcastError::rss_missing_field("title"))?
             .to_string();
 
-        let link = value.enclosure()
-            .ok_or_else(|| RustcastError::rss_missing_field("enclosure"))?
-            .url()
-            .to_string();
+        let enclosure = value.enclosure()
+            .ok_or_else(|| RustcastError::rss_missing_field("enclosure"))?;
+        let link = enclosure.url().to_string();
+        let enclosure_type = enclosure::mime_type(enclosure.mime_type());
+        // Only worth storing when there's more than the enclosure itself
+        let enclosures = Some(enclosure::from_rss_item(&value))
+            .filter(|enclosures| enclosures.len() > 1)
+            .and_then(|enclosures| serde_json::to_string(&enclosures).ok());
 
-        let description = value.description()
+        // `content:encoded` carries the full show notes when a feed has both
+        let description = value.content()
+            .filter(|content| !content.trim().is_empty())
+            .or(value.description())
             .unwrap_or("No description available")
             .to_string();
 
@@ -25,12 +33,25 @@ impl episode::ActiveModel {
             .map(|d| d.to_string())
             .unwrap_or_else(|| "Unknown".to_string());
 
+        let duration = value.itunes_ext()
+            .and_then(|itunes| itunes.duration())
+            .and_then(crate::utils::parse_duration);
+
+        let image_url = value.itunes_ext()
+            .and_then(|itunes| itunes.image())
+            .map(|url| url.trim().to_string())
+            .filter(|url| !url.is_empty());
+
         Ok(episode::ActiveModel {
             title: ActiveValue::Set(Some(title)),
             link: ActiveValue::Set(Some(link)),
             description: ActiveValue::Set(Some(description)),
             guid: ActiveValue::Set(Some(guid)),
             pub_date: ActiveValue::Set(Some(pub_date)),
+            duration: ActiveValue::Set(duration),
+            image_url: ActiveValue::Set(image_url),
+            enclosure_type: ActiveValue::Set(enclosure_type),
+            enclosures: ActiveValue::Set(enclosures),
             ..Default::default()
         })
     }
@@ -1 +1,2 @@
 mod episode_traits;
+mod podcast_traits;
@@ -0,0 +1,10 @@
+use crate::entity::podcast;
+
+impl podcast::Model {
+    /// Title to show, preferring the one the user set over the feed's
+    pub fn display_title(&self) -> Option<&str> {
+        self.title_override.as_deref()
+            .filter(|t| !t.trim().is_empty())
+            .or(self.title.as_deref())
+    }
+}
@@ -0,0 +1,64 @@
+use eframe::egui;
+use crate::entity::episode;
+use crate::podcasts_model::ArtworkState;
+use crate::{artwork, AsyncAction, MyEguiApp};
+
+impl MyEguiApp {
+    /// Starts fetching artwork that isn't loaded or being loaded yet
+    pub(crate) fn request_artwork(&mut self, url: Option<&str>) {
+        let Some(url) = url.filter(|_| self.config.show_artwork) else {
+            return;
+        };
+        if !self.podcasts_model.artwork.contains_key(url) {
+            self.podcasts_model.artwork.insert(url.to_string(), ArtworkState::Loading);
+            self.async_action_tx.send(AsyncAction::LoadArtwork(url.to_string()));
+        }
+    }
+
+    /// Artwork of every podcast in the side panel
+    pub(crate) fn request_podcast_artwork(&mut self) {
+        if !self.config.show_artwork {
+            return;
+        }
+        let missing: Vec<String> = self.podcasts_model.podcasts.iter().flatten()
+            .filter_map(|p| p.image_url.as_ref())
+            .filter(|url| !self.podcasts_model.artwork.contains_key(*url))
+            .cloned()
+            .collect();
+        for url in missing {
+            self.request_artwork(Some(&url));
+        }
+    }
+
+    /// The artwork ready to show, if it's loaded and artwork is turned on
+    pub(crate) fn artwork(&self, url: Option<&str>) -> Option<egui::Image<'static>> {
+        let url = url.filter(|_| self.config.show_artwork)?;
+        match self.podcasts_model.artwork.get(url)? {
+            ArtworkState::Ready(png) => Some(egui::Image::from_bytes(artwork::thumbnail_uri(url), png.clone())),
+            ArtworkState::Loading | ArtworkState::Unavailable => None,
+        }
+    }
+
+    /// Artwork of an episode, or of its podcast if it has none of its own
+    pub(crate) fn episode_artwork_url(&self, episode: &episode::Model) -> Option<String> {
+        episode.image_url.clone().or_else(|| {
+            self.podcasts_model.podcasts.iter().flatten()
+                .find(|p| p.id == episode.podcast_id)
+                .and_then(|p| p.image_url.clone())
+        })
+    }
+
+    pub(crate) fn artwork_loaded(&mut self, url: String, result: Result<Vec<u8>, String>) {
+        // Missing artwork is already logged and isn't worth an error dialog
+        let state = match result {
+            Ok(png) => ArtworkState::Ready(png.into()),
+            Err(_) => ArtworkState::Unavailable,
+        };
+        self.podcasts_model.artwork.insert(url, state);
+    }
+}
+
+/// Square artwork of the given size with rounded corners
+pub(crate) fn thumbnail(ui: &mut egui::Ui, image: egui::Image<'static>, size: f32) {
+    ui.add(image.fit_to_exact_size(egui::vec2(size, size)).rounding(size / 8.0));
+}
@@ -0,0 +1,276 @@
+use std::path::PathBuf;
+use eframe::egui;
+use crate::bookmarks::{format_range, NewBookmark};
+use crate::entity::{bookmark, episode};
+use crate::podcasts_model::{BookmarkDialog, BookmarksWindow};
+use crate::{utils, AsyncAction, MyEguiApp};
+
+/// What was clicked in a list of bookmarks
+pub(crate) enum BookmarkAction {
+    Jump(bookmark::Model),
+    Edit(bookmark::Model),
+    Delete(i32),
+}
+
+impl MyEguiApp {
+    /// Starts a bookmark at the current position of the episode that's playing
+    pub(crate) fn new_bookmark(&mut self) {
+        let Some(episode) = &self.podcasts_model.current_episode else {
+            return;
+        };
+        let Some(link) = episode.link.clone() else {
+            return;
+        };
+        self.podcasts_model.bookmark_dialog = Some(BookmarkDialog {
+            bookmark_id: None,
+            podcast_id: episode.podcast_id,
+            ep_link: link,
+            episode_title: episode.title.clone(),
+            position: self.player_wrapper.inner_player.current_position(),
+            end_position: None,
+            note: String::new(),
+            busy: false,
+        });
+    }
+
+    pub(crate) fn show_bookmark_window(&mut self, ctx: &egui::Context) {
+        let is_current = self.podcasts_model.bookmark_dialog.as_ref().is_some_and(|dialog| {
+            self.podcasts_model.current_episode.as_ref()
+                .is_some_and(|current| current.link.as_ref() == Some(&dialog.ep_link))
+        });
+        let now = self.player_wrapper.inner_player.current_position();
+        let Some(dialog) = &mut self.podcasts_model.bookmark_dialog else {
+            return;
+        };
+        let mut open = true;
+        let mut save = false;
+
+        let title = if dialog.bookmark_id.is_some() { "Edit bookmark" } else { "New bookmark" };
+        egui::Window::new(title)
+            .collapsible(false)
+            .resizable(false)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.strong(dialog.episode_title.as_deref().unwrap_or("Unknown episode"));
+                ui.label(format!("At {}", utils::format_position(dialog.position)));
+
+                ui.horizontal(|ui| {
+                    let mut has_end = dialog.end_position.is_some();
+                    if ui.checkbox(&mut has_end, "Ends at").changed() {
+                        dialog.end_position = has_end.then(|| now.max(dialog.position + 1.0));
+                    }
+                    if let Some(end) = &mut dialog.end_position {
+                        ui.add(egui::DragValue::new(end)
+                            .speed(1.0)
+                            .clamp_range(0.0..=f64::MAX)
+                            .custom_formatter(|seconds, _| utils::format_position(seconds)));
+                        if is_current && ui.small_button("Now").on_hover_text("End at the current position").clicked() {
+                            *end = now;
+                        }
+                    }
+                });
+
+                ui.label("Note");
+                ui.add(egui::TextEdit::multiline(&mut dialog.note).desired_rows(3));
+
+                ui.horizontal(|ui| {
+                    if dialog.busy {
+                        ui.spinner();
+                    } else if ui.button("Save").clicked() {
+                        save = true;
+                    }
+                });
+            });
+
+        if !open {
+            self.podcasts_model.bookmark_dialog = None;
+        } else if save {
+            dialog.busy = true;
+            let action = match dialog.bookmark_id {
+                Some(bookmark_id) => AsyncAction::UpdateBookmark(bookmark_id, dialog.end_position, dialog.note.clone()),
+                None => AsyncAction::AddBookmark(NewBookmark {
+                    podcast_id: dialog.podcast_id,
+                    ep_link: dialog.ep_link.clone(),
+                    episode_title: dialog.episode_title.clone(),
+                    position: dialog.position,
+                    end_position: dialog.end_position,
+                    note: dialog.note.clone(),
+                }),
+            };
+            self.async_action_tx.send(action);
+        }
+    }
+
+    pub(crate) fn open_bookmarks_window(&mut self) {
+        let export_path = dirs::home_dir()
+            .unwrap_or_default()
+            .join("rustcast-bookmarks.md");
+        self.podcasts_model.bookmarks_window = Some(BookmarksWindow {
+            export_path: export_path.display().to_string(),
+            exporting: false,
+        });
+    }
+
+    /// Every bookmark, grouped by episode
+    pub(crate) fn show_bookmarks_window(&mut self, ctx: &egui::Context) {
+        let Some(window) = &mut self.podcasts_model.bookmarks_window else {
+            return;
+        };
+        let bookmarks = &self.podcasts_model.bookmarks;
+        let mut open = true;
+        let mut action = None;
+        let mut export = None;
+
+        egui::Window::new("Bookmarks")
+            .collapsible(false)
+            .resizable(true)
+            .default_width(460.0)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                egui::ScrollArea::vertical()
+                    .max_height(360.0)
+                    .auto_shrink([false, true])
+                    .show(ui, |ui| {
+                        if bookmarks.is_empty() {
+                            ui.weak("No bookmarks yet. Press B while listening to add one.");
+                        }
+                        for episode in bookmarks.chunk_by(|a, b| a.ep_link == b.ep_link) {
+                            ui.strong(episode[0].episode_title.as_deref().unwrap_or("Unknown episode"));
+                            bookmark_rows(ui, episode.iter(), &mut action);
+                            ui.add_space(6.0);
+                        }
+                    });
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.label("Export to");
+                    ui.text_edit_singleline(&mut window.export_path);
+                    if window.exporting {
+                        ui.spinner();
+                    } else if ui.add_enabled(!bookmarks.is_empty(), egui::Button::new("Export Markdown")).clicked() {
+                        window.exporting = true;
+                        export = Some(PathBuf::from(window.export_path.trim()));
+                    }
+                });
+            });
+
+        if let Some(file) = export {
+            self.async_action_tx.send(AsyncAction::ExportBookmarks(file));
+        }
+        if let Some(action) = action {
+            self.apply_bookmark_action(action);
+        }
+        if !open {
+            self.podcasts_model.bookmarks_window = None;
+        }
+    }
+
+    pub(crate) fn apply_bookmark_action(&mut self, action: BookmarkAction) {
+        match action {
+            BookmarkAction::Jump(bookmark) => self.jump_to_bookmark(&bookmark),
+            BookmarkAction::Edit(bookmark) => {
+                self.podcasts_model.bookmark_dialog = Some(BookmarkDialog::from(&bookmark));
+            }
+            BookmarkAction::Delete(bookmark_id) => {
+                self.async_action_tx.send(AsyncAction::DeleteBookmark(bookmark_id));
+            }
+        }
+    }
+
+    /// Plays the bookmarked episode from the bookmark. Bookmarks only know the episode's
+    /// link, so anything but the current episode is looked up first.
+    fn jump_to_bookmark(&mut self, bookmark: &bookmark::Model) {
+        let current = self.podcasts_model.current_episode.as_ref()
+            .filter(|current| current.link.as_ref() == Some(&bookmark.ep_link))
+            .cloned();
+        match current {
+            Some(episode) => self.play_from(episode, bookmark.position),
+            None => {
+                self.podcasts_model.start_at = Some((bookmark.ep_link.clone(), bookmark.position));
+                self.async_action_tx.send(AsyncAction::FindEpisode(bookmark.ep_link.clone()));
+            }
+        }
+    }
+
+    pub(crate) fn episode_found(&mut self, link: String, result: Result<Option<Box<episode::Model>>, String>) {
+        let Some(seconds) = self.podcasts_model.start_at.as_ref()
+            .filter(|(start_link, _)| *start_link == link)
+            .map(|(_, seconds)| *seconds)
+        else {
+            // Something else started playing in the meantime
+            return;
+        };
+        match result {
+            Ok(Some(episode)) => self.play_from(*episode, seconds),
+            Ok(None) => {
+                self.podcasts_model.start_at = None;
+                self.error = "This episode is no longer in its feed, so it can't be played.".to_string();
+                self.show_error = true;
+            }
+            Err(e) => {
+                self.podcasts_model.start_at = None;
+                self.error = e;
+                self.show_error = true;
+            }
+        }
+    }
+
+    pub(crate) fn bookmarks_updated(&mut self, result: Result<Vec<bookmark::Model>, String>) {
+        match result {
+            Ok(bookmarks) => {
+                self.podcasts_model.bookmarks = bookmarks;
+                if self.podcasts_model.bookmark_dialog.as_ref().is_some_and(|dialog| dialog.busy) {
+                    self.podcasts_model.bookmark_dialog = None;
+                }
+            }
+            Err(e) => {
+                if let Some(dialog) = &mut self.podcasts_model.bookmark_dialog {
+                    dialog.busy = false;
+                }
+                self.error = e;
+                self.show_error = true;
+            }
+        }
+    }
+
+    pub(crate) fn bookmarks_exported(&mut self, result: Result<PathBuf, String>) {
+        if let Some(window) = &mut self.podcasts_model.bookmarks_window {
+            window.exporting = false;
+        }
+        match result {
+            Ok(file) => self.notice = Some(format!("Bookmarks exported to {}", file.display())),
+            Err(e) => {
+                self.error = e;
+                self.show_error = true;
+            }
+        }
+    }
+}
+
+/// A row per bookmark with its range, note and what can be done with it
+pub(crate) fn bookmark_rows<'a>(
+    ui: &mut egui::Ui,
+    bookmarks: impl Iterator<Item = &'a bookmark::Model>,
+    action: &mut Option<BookmarkAction>,
+) {
+    for bookmark in bookmarks {
+        ui.horizontal(|ui| {
+            let range = format_range(bookmark.position, bookmark.end_position);
+            if ui.link(range).on_hover_text("Play from here").clicked() {
+                *action = Some(BookmarkAction::Jump(bookmark.clone()));
+            }
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui.small_button("🗑").on_hover_text("Delete").clicked() {
+                    *action = Some(BookmarkAction::Delete(bookmark.id));
+                }
+                if ui.small_button("✏").on_hover_text("Edit").clicked() {
+                    *action = Some(BookmarkAction::Edit(bookmark.clone()));
+                }
+                ui.with_layout(egui::Layout::left_to_right(egui::Align::Center), |ui| {
+                    ui.add(egui::Label::new(&bookmark.note).truncate(true))
+                        .on_hover_text(&bookmark.note);
+                });
+            });
+        });
+    }
+}
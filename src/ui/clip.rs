@@ -0,0 +1,274 @@
+use std::path::PathBuf;
+use eframe::egui;
+use crate::bookmarks::format_range;
+use crate::clip::{ClipRequest, MAX_CLIP_SECONDS};
+use crate::protocol::Progress;
+use crate::{utils, AsyncAction, MyEguiApp, PlayerState, RequestKind};
+
+/// Length of a new selection
+const DEFAULT_CLIP_SECONDS: f64 = 30.0;
+const SELECTOR_HEIGHT: f32 = 10.0;
+const HANDLE_WIDTH: f32 = 6.0;
+/// The `Timeline` leaves room for a time label on each side of its track
+const TIMELINE_FONT_SIZE: f32 = 12.0;
+
+/// Range of the current episode being cut into a clip
+pub(crate) struct ClipDialog {
+    ep_link: String,
+    podcast_id: i32,
+    episode_title: Option<String>,
+    start: f64,
+    end: f64,
+    export_path: String,
+    /// Playing the selection, to stop at its end
+    previewing: bool,
+}
+
+impl MyEguiApp {
+    /// Selects the half minute from the current position of the episode that's playing
+    pub(crate) fn open_clip_window(&mut self) {
+        let Some(episode) = &self.podcasts_model.current_episode else {
+            return;
+        };
+        let Some(link) = episode.link.clone() else {
+            return;
+        };
+        let start = self.player_wrapper.inner_player.current_position();
+        let duration = self.player_wrapper.inner_player.duration();
+        let end = if duration > start { (start + DEFAULT_CLIP_SECONDS).min(duration) } else { start + DEFAULT_CLIP_SECONDS };
+        let export_path = dirs::home_dir()
+            .unwrap_or_default()
+            .join("rustcast-clip.wav");
+        self.clip_dialog = Some(ClipDialog {
+            ep_link: link,
+            podcast_id: episode.podcast_id,
+            episode_title: episode.title.clone(),
+            start,
+            end,
+            export_path: export_path.display().to_string(),
+            previewing: false,
+        });
+    }
+
+    /// The clip's range under the timeline, while it's the current episode that's being cut
+    pub(crate) fn show_clip_selector(&mut self, ui: &mut egui::Ui) {
+        let current = self.podcasts_model.current_episode.as_ref().and_then(|episode| episode.link.as_ref());
+        let Some(dialog) = self.clip_dialog.as_mut().filter(|dialog| Some(&dialog.ep_link) == current) else {
+            return;
+        };
+        let duration = self.player_wrapper.inner_player.duration();
+        if duration > 0.0 {
+            range_selector(ui, &mut dialog.start, &mut dialog.end, duration);
+        }
+    }
+
+    /// Stops a preview at the end of the selection
+    pub(crate) fn advance_clip_preview(&mut self) {
+        let Some(dialog) = self.clip_dialog.as_mut().filter(|dialog| dialog.previewing) else {
+            return;
+        };
+        if !self.player_wrapper.player_state.is_active() {
+            dialog.previewing = false;
+        } else if self.player_wrapper.inner_player.current_position() >= dialog.end {
+            dialog.previewing = false;
+            self.player_wrapper.inner_player.pause();
+            self.player_wrapper.player_state = PlayerState::Paused;
+        }
+    }
+
+    pub(crate) fn show_clip_window(&mut self, ctx: &egui::Context) {
+        let is_current = self.clip_dialog.as_ref().is_some_and(|dialog| {
+            self.podcasts_model.current_episode.as_ref()
+                .is_some_and(|current| current.link.as_ref() == Some(&dialog.ep_link))
+        });
+        let now = self.player_wrapper.inner_player.current_position();
+        let progress = self.async_action_tx.progress(RequestKind::Clip);
+        let podcast = self.clip_dialog.as_ref().and_then(|dialog| {
+            self.podcasts_model.podcasts.iter().flatten().find(|podcast| podcast.id == dialog.podcast_id)
+        });
+        let podcast_title = podcast.and_then(|podcast| podcast.display_title()).map(str::to_string);
+        let gain_db = podcast.map_or(0, |podcast| podcast.gain_db) as f64;
+        let loudness_target = self.config.normalize_loudness.then_some(self.config.loudness_target);
+        let Some(dialog) = &mut self.clip_dialog else {
+            return;
+        };
+        let mut open = true;
+        let mut preview = false;
+        let mut export = None;
+
+        egui::Window::new("Export clip")
+            .collapsible(false)
+            .resizable(false)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.strong(dialog.episode_title.as_deref().unwrap_or("Unknown episode"));
+                egui::Grid::new("clip_range")
+                    .num_columns(3)
+                    .show(ui, |ui| {
+                        for (label, value) in [("Start", &mut dialog.start), ("End", &mut dialog.end)] {
+                            ui.label(label);
+                            ui.add(egui::DragValue::new(value)
+                                .speed(0.5)
+                                .clamp_range(0.0..=f64::MAX)
+                                .custom_formatter(|seconds, _| utils::format_position(seconds)));
+                            if ui.add_enabled(is_current, egui::Button::new("Now").small())
+                                .on_hover_text("Use the current position")
+                                .clicked()
+                            {
+                                *value = now;
+                            }
+                            ui.end_row();
+                        }
+                    });
+
+                let length = dialog.end - dialog.start;
+                let valid = length > 0.0 && length <= MAX_CLIP_SECONDS;
+                if valid {
+                    ui.weak(format!("{} long", utils::format_position(length)));
+                } else {
+                    ui.colored_label(ui.visuals().warn_fg_color,
+                        format!("Pick an end after the start, at most {} later.", utils::format_position(MAX_CLIP_SECONDS)));
+                }
+
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(is_current && valid, egui::Button::new("▶ Preview"))
+                        .on_disabled_hover_text("Play the episode to preview its clip")
+                        .clicked()
+                    {
+                        preview = true;
+                    }
+                });
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.label("Save as");
+                    ui.text_edit_singleline(&mut dialog.export_path);
+                });
+                ui.horizontal(|ui| {
+                    match progress {
+                        Some(progress) => {
+                            ui.spinner();
+                            ui.label(match progress {
+                                Progress::Downloading { received, total: Some(total) } if total > 0 =>
+                                    format!("Fetching the episode, {}%", received * 100 / total),
+                                Progress::Downloading { .. } => "Fetching the episode".to_string(),
+                                _ => "Cutting".to_string(),
+                            });
+                        }
+                        None => {
+                            if ui.add_enabled(valid, egui::Button::new("Export")).clicked() {
+                                export = Some(ClipRequest {
+                                    podcast_id: dialog.podcast_id,
+                                    ep_link: dialog.ep_link.clone(),
+                                    podcast_title: podcast_title.clone(),
+                                    episode_title: dialog.episode_title.clone(),
+                                    start: dialog.start,
+                                    end: dialog.end,
+                                    gain_db,
+                                    loudness_target,
+                                    file: PathBuf::from(dialog.export_path.trim()),
+                                });
+                            }
+                        }
+                    }
+                });
+                ui.weak(format!("Saved as WAV, tagged with the podcast, episode and {}.",
+                    format_range(dialog.start, Some(dialog.end))));
+            });
+
+        if preview {
+            dialog.previewing = true;
+            self.player_wrapper.inner_player.seek(dialog.start);
+            if !self.player_wrapper.player_state.is_active() {
+                self.player_wrapper.inner_player.play();
+                self.player_wrapper.player_state = PlayerState::Playing;
+            }
+        }
+        if let Some(request) = export {
+            self.async_action_tx.send_tracked(RequestKind::Clip, AsyncAction::ExportClip(request));
+        }
+        if !open {
+            self.clip_dialog = None;
+        }
+    }
+
+    pub(crate) fn clip_exported(&mut self, result: Result<(PathBuf, f64), String>) {
+        match result {
+            Ok((file, seconds)) => {
+                self.notice = Some(format!("Exported a {} clip to {}", utils::format_position(seconds), file.display()));
+            }
+            Err(e) => {
+                self.error = e;
+                self.show_error = true;
+            }
+        }
+    }
+}
+
+/// A strip under the timeline with a handle at each end of the range, lined up with the
+/// timeline's track
+fn range_selector(ui: &mut egui::Ui, start: &mut f64, end: &mut f64, duration: f64) {
+    let (rect, response) = ui.allocate_exact_size(
+        egui::vec2(ui.available_width(), SELECTOR_HEIGHT),
+        egui::Sense::click(),
+    );
+    let track = timeline_track(ui, rect);
+    let x_of = |seconds: f64| track.left() + (seconds / duration).clamp(0.0, 1.0) as f32 * track.width();
+    let seconds_at = |x: f32| ((x - track.left()) / track.width()).clamp(0.0, 1.0) as f64 * duration;
+
+    let handle = |seconds: f64| egui::Rect::from_center_size(
+        egui::pos2(x_of(seconds), rect.center().y),
+        egui::vec2(HANDLE_WIDTH, SELECTOR_HEIGHT),
+    );
+    let start_handle = ui.interact(handle(*start), response.id.with("start"), egui::Sense::drag());
+    let end_handle = ui.interact(handle(*end), response.id.with("end"), egui::Sense::drag());
+
+    if let Some(pointer) = start_handle.interact_pointer_pos().filter(|_| start_handle.dragged()) {
+        *start = seconds_at(pointer.x).min(*end);
+    } else if let Some(pointer) = end_handle.interact_pointer_pos().filter(|_| end_handle.dragged()) {
+        *end = seconds_at(pointer.x).max(*start);
+    } else if let Some(pointer) = response.interact_pointer_pos().filter(|_| response.clicked()) {
+        // A click moves whichever end is closer
+        let seconds = seconds_at(pointer.x);
+        if (seconds - *start).abs() <= (seconds - *end).abs() {
+            *start = seconds.min(*end);
+        } else {
+            *end = seconds.max(*start);
+        }
+    }
+
+    let painter = ui.painter_at(rect);
+    let visuals = ui.visuals();
+    painter.rect_filled(track, 2.0, visuals.extreme_bg_color);
+    let selection = egui::Rect::from_x_y_ranges(x_of(*start)..=x_of(*end), track.y_range());
+    painter.rect_filled(selection, 2.0, visuals.selection.bg_fill);
+    for (seconds, handle) in [(*start, &start_handle), (*end, &end_handle)] {
+        let color = if handle.hovered() || handle.dragged() {
+            visuals.selection.stroke.color
+        } else {
+            visuals.strong_text_color()
+        };
+        painter.rect_filled(handle_rect(x_of(seconds), track), 1.0, color);
+    }
+
+    response.on_hover_text(format!("Clip {}", format_range(*start, Some(*end))));
+}
+
+/// Part of `rect` the `Timeline`'s track takes up, between its time labels
+pub(super) fn timeline_track(ui: &egui::Ui, rect: egui::Rect) -> egui::Rect {
+    let label_width = ui.fonts(|fonts| {
+        fonts.layout_no_wrap(
+            "0:00:00.0".to_string(),
+            egui::FontId::proportional(TIMELINE_FONT_SIZE),
+            egui::Color32::WHITE,
+        ).rect.width()
+    });
+    egui::Rect::from_x_y_ranges(
+        rect.left() + label_width + 5.0..=rect.right() - label_width - 5.0,
+        rect.y_range(),
+    )
+}
+
+fn handle_rect(x: f32, track: egui::Rect) -> egui::Rect {
+    egui::Rect::from_center_size(egui::pos2(x, track.center().y), egui::vec2(2.0, track.height()))
+}
@@ -0,0 +1,104 @@
+use eframe::egui;
+use crate::credentials::{FeedCredentials, Passphrase};
+use crate::{AsyncAction, MyEguiApp};
+
+impl MyEguiApp {
+    pub(crate) fn open_credentials_window(&mut self, podcast_id: i32) {
+        let dialog = &mut self.podcasts_model.credentials_dialog;
+        dialog.podcast_id = Some(podcast_id);
+        dialog.username = String::new();
+        dialog.password = String::new();
+        dialog.headers = String::new();
+
+        if dialog.unlocked {
+            self.async_action_tx.send(AsyncAction::GetCredentials(podcast_id));
+        }
+        self.show_credentials = true;
+    }
+
+    /// URL to hand to the player, through the local stream relay when it's running
+    pub(crate) fn stream_url(&self, podcast_id: i32, link: &str) -> String {
+        match &self.stream_proxy {
+            Some(proxy) => proxy.stream_url(podcast_id, link),
+            None => link.to_string(),
+        }
+    }
+
+    pub(crate) fn show_credentials_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_credentials;
+
+        egui::Window::new("Private feed credentials")
+            .collapsible(false)
+            .resizable(true)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                let dialog = &mut self.podcasts_model.credentials_dialog;
+
+                if !dialog.unlocked {
+                    ui.label("Credentials are stored encrypted. Enter your passphrase to unlock them; \
+                              the first passphrase you enter creates the store.");
+                    let passphrase = ui.add(
+                        egui::TextEdit::singleline(&mut dialog.passphrase)
+                            .password(true)
+                            .hint_text("Passphrase"),
+                    );
+                    let submitted = passphrase.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+                        if dialog.busy {
+                            ui.spinner();
+                        } else if (ui.add(egui::Button::new("Unlock")).clicked() || submitted)
+                            && !dialog.passphrase.is_empty()
+                        {
+                            dialog.busy = true;
+                            self.async_action_tx.send(AsyncAction::UnlockCredentials(Passphrase(dialog.passphrase.clone())));
+                        }
+                    });
+                    return;
+                }
+
+                let Some(podcast_id) = dialog.podcast_id else {
+                    return;
+                };
+
+                ui.add(egui::TextEdit::singleline(&mut dialog.username).hint_text("Username"));
+                ui.add(
+                    egui::TextEdit::singleline(&mut dialog.password)
+                        .password(true)
+                        .hint_text("Password"),
+                );
+                ui.add(
+                    egui::TextEdit::multiline(&mut dialog.headers)
+                        .desired_rows(3)
+                        .hint_text("Extra headers, one \"Name: value\" per line"),
+                );
+
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+                    if dialog.busy {
+                        ui.spinner();
+                        return;
+                    }
+                    if ui.add(egui::Button::new("Save")).clicked() {
+                        dialog.busy = true;
+                        let feed_credentials = FeedCredentials {
+                            username: dialog.username.trim().to_string(),
+                            password: dialog.password.clone(),
+                            headers: FeedCredentials::parse_headers(&dialog.headers),
+                        };
+                        self.async_action_tx.send(AsyncAction::SaveCredentials(podcast_id, Some(feed_credentials)));
+                    }
+                    if ui.add(egui::Button::new("Remove")).clicked() {
+                        dialog.busy = true;
+                        self.async_action_tx.send(AsyncAction::SaveCredentials(podcast_id, None));
+                    }
+                });
+            });
+
+        self.show_credentials = open;
+        if !open {
+            let dialog = &mut self.podcasts_model.credentials_dialog;
+            dialog.password = String::new();
+            dialog.passphrase = String::new();
+        }
+    }
+}
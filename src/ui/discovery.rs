@@ -0,0 +1,107 @@
+use eframe::egui;
+use crate::{AsyncAction, MyEguiApp, RequestKind};
+
+const ARTWORK_SIZE: f32 = 64.0;
+
+impl MyEguiApp {
+    pub(crate) fn show_discovery_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_discovery;
+
+        egui::Window::new("Find podcasts")
+            .collapsible(false)
+            .resizable(true)
+            .default_width(420.0)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                let dialog = &mut self.podcasts_model.discovery_dialog;
+                let searching = self.async_action_tx.in_flight(RequestKind::Search);
+
+                ui.horizontal(|ui| {
+                    let query = ui.add(
+                        egui::TextEdit::singleline(&mut dialog.query)
+                            .hint_text("Search by keyword")
+                            .desired_width(280.0),
+                    );
+                    let submitted = query.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+
+                    // A new search replaces one that is still running
+                    if (ui.add(egui::Button::new("Search")).clicked() || submitted)
+                        && !dialog.query.trim().is_empty()
+                    {
+                        self.async_action_tx
+                            .send_tracked(RequestKind::Search, AsyncAction::SearchDirectory(dialog.query.trim().to_string()));
+                    }
+                    if searching {
+                        ui.spinner();
+                    }
+                });
+
+                ui.separator();
+
+                egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                    for result in &dialog.results {
+                        let already_subscribed = dialog.subscribed.contains(&result.feed_url)
+                            || self.podcasts_model.podcasts.iter().flatten().any(|p| {
+                                p.link.as_deref()
+                                    .map(|link| crate::utils::same_feed_url(link, &result.feed_url))
+                                    .unwrap_or(false)
+                            });
+
+                        ui.horizontal(|ui| {
+                            match result.artwork_url.as_ref().filter(|_| self.config.show_artwork) {
+                                Some(artwork_url) => {
+                                    ui.add(egui::Image::new(artwork_url.as_str())
+                                        .fit_to_exact_size(egui::vec2(ARTWORK_SIZE, ARTWORK_SIZE))
+                                        .rounding(4.0));
+                                }
+                                None => {
+                                    ui.add_space(ARTWORK_SIZE);
+                                }
+                            }
+
+                            ui.vertical(|ui| {
+                                ui.strong(&result.title);
+                                if !result.author.is_empty() {
+                                    ui.weak(&result.author);
+                                }
+                                if !result.description.is_empty() {
+                                    let description: String = result.description.chars().take(240).collect();
+                                    ui.label(description);
+                                }
+
+                                let subscribing = dialog.subscribing.values().any(|url| *url == result.feed_url);
+                                if already_subscribed {
+                                    ui.add_enabled(false, egui::Button::new("Subscribed"));
+                                } else if subscribing {
+                                    ui.horizontal(|ui| {
+                                        ui.add_enabled(false, egui::Button::new("Subscribing…"));
+                                        ui.spinner();
+                                    });
+                                } else if ui.add(egui::Button::new("Subscribe")).clicked() {
+                                    let id = self.async_action_tx
+                                        .send_tracked(RequestKind::AddPodcast, AsyncAction::AddPodcast(
+                                            result.title.clone(),
+                                            result.feed_url.clone(),
+                                            result.description.chars().take(2000).collect(),
+                                            None,
+                                        ));
+
+                                    dialog.subscribing.insert(id, result.feed_url.clone());
+                                }
+                            });
+                        });
+                        ui.separator();
+                    }
+
+                    if dialog.results.is_empty() && !searching && !dialog.query.is_empty() {
+                        ui.weak("No results yet. Press Search to query the directories.");
+                    }
+                });
+            });
+
+        if !open {
+            self.podcasts_model.discovery_dialog = Default::default();
+        }
+        self.show_discovery = open;
+    }
+}
@@ -0,0 +1,193 @@
+use eframe::egui;
+use log::info;
+use crate::enclosure::{self, EnclosurePreference};
+use crate::entity::episode;
+use crate::show_notes::{Block, Inline};
+use crate::{utils, AsyncAction, MyEguiApp, PlayerState, RequestKind};
+use super::bookmarks::{bookmark_rows, BookmarkAction};
+use super::thumbnail;
+
+const DETAILS_ARTWORK_SIZE: f32 = 96.0;
+
+impl MyEguiApp {
+    /// Side pane with the selected episode and its show notes
+    pub(crate) fn show_episode_details(&mut self, ctx: &egui::Context) {
+        let artwork_url = self.podcasts_model.episode_details.as_ref()
+            .and_then(|details| self.episode_artwork_url(&details.episode));
+        self.request_artwork(artwork_url.as_deref());
+        let artwork = self.artwork(artwork_url.as_deref());
+        let Some(details) = &self.podcasts_model.episode_details else {
+            return;
+        };
+        let bookmarks = &self.podcasts_model.bookmarks;
+        let preference = self.podcasts_model.podcasts.iter().flatten()
+            .find(|podcast| podcast.id == details.episode.podcast_id)
+            .map(EnclosurePreference::of)
+            .unwrap_or_default();
+        let mut close = false;
+        let mut seek = None;
+        let mut bookmark_action: Option<BookmarkAction> = None;
+
+        egui::SidePanel::right("episode_details")
+            .resizable(true)
+            .default_width(320.0)
+            .width_range(220.0..=600.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+                        if ui.small_button("✖").on_hover_text("Close").clicked() {
+                            close = true;
+                        }
+                        ui.with_layout(egui::Layout::left_to_right(egui::Align::TOP).with_main_wrap(true), |ui| {
+                            ui.strong(details.episode.title.as_deref().unwrap_or("Unknown Episode"));
+                        });
+                    });
+                });
+
+                if let Some(artwork) = artwork {
+                    thumbnail(ui, artwork, DETAILS_ARTWORK_SIZE);
+                }
+                let episode = &details.episode;
+                let mut facts = Vec::new();
+                if let Some(published) = episode.pub_date.as_deref().filter(|date| *date != "Unknown") {
+                    facts.push(published.to_string());
+                }
+                if let Some(duration) = episode.duration {
+                    facts.push(utils::format_position(duration as f64));
+                }
+                if !facts.is_empty() {
+                    ui.weak(facts.join(" · "));
+                }
+                show_versions(ui, episode, &preference);
+                let link = details.episode.link.as_ref();
+                let mut episode_bookmarks = bookmarks.iter()
+                    .filter(|bookmark| Some(&bookmark.ep_link) == link)
+                    .peekable();
+                if episode_bookmarks.peek().is_some() {
+                    ui.separator();
+                    ui.label("Bookmarks");
+                    bookmark_rows(ui, episode_bookmarks, &mut bookmark_action);
+                }
+                ui.separator();
+
+                egui::ScrollArea::vertical()
+                    .auto_shrink([false, false])
+                    .show(ui, |ui| {
+                        if details.notes.is_empty() {
+                            ui.weak("This episode has no show notes.");
+                        }
+                        for block in &details.notes {
+                            show_block(ui, block, &mut seek);
+                        }
+                    });
+            });
+
+        if close {
+            self.podcasts_model.episode_details = None;
+        } else if let Some(seconds) = seek {
+            let episode = details.episode.clone();
+            self.play_from(episode, seconds);
+        } else if let Some(action) = bookmark_action {
+            self.apply_bookmark_action(action);
+        }
+    }
+
+    /// Plays an episode from the given position, opening it first unless it's the current one
+    pub(crate) fn play_from(&mut self, episode: episode::Model, seconds: f64) {
+        let is_current = self.podcasts_model.current_episode.as_ref()
+            .is_some_and(|current| current.link == episode.link);
+        if is_current && !self.async_action_tx.in_flight(RequestKind::PlaybackPosition) {
+            self.player_wrapper.inner_player.seek(seconds);
+            if self.player_wrapper.player_state.is_stopped() {
+                self.player_wrapper.inner_player.play();
+                self.player_wrapper.player_state = PlayerState::Playing;
+            }
+            return;
+        }
+
+        let Some(link) = episode.link.clone() else {
+            return;
+        };
+        self.save_playback_position();
+        info!("Playing '{}' from {:.1}s", episode.title.as_deref().unwrap_or("Unknown"), seconds);
+        self.podcasts_model.start_at = Some((link.clone(), seconds));
+        self.podcasts_model.current_episode = Some(episode);
+        self.async_action_tx.send_tracked(RequestKind::PlaybackPosition, AsyncAction::LoadEpisodeState(link));
+    }
+}
+
+/// The versions an episode comes in, marking the one that plays, and a way to watch its video
+fn show_versions(ui: &mut egui::Ui, episode: &episode::Model, preference: &EnclosurePreference) {
+    let versions = enclosure::of_episode(episode);
+    let video = versions.iter().find(|version| version.is_video());
+    if versions.len() < 2 && video.is_none() {
+        return;
+    }
+    let played = preference.choose(&versions).map(|version| version.url.as_str());
+
+    if versions.len() > 1 {
+        ui.horizontal_wrapped(|ui| {
+            ui.weak("Versions:");
+            for version in &versions {
+                let label = if version.is_video() { format!("🎬 {}", version.describe()) } else { version.describe() };
+                if Some(version.url.as_str()) == played {
+                    ui.label(label).on_hover_text("This version plays");
+                } else {
+                    ui.weak(label);
+                }
+            }
+        });
+    }
+    if let Some(video) = video {
+        ui.horizontal_wrapped(|ui| {
+            ui.weak("Video plays as audio here.");
+            if ui.small_button("Open in external player").on_hover_text(&video.url).clicked() {
+                ui.ctx().open_url(egui::OpenUrl::new_tab(&video.url));
+            }
+        });
+    }
+}
+
+fn show_block(ui: &mut egui::Ui, block: &Block, seek: &mut Option<f64>) {
+    match block {
+        Block::Paragraph(inlines) => show_inlines(ui, None, inlines, seek),
+        Block::Heading(inlines) => {
+            ui.add_space(4.0);
+            show_inlines(ui, None, inlines, seek);
+        }
+        Block::ListItem(marker, inlines) => show_inlines(ui, Some(marker), inlines, seek),
+    }
+    ui.add_space(6.0);
+}
+
+fn show_inlines(ui: &mut egui::Ui, marker: Option<&str>, inlines: &[Inline], seek: &mut Option<f64>) {
+    ui.horizontal_wrapped(|ui| {
+        ui.spacing_mut().item_spacing.x = 0.0;
+        if let Some(marker) = marker {
+            ui.label(format!("{} ", marker));
+        }
+        for inline in inlines {
+            match inline {
+                Inline::Text { text, bold, italic } => {
+                    let mut text = egui::RichText::new(text);
+                    if *bold {
+                        text = text.strong();
+                    }
+                    if *italic {
+                        text = text.italics();
+                    }
+                    ui.label(text);
+                }
+                Inline::Link { text, url } => {
+                    ui.hyperlink_to(text, url).on_hover_text(url);
+                }
+                Inline::Timestamp { text, seconds } => {
+                    if ui.link(text).on_hover_text("Play from here").clicked() {
+                        *seek = Some(*seconds);
+                    }
+                }
+                Inline::LineBreak => ui.end_row(),
+            }
+        }
+    });
+}
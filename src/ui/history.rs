@@ -0,0 +1,262 @@
+use std::path::PathBuf;
+use eframe::egui;
+use egui_extras::{Column, TableBuilder};
+use log::warn;
+use crate::history::ListeningStats;
+use crate::{utils, AsyncAction, MyEguiApp, PlayerState, RequestKind};
+
+const CHART_HEIGHT: f32 = 100.0;
+
+/// The statistics window; `stats` is `None` until they've been loaded
+pub(crate) struct StatsDialog {
+    stats: Option<ListeningStats>,
+    export_path: String,
+    exporting: bool,
+}
+
+impl MyEguiApp {
+    /// Hands what the player is doing to the session tracker and logs every session that ends
+    pub(crate) fn track_listening(&mut self) {
+        let playing = self.player_wrapper.player_state == PlayerState::Playing
+            // Until the episode is opened the player still reports the previous one
+            && !self.async_action_tx.in_flight(RequestKind::PlaybackPosition);
+        let episode = self.podcasts_model.current_episode.as_ref().filter(|_| playing);
+        let position = self.player_wrapper.inner_player.current_position();
+        let speed = self.player_wrapper.speed;
+
+        if let Some(session) = self.player_wrapper.sessions.observe(episode, position, speed) {
+            self.async_action_tx.send(AsyncAction::LogSession(session));
+        }
+    }
+
+    /// Logs the session in progress, for when the app closes
+    pub(crate) fn end_listening_session(&mut self) {
+        if let Some(session) = self.player_wrapper.sessions.close() {
+            self.async_action_tx.send(AsyncAction::LogSession(session));
+        }
+    }
+
+    pub(crate) fn open_stats_window(&mut self) {
+        let export_path = dirs::home_dir()
+            .unwrap_or_default()
+            .join("rustcast-history.csv");
+        self.stats_dialog = Some(StatsDialog {
+            stats: None,
+            export_path: export_path.display().to_string(),
+            exporting: false,
+        });
+        self.async_action_tx.send(AsyncAction::GetListeningStats);
+    }
+
+    pub(crate) fn show_stats_window(&mut self, ctx: &egui::Context) {
+        let Some(dialog) = &mut self.stats_dialog else {
+            return;
+        };
+        let mut open = true;
+
+        egui::Window::new("Listening statistics")
+            .collapsible(false)
+            .resizable(true)
+            .default_width(520.0)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                match &dialog.stats {
+                    Some(stats) => show_stats(ui, stats),
+                    None => {
+                        ui.spinner();
+                    }
+                }
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.label("Export to");
+                    ui.text_edit_singleline(&mut dialog.export_path);
+                    if dialog.exporting {
+                        ui.spinner();
+                    } else if ui.add(egui::Button::new("Export CSV")).clicked() {
+                        dialog.exporting = true;
+                        self.async_action_tx.send(AsyncAction::ExportHistory(PathBuf::from(dialog.export_path.trim())));
+                    }
+                });
+            });
+
+        if !open {
+            self.stats_dialog = None;
+        }
+    }
+
+    pub(crate) fn session_logged(&mut self, result: Result<(), String>) {
+        // Losing a session isn't worth interrupting playback for
+        if let Err(e) = result {
+            warn!("Listening session was not logged: {}", e);
+        }
+    }
+
+    pub(crate) fn listening_stats_updated(&mut self, result: Result<ListeningStats, String>) {
+        match result {
+            Ok(stats) => {
+                if let Some(dialog) = &mut self.stats_dialog {
+                    dialog.stats = Some(stats);
+                }
+            }
+            Err(e) => {
+                self.stats_dialog = None;
+                self.error = e;
+                self.show_error = true;
+            }
+        }
+    }
+
+    pub(crate) fn history_exported(&mut self, result: Result<PathBuf, String>) {
+        if let Some(dialog) = &mut self.stats_dialog {
+            dialog.exporting = false;
+        }
+        match result {
+            Ok(file) => self.notice = Some(format!("Listening history exported to {}", file.display())),
+            Err(e) => {
+                self.error = e;
+                self.show_error = true;
+            }
+        }
+    }
+}
+
+fn show_stats(ui: &mut egui::Ui, stats: &ListeningStats) {
+    egui::Grid::new("stats_totals")
+        .num_columns(2)
+        .spacing([12.0, 4.0])
+        .show(ui, |ui| {
+            ui.label("Time listened");
+            ui.strong(format_listened(stats.total_seconds));
+            ui.end_row();
+
+            ui.label("Saved by speed-up");
+            ui.strong(format_listened(stats.saved_seconds - stats.trimmed_seconds));
+            ui.end_row();
+
+            ui.label("Saved by trimming silence");
+            ui.strong(format_listened(stats.trimmed_seconds));
+            ui.end_row();
+        });
+
+    ui.separator();
+    ui.label(format!("Last {} days", stats.daily.len()));
+    daily_chart(ui, stats);
+
+    ui.separator();
+    egui::CollapsingHeader::new("By podcast")
+        .default_open(true)
+        .show(ui, |ui| {
+            egui::Grid::new("stats_per_podcast")
+                .num_columns(2)
+                .striped(true)
+                .show(ui, |ui| {
+                    for (podcast, seconds) in &stats.per_podcast {
+                        ui.label(podcast);
+                        ui.label(format_listened(*seconds));
+                        ui.end_row();
+                    }
+                });
+        });
+
+    if !stats.trimmed_per_episode.is_empty() {
+        egui::CollapsingHeader::new("Silence trimmed by episode")
+            .show(ui, |ui| {
+                egui::Grid::new("stats_trimmed")
+                    .num_columns(2)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for (episode, seconds) in &stats.trimmed_per_episode {
+                            ui.label(episode);
+                            ui.label(utils::format_position(*seconds));
+                            ui.end_row();
+                        }
+                    });
+            });
+    }
+
+    egui::CollapsingHeader::new("Recent sessions")
+        .show(ui, |ui| {
+            TableBuilder::new(ui)
+                .striped(true)
+                .max_scroll_height(240.0)
+                .column(Column::auto())
+                .column(Column::auto().clip(true))
+                .column(Column::remainder().clip(true))
+                .column(Column::auto())
+                .header(20.0, |mut header| {
+                    for title in ["When", "Podcast", "Episode", "Listened"] {
+                        header.col(|ui| {
+                            ui.strong(title);
+                        });
+                    }
+                })
+                .body(|body| {
+                    body.rows(18.0, stats.recent.len(), |mut row| {
+                        let entry = &stats.recent[row.index()];
+                        row.col(|ui| {
+                            ui.label(utils::format_timestamp(entry.started_at));
+                        });
+                        row.col(|ui| {
+                            ui.label(&entry.podcast);
+                        });
+                        row.col(|ui| {
+                            ui.label(&entry.episode);
+                        });
+                        row.col(|ui| {
+                            ui.label(format_listened(entry.listened_seconds));
+                        });
+                    });
+                });
+        });
+}
+
+/// One bar per day, scaled to the busiest day
+fn daily_chart(ui: &mut egui::Ui, stats: &ListeningStats) {
+    let width = ui.available_width().max(200.0);
+    let (rect, response) = ui.allocate_exact_size(egui::vec2(width, CHART_HEIGHT), egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    let visuals = ui.visuals();
+    painter.rect_filled(rect, 2.0, visuals.extreme_bg_color);
+
+    let busiest = stats.daily.iter().map(|(_, seconds)| *seconds).fold(0.0, f64::max);
+    if stats.daily.is_empty() || busiest <= 0.0 {
+        painter.text(rect.center(), egui::Align2::CENTER_CENTER, "Nothing listened yet",
+            egui::FontId::default(), visuals.weak_text_color());
+        return;
+    }
+
+    let slot = rect.width() / stats.daily.len() as f32;
+    let hovered = response.hover_pos()
+        .map(|pos| ((pos.x - rect.left()) / slot) as usize)
+        .filter(|index| *index < stats.daily.len());
+
+    for (index, (_, seconds)) in stats.daily.iter().enumerate() {
+        let height = (*seconds / busiest) as f32 * (rect.height() - 4.0);
+        let left = rect.left() + index as f32 * slot + slot * 0.15;
+        let bar = egui::Rect::from_min_max(
+            egui::pos2(left, rect.bottom() - height),
+            egui::pos2(left + slot * 0.7, rect.bottom()),
+        );
+        let color = if hovered == Some(index) {
+            visuals.selection.stroke.color
+        } else {
+            visuals.selection.bg_fill
+        };
+        painter.rect_filled(bar, 1.0, color);
+    }
+
+    if let Some((day, seconds)) = hovered.map(|index| &stats.daily[index]) {
+        response.on_hover_text(format!("{}: {}", day.format("%a %Y-%m-%d"), format_listened(*seconds)));
+    }
+}
+
+/// Listening time rounded to minutes
+fn format_listened(seconds: f64) -> String {
+    let minutes = (seconds / 60.0).round() as i64;
+    if minutes >= 60 {
+        format!("{} h {} min", minutes / 60, minutes % 60)
+    } else {
+        format!("{} min", minutes)
+    }
+}
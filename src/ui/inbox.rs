@@ -0,0 +1,212 @@
+use eframe::egui;
+use egui_extras::{Column, TableBuilder};
+use crate::entity::episode;
+use crate::{utils, AsyncAction, MyEguiApp, RequestKind};
+
+/// What can be done with new episodes, one at a time or all selected ones at once. Each of
+/// them takes the episodes out of the inbox.
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Triage {
+    Enqueue,
+    Download,
+    MarkPlayed,
+    Archive,
+}
+
+impl Triage {
+    const ALL: [Triage; 4] = [Triage::Enqueue, Triage::Download, Triage::MarkPlayed, Triage::Archive];
+
+    fn icon(&self) -> &'static str {
+        match self {
+            Triage::Enqueue => "➕",
+            Triage::Download => "⬇",
+            Triage::MarkPlayed => "✔",
+            Triage::Archive => "🗄",
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Triage::Enqueue => "Add to queue",
+            Triage::Download => "Download",
+            Triage::MarkPlayed => "Mark played",
+            Triage::Archive => "Archive",
+        }
+    }
+}
+
+impl MyEguiApp {
+    pub(crate) fn open_inbox(&mut self) {
+        self.podcasts_model.inbox_open = true;
+        self.async_action_tx.send(AsyncAction::GetInbox);
+    }
+
+    pub(crate) fn show_inbox(&mut self, ui: &mut egui::Ui) {
+        let model = &mut self.podcasts_model;
+        let mut triage = None;
+
+        ui.horizontal(|ui| {
+            ui.heading(format!("Inbox ({})", model.inbox.len()));
+            ui.separator();
+            if ui.small_button("Select all").clicked() {
+                model.inbox_selected = model.inbox.iter().filter_map(|e| e.link.clone()).collect();
+            }
+            if ui.small_button("Select none").clicked() {
+                model.inbox_selected.clear();
+            }
+            ui.add_enabled_ui(!model.inbox_selected.is_empty(), |ui| {
+                for action in Triage::ALL {
+                    if ui.button(format!("{} {}", action.icon(), action.label())).clicked() {
+                        let selected = model.inbox.iter()
+                            .filter(|e| e.link.as_ref().is_some_and(|link| model.inbox_selected.contains(link)))
+                            .cloned()
+                            .collect();
+                        triage = Some((action, selected));
+                    }
+                }
+            });
+        });
+
+        if model.inbox.is_empty() {
+            ui.weak("Nothing new. New episodes show up here after feeds are refreshed.");
+            return;
+        }
+
+        let text_height = egui::TextStyle::Body
+            .resolve(ui.style())
+            .size
+            .max(ui.spacing().interact_size.y);
+        let podcast_title = |podcast_id: i32| model.podcasts.iter().flatten()
+            .find(|p| p.id == podcast_id)
+            .and_then(|p| p.display_title())
+            .unwrap_or("Unknown podcast")
+            .to_string();
+
+        TableBuilder::new(ui)
+            .striped(true)
+            .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+            .column(Column::auto())
+            .column(Column::auto())
+            .column(Column::auto())
+            .column(Column::auto())
+            .column(Column::remainder())
+            .min_scrolled_height(0.0)
+            .header(20.0, |mut header| {
+                header.col(|_| {});
+                header.col(|ui| {
+                    ui.strong("Action");
+                });
+                header.col(|ui| {
+                    ui.strong("Seen");
+                });
+                header.col(|ui| {
+                    ui.strong("Podcast");
+                });
+                header.col(|ui| {
+                    ui.strong("Title");
+                });
+            })
+            .body(|body| {
+                body.rows(text_height + 5.0, model.inbox.len(), |mut row| {
+                    let episode = &model.inbox[row.index()];
+                    let link = episode.link.clone().unwrap_or_default();
+                    row.col(|ui| {
+                        let mut selected = model.inbox_selected.contains(&link);
+                        if ui.checkbox(&mut selected, "").changed() {
+                            if selected {
+                                model.inbox_selected.insert(link.clone());
+                            } else {
+                                model.inbox_selected.remove(&link);
+                            }
+                        }
+                    });
+                    row.col(|ui| {
+                        for action in Triage::ALL {
+                            if action == Triage::Download && model.downloading.contains(&link) {
+                                ui.spinner();
+                            } else if ui.small_button(action.icon()).on_hover_text(action.label()).clicked() {
+                                triage = Some((action, vec![episode.clone()]));
+                            }
+                        }
+                    });
+                    row.col(|ui| {
+                        ui.label(episode.first_seen.map(utils::format_timestamp).unwrap_or_default());
+                    });
+                    row.col(|ui| {
+                        ui.label(podcast_title(episode.podcast_id));
+                    });
+                    row.col(|ui| {
+                        ui.label(episode.title.as_deref().unwrap_or("Unknown Episode"));
+                    });
+                });
+            });
+
+        if let Some((action, episodes)) = triage {
+            self.triage(action, episodes);
+        }
+    }
+
+    fn triage(&mut self, action: Triage, episodes: Vec<episode::Model>) {
+        let links: Vec<String> = episodes.iter().filter_map(|e| e.link.clone()).collect();
+        // Gone from the list right away, the worker answers with the inbox as it is now
+        self.podcasts_model.inbox.retain(|e| e.link.as_ref().is_none_or(|link| !links.contains(link)));
+        for link in &links {
+            self.podcasts_model.inbox_selected.remove(link);
+        }
+
+        match action {
+            Triage::Enqueue => {
+                self.enqueue(episodes);
+                self.async_action_tx.send(AsyncAction::ArchiveEpisodes(links));
+            }
+            Triage::Download => {
+                for episode in &episodes {
+                    if let Some(link) = &episode.link {
+                        self.podcasts_model.downloading.insert(link.clone());
+                        self.async_action_tx.send_tracked(
+                            RequestKind::Download,
+                            AsyncAction::DownloadEpisode(episode.podcast_id, link.clone()),
+                        );
+                    }
+                }
+                self.async_action_tx.send(AsyncAction::ArchiveEpisodes(links));
+            }
+            Triage::MarkPlayed => {
+                let played = episodes.into_iter()
+                    .filter_map(|e| Some((e.podcast_id, e.link?)))
+                    .collect();
+                self.async_action_tx.send(AsyncAction::MarkPlayed(played));
+            }
+            Triage::Archive => {
+                self.async_action_tx.send(AsyncAction::ArchiveEpisodes(links));
+            }
+        }
+    }
+
+    pub(crate) fn inbox_updated(&mut self, result: Result<Vec<episode::Model>, String>) {
+        match result {
+            Ok(inbox) => {
+                let model = &mut self.podcasts_model;
+                model.inbox_selected.retain(|link| inbox.iter().any(|e| e.link.as_ref() == Some(link)));
+                model.inbox = inbox;
+            }
+            Err(e) => {
+                self.error = e;
+                self.show_error = true;
+            }
+        }
+    }
+
+    pub(crate) fn episode_downloaded(&mut self, link: String, result: Result<(), String>) {
+        self.podcasts_model.downloading.remove(&link);
+        match result {
+            Ok(()) => {
+                self.async_action_tx.send(AsyncAction::GetGroups);
+            }
+            Err(e) => {
+                self.error = e;
+                self.show_error = true;
+            }
+        }
+    }
+}
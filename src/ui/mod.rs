@@ -0,0 +1,21 @@
+mod artwork;
+mod bookmarks;
+mod clip;
+mod credentials;
+mod discovery;
+mod episode_details;
+mod history;
+mod inbox;
+mod playback;
+mod playlists;
+mod podcast_list;
+mod recovery;
+mod settings;
+mod silence;
+mod subscriptions;
+
+pub(crate) use artwork::thumbnail;
+pub(crate) use clip::ClipDialog;
+pub(crate) use history::StatsDialog;
+pub(crate) use playback::PlaybackFailure;
+pub(crate) use settings::{theme_visuals, SettingsDialog};
@@ -0,0 +1,325 @@
+use eframe::egui;
+use log::{error, info, warn};
+use crate::enclosure::{self, EnclosurePreference};
+use crate::entity::episode;
+use crate::error::{PlayerError, RustcastError};
+use crate::stream_proxy::StreamHealth;
+use crate::playback::SavedPosition;
+use crate::{downloads, player, AsyncAction, MyEguiApp, PlayerState, RequestKind};
+use super::clip::timeline_track;
+
+/// Times a stalled stream is opened again before playback counts as failed
+const MAX_RECONNECTS: u32 = 2;
+const BUFFERED_HEIGHT: f32 = 2.0;
+
+/// An episode that stopped playing and where, kept so it can be tried again
+pub(crate) struct PlaybackFailure {
+    episode: episode::Model,
+    position: f64,
+    message: String,
+    /// Trying again can help; it can't when the format isn't supported
+    retryable: bool,
+}
+
+impl MyEguiApp {
+    /// Opens the version of the current episode the podcast prefers at `position` and starts
+    /// playing, unless its type already says it won't play
+    pub(crate) fn open_current_episode(&mut self, position: f64) {
+        let Some(episode) = &self.podcasts_model.current_episode else {
+            error!("No current episode selected");
+            self.error = "No episode selected for playback.".to_string();
+            self.show_error = true;
+            return;
+        };
+        let Some(link) = episode.link.clone() else {
+            error!("Episode link is missing");
+            self.error = "Episode link is missing or invalid.".to_string();
+            self.show_error = true;
+            return;
+        };
+        let podcast_id = episode.podcast_id;
+        let preference = self.podcasts_model.podcasts.iter().flatten()
+            .find(|podcast| podcast.id == podcast_id)
+            .map(EnclosurePreference::of)
+            .unwrap_or_default();
+        let enclosures = enclosure::of_episode(episode);
+        let chosen = preference.choose(&enclosures);
+        let url = chosen.map_or_else(|| link.clone(), |chosen| chosen.url.clone());
+        let mime_type = chosen.and_then(|chosen| chosen.mime_type.clone());
+        if url != link {
+            info!("Playing {} of '{}'", chosen.map(enclosure::Enclosure::describe).unwrap_or_default(),
+                episode.title.as_deref().unwrap_or("Unknown"));
+        }
+        self.playback_failure = None;
+        self.player_wrapper.playing_download = false;
+        self.player_wrapper.reconnects = 0;
+
+        if let Err(e) = player::check_playable(mime_type.as_deref()) {
+            warn!("Not opening {}: {}", url, e);
+            self.player_wrapper.stream_url = None;
+            self.playback_failed(e, position);
+            return;
+        }
+
+        // Every episode starts at the default speed, where the player can change it
+        let speed = if self.player_wrapper.inner_player.supports_speed() {
+            f64::from(self.config.default_speed)
+        } else {
+            1.0
+        };
+        self.player_wrapper.inner_player.set_speed(speed);
+        self.player_wrapper.speed = speed;
+        self.apply_loudness();
+
+        let stream_url = self.stream_url(podcast_id, &url);
+        self.start_stream(stream_url, position);
+        self.async_action_tx.send_tracked(RequestKind::Probe, AsyncAction::ProbeEnclosure(podcast_id, link.clone(), url));
+        self.detect_silences(podcast_id, link);
+    }
+
+    /// Hands the player the current podcast's volume offset and the loudness to normalize to
+    pub(crate) fn apply_loudness(&mut self) {
+        let podcast_id = self.podcasts_model.current_episode.as_ref().map(|episode| episode.podcast_id);
+        let gain_db = self.podcasts_model.podcasts.iter().flatten()
+            .find(|podcast| Some(podcast.id) == podcast_id)
+            .map_or(0, |podcast| podcast.gain_db);
+        let target = self.config.normalize_loudness.then_some(self.config.loudness_target);
+        self.player_wrapper.inner_player.set_loudness(gain_db as f64, target);
+    }
+
+    fn start_stream(&mut self, url: String, position: f64) {
+        self.player_wrapper.player_state = self.player_wrapper.control.open(
+            self.player_wrapper.inner_player.as_mut(),
+            &url,
+            position,
+            std::time::Instant::now(),
+        );
+        self.player_wrapper.stream_url = Some(url);
+    }
+
+    /// Stops on a failure of the player. A stalled stream is opened again at the same
+    /// position a couple of times, then a failed stream falls back to the episode's downloaded
+    /// copy if there is one; otherwise the failure is shown with a way to retry.
+    pub(crate) fn playback_failed(&mut self, e: PlayerError, position: f64) {
+        let message = RustcastError::Player(e.clone()).user_friendly_message();
+        if matches!(e, PlayerError::SeekFailed(_)) {
+            // Playback carries on from where the player ended up
+            self.error = message;
+            self.show_error = true;
+            return;
+        }
+
+        self.player_wrapper.inner_player.pause();
+        self.player_wrapper.player_state = PlayerState::Error;
+        let Some(episode) = self.podcasts_model.current_episode.clone() else {
+            return;
+        };
+        let retryable = !matches!(e, PlayerError::UnsupportedFormat(_));
+
+        let stalled = matches!(e, PlayerError::PlaybackFailed(_));
+        if stalled && !self.player_wrapper.playing_download && self.player_wrapper.reconnects < MAX_RECONNECTS {
+            if let Some(url) = self.player_wrapper.stream_url.clone() {
+                self.player_wrapper.reconnects += 1;
+                info!("Reconnecting to the stream of '{}' at {:.1}s", episode.title.as_deref().unwrap_or("Unknown"), position);
+                self.start_stream(url, position);
+                return;
+            }
+        }
+
+        if retryable && !self.player_wrapper.playing_download {
+            if let Some(url) = self.downloaded_copy_url(&episode) {
+                info!("Streaming failed, playing the downloaded copy of '{}'", episode.title.as_deref().unwrap_or("Unknown"));
+                self.player_wrapper.playing_download = true;
+                self.start_stream(url, position);
+                self.notice = Some(format!("{} Playing the downloaded copy instead.", message));
+                return;
+            }
+        }
+
+        self.playback_failure = Some(PlaybackFailure {
+            episode,
+            position,
+            message,
+            retryable,
+        });
+    }
+
+    /// Relay URL of the episode's downloaded file, if it has been downloaded
+    fn downloaded_copy_url(&self, episode: &episode::Model) -> Option<String> {
+        let file = downloads::episode_file(&self.config.download_dir, episode.podcast_id, episode.link.as_deref()?);
+        if !file.exists() {
+            return None;
+        }
+        self.stream_proxy.as_ref()?.file_url(&file)
+    }
+
+    pub(crate) fn enclosure_probed(&mut self, link: String, result: Result<(), String>) {
+        let Err(message) = result else {
+            return;
+        };
+        let is_current = self.podcasts_model.current_episode.as_ref()
+            .is_some_and(|episode| episode.link.as_ref() == Some(&link));
+        if !is_current || self.player_wrapper.playing_download {
+            return;
+        }
+        let position = self.player_wrapper.inner_player.current_position();
+        self.playback_failed(PlayerError::OpenFailed(message), position);
+    }
+
+    pub(crate) fn show_playback_failure_window(&mut self, ctx: &egui::Context) {
+        let Some(failure) = &self.playback_failure else {
+            return;
+        };
+        let mut open = true;
+        let mut retry = false;
+
+        egui::Window::new("Playback failed")
+            .collapsible(false)
+            .resizable(false)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.strong(failure.episode.title.as_deref().unwrap_or("Unknown episode"));
+                ui.label(&failure.message);
+                if failure.retryable {
+                    ui.horizontal(|ui| {
+                        retry = ui.button("Retry").clicked();
+                        ui.weak("Downloading the episode lets it play without a connection.");
+                    });
+                }
+            });
+
+        if retry {
+            self.retry_playback();
+        } else if !open {
+            self.playback_failure = None;
+        }
+    }
+
+    /// Opens the failed episode again where it stopped
+    fn retry_playback(&mut self) {
+        let failed = match self.playback_failure.take() {
+            Some(failure) => Some((failure.episode, failure.position)),
+            None => self.podcasts_model.current_episode.clone()
+                .map(|episode| (episode, self.player_wrapper.inner_player.current_position())),
+        };
+        let Some((episode, position)) = failed else {
+            return;
+        };
+        let Some(link) = episode.link.clone() else {
+            return;
+        };
+        info!("Retrying '{}' from {:.1}s", episode.title.as_deref().unwrap_or("Unknown"), position);
+        self.podcasts_model.start_at = Some((link.clone(), position));
+        self.podcasts_model.current_episode = Some(episode);
+        self.async_action_tx.send_tracked(RequestKind::PlaybackPosition, AsyncAction::LoadEpisodeState(link));
+    }
+
+    /// Plays the current episode again: from the start once it has ended, reopened after a
+    /// failure
+    pub(crate) fn resume_playback(&mut self) {
+        match self.player_wrapper.player_state.clone() {
+            PlayerState::Error => self.retry_playback(),
+            state => {
+                self.player_wrapper.player_state = self.player_wrapper.control.resume(self.player_wrapper.inner_player.as_mut(), &state);
+            }
+        }
+    }
+
+    /// Pauses the current episode and saves where it was left
+    pub(crate) fn pause_playback(&mut self) {
+        let saved = self.player_wrapper.control.pause(
+            self.player_wrapper.inner_player.as_mut(),
+            self.podcasts_model.current_episode.as_ref(),
+            std::time::Instant::now(),
+        );
+        self.player_wrapper.player_state = PlayerState::Paused;
+        match saved {
+            Some(saved) => self.store_position(saved),
+            None => warn!("Cannot save episode state: no episode with a link is open"),
+        }
+    }
+
+    /// Shows the position right away and saves it to the database in the background
+    pub(crate) fn store_position(&mut self, saved: SavedPosition) {
+        self.podcasts_model.episode_states.insert(saved.ep_link.clone(), saved.position);
+        self.async_action_tx.send(saved.into_action());
+    }
+
+    /// Follows the player through loading, buffering and the end of the episode
+    pub(crate) fn update_player_state(&mut self) {
+        // Until the episode is opened the player still reports the previous one
+        if self.async_action_tx.in_flight(RequestKind::PlaybackPosition) {
+            return;
+        }
+        let player = &mut self.player_wrapper.inner_player;
+        let next = match self.player_wrapper.player_state {
+            PlayerState::Loading | PlayerState::Buffering | PlayerState::Playing if player.is_finished() => {
+                player.pause();
+                PlayerState::Ended
+            }
+            PlayerState::Loading if !player.is_loading() => PlayerState::Playing,
+            PlayerState::Playing if player.is_buffering() => PlayerState::Buffering,
+            PlayerState::Buffering if !player.is_buffering() => PlayerState::Playing,
+            _ => return,
+        };
+        self.player_wrapper.player_state = next;
+    }
+
+    /// What the relay has seen of the stream that's playing
+    fn stream_health(&self) -> Option<StreamHealth> {
+        self.stream_proxy.as_ref()?.health(self.player_wrapper.stream_url.as_deref()?)
+    }
+
+    /// A spinner and the throughput while waiting for the stream, and how playback ended
+    pub(crate) fn show_stream_status(&self, ui: &mut egui::Ui) {
+        let waiting = match self.player_wrapper.player_state {
+            PlayerState::Loading => "Loading",
+            PlayerState::Buffering => "Buffering",
+            PlayerState::Ended => {
+                ui.weak("· Ended");
+                return;
+            }
+            PlayerState::Error => {
+                ui.colored_label(ui.visuals().warn_fg_color, "· Playback failed");
+                return;
+            }
+            _ => return,
+        };
+
+        ui.spinner();
+        let health = self.stream_health();
+        let label = match health.as_ref().map(StreamHealth::bytes_per_second) {
+            Some(rate) if rate > 0.0 => format!("{} · {}", waiting, format_throughput(rate)),
+            _ => waiting.to_string(),
+        };
+        let label = ui.weak(label);
+        if let Some(health) = health.filter(|health| health.reconnects > 0) {
+            label.on_hover_text(format!("Reconnected {} times", health.reconnects));
+        }
+    }
+
+    /// Marks the parts of the stream that have arrived along the bottom of the timeline
+    pub(crate) fn show_buffered_ranges(&self, ui: &egui::Ui, timeline: egui::Rect) {
+        let Some(health) = self.stream_health() else {
+            return;
+        };
+        let track = timeline_track(ui, timeline);
+        let color = ui.visuals().weak_text_color().gamma_multiply(0.6);
+        let painter = ui.painter_at(timeline);
+        for (start, end) in health.buffered_fractions() {
+            let range = egui::Rect::from_x_y_ranges(
+                track.left() + start as f32 * track.width()..=track.left() + end as f32 * track.width(),
+                track.bottom() - BUFFERED_HEIGHT..=track.bottom(),
+            );
+            painter.rect_filled(range, 0.0, color);
+        }
+    }
+}
+
+fn format_throughput(bytes_per_second: f64) -> String {
+    if bytes_per_second >= 1024.0 * 1024.0 {
+        format!("{:.1} MB/s", bytes_per_second / (1024.0 * 1024.0))
+    } else {
+        format!("{:.0} KB/s", bytes_per_second / 1024.0)
+    }
+}
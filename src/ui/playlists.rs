@@ -0,0 +1,271 @@
+use eframe::egui;
+use log::{info, warn};
+use crate::entity::episode;
+use crate::playback::SavedPosition;
+use crate::playlist::{PlayState, Playlist, PlaylistSort};
+use crate::podcasts_model::PlaylistDialog;
+use crate::protocol::PlaylistEpisodes;
+use crate::{AsyncAction, MyEguiApp, PlayerState, RequestKind};
+
+impl MyEguiApp {
+    pub(crate) fn open_playlist(&mut self, playlist_id: i32) {
+        self.podcasts_model.current_playlist = Some(playlist_id);
+        self.podcasts_model.current_tag = None;
+        self.podcasts_model.inbox_open = false;
+        self.podcasts_model.current_podcast = Default::default();
+        self.async_action_tx.send_tracked(RequestKind::Episodes, AsyncAction::GetPlaylistEpisodes(playlist_id));
+    }
+
+    /// Plays the playlist from its first episode, loading it first
+    pub(crate) fn play_playlist(&mut self, playlist_id: i32) {
+        self.podcasts_model.play_when_loaded = Some(playlist_id);
+        self.open_playlist(playlist_id);
+    }
+
+    /// Loads the shown playlist again, its episodes may have changed
+    pub(crate) fn refresh_current_playlist(&mut self) {
+        if let Some(playlist_id) = self.podcasts_model.current_playlist {
+            self.async_action_tx.send_tracked(RequestKind::Episodes, AsyncAction::GetPlaylistEpisodes(playlist_id));
+        }
+    }
+
+    pub(crate) fn current_playlist_name(&self) -> Option<&str> {
+        let playlist_id = self.podcasts_model.current_playlist?;
+        self.podcasts_model.playlists.iter()
+            .find(|playlist| playlist.id == playlist_id)
+            .map(|playlist| playlist.name.as_str())
+    }
+
+    /// Queues the episodes and starts the first one
+    pub(crate) fn play_all(&mut self, episodes: Vec<episode::Model>) {
+        self.podcasts_model.queue = episodes.into();
+        self.play_next_in_queue();
+    }
+
+    /// Adds episodes to the end of the queue, starting it if nothing was loaded yet
+    pub(crate) fn enqueue(&mut self, episodes: Vec<episode::Model>) {
+        let queue = &mut self.podcasts_model.queue;
+        for episode in episodes {
+            if !queue.iter().any(|queued| queued.link == episode.link) {
+                queue.push_back(episode);
+            }
+        }
+        if self.podcasts_model.current_episode.is_none() {
+            self.play_next_in_queue();
+        }
+    }
+
+    fn play_next_in_queue(&mut self) {
+        while let Some(episode) = self.podcasts_model.queue.pop_front() {
+            if let Some(link) = episode.link.clone() {
+                self.save_playback_position();
+                info!("Playing next in queue: {}", episode.title.as_deref().unwrap_or("Unknown"));
+                self.podcasts_model.current_episode = Some(episode);
+                self.async_action_tx.send_tracked(RequestKind::PlaybackPosition, AsyncAction::LoadEpisodeState(link));
+                return;
+            }
+            warn!("Skipping queued episode without a link: {:?}", episode.title);
+        }
+    }
+
+    /// Moves on to the next queued episode once the current one has played through
+    pub(crate) fn advance_queue(&mut self) {
+        if !self.podcasts_model.queue.is_empty()
+            && self.player_wrapper.player_state == PlayerState::Ended
+            // Until the next episode is opened the state is still the one that ended
+            && !self.async_action_tx.in_flight(RequestKind::PlaybackPosition)
+        {
+            self.play_next_in_queue();
+        }
+    }
+
+    pub(crate) fn save_playback_position(&mut self) {
+        let saved = SavedPosition::of(
+            self.player_wrapper.inner_player.as_ref(),
+            self.podcasts_model.current_episode.as_ref(),
+        );
+        if let Some(saved) = saved {
+            self.store_position(saved);
+        }
+    }
+
+    pub(crate) fn open_playlist_window(&mut self, playlist: Option<Playlist>) {
+        self.podcasts_model.playlist_dialog = Some(match playlist {
+            Some(playlist) => PlaylistDialog {
+                playlist_id: Some(playlist.id),
+                name: playlist.name,
+                query: playlist.query,
+                busy: false,
+            },
+            None => Default::default(),
+        });
+    }
+
+    pub(crate) fn show_playlist_window(&mut self, ctx: &egui::Context) {
+        let model = &mut self.podcasts_model;
+        let Some(dialog) = &mut model.playlist_dialog else {
+            return;
+        };
+        let mut open = true;
+        let title = if dialog.playlist_id.is_some() { "Edit playlist" } else { "New playlist" };
+
+        egui::Window::new(title)
+            .collapsible(false)
+            .resizable(true)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                let query = &mut dialog.query;
+                egui::Grid::new("playlist_grid")
+                    .num_columns(2)
+                    .spacing([12.0, 6.0])
+                    .show(ui, |ui| {
+                        ui.label("Name");
+                        ui.text_edit_singleline(&mut dialog.name);
+                        ui.end_row();
+
+                        ui.label("Episodes");
+                        egui::ComboBox::from_id_source("playlist_state")
+                            .selected_text(query.state.label())
+                            .show_ui(ui, |ui| {
+                                for state in PlayState::ALL {
+                                    ui.selectable_value(&mut query.state, state, state.label());
+                                }
+                            });
+                        ui.end_row();
+
+                        ui.label("At least");
+                        optional_minutes(ui, &mut query.min_minutes);
+                        ui.end_row();
+
+                        ui.label("At most");
+                        optional_minutes(ui, &mut query.max_minutes);
+                        ui.end_row();
+
+                        ui.label("Order");
+                        egui::ComboBox::from_id_source("playlist_sort")
+                            .selected_text(query.sort.label())
+                            .show_ui(ui, |ui| {
+                                for sort in PlaylistSort::ALL {
+                                    ui.selectable_value(&mut query.sort, sort, sort.label());
+                                }
+                            });
+                        ui.end_row();
+
+                        ui.label("Limit");
+                        ui.horizontal(|ui| {
+                            let mut limited = query.limit.is_some();
+                            if ui.checkbox(&mut limited, "").changed() {
+                                query.limit = limited.then_some(20);
+                            }
+                            if let Some(limit) = &mut query.limit {
+                                ui.add(egui::DragValue::new(limit).clamp_range(1..=1000).suffix(" episodes"));
+                            }
+                        });
+                        ui.end_row();
+                    });
+
+                ui.separator();
+                ui.label("From these podcasts and tags, or from all if none is picked:");
+                egui::ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                    for group in &model.groups.tags {
+                        toggle(ui, &mut query.tag_ids, group.tag.id, &format!("🏷 {}", group.tag.name));
+                    }
+                    for podcast in model.podcasts.iter().flatten() {
+                        if let Some(title) = podcast.display_title() {
+                            toggle(ui, &mut query.podcast_ids, podcast.id, title);
+                        }
+                    }
+                });
+
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+                    if dialog.busy {
+                        ui.spinner();
+                    } else if ui.add(egui::Button::new("Save")).clicked() {
+                        dialog.busy = true;
+                        self.async_action_tx.send(AsyncAction::SavePlaylist(
+                            dialog.playlist_id,
+                            dialog.name.clone(),
+                            dialog.query.clone(),
+                        ));
+                    }
+                });
+            });
+
+        if !open {
+            self.podcasts_model.playlist_dialog = None;
+        }
+    }
+
+    pub(crate) fn playlists_updated(&mut self, result: Result<Vec<Playlist>, String>) {
+        match result {
+            Ok(playlists) => {
+                let saved = self.podcasts_model.playlist_dialog.as_ref().is_some_and(|dialog| dialog.busy);
+                if saved {
+                    self.podcasts_model.playlist_dialog = None;
+                }
+                self.podcasts_model.playlists = playlists;
+
+                if self.podcasts_model.current_playlist.is_some() && self.current_playlist_name().is_none() {
+                    // The playlist was deleted while it was shown
+                    self.podcasts_model.current_playlist = None;
+                    self.podcasts_model.episodes = None;
+                    self.podcasts_model.episode_states.clear();
+                } else if saved {
+                    self.refresh_current_playlist();
+                }
+            }
+            Err(e) => {
+                if let Some(dialog) = &mut self.podcasts_model.playlist_dialog {
+                    dialog.busy = false;
+                }
+                self.error = e;
+                self.show_error = true;
+            }
+        }
+    }
+
+    pub(crate) fn playlist_episodes_updated(&mut self, result: Result<PlaylistEpisodes, String>) {
+        match result {
+            Ok(playlist_episodes) => {
+                let play = self.podcasts_model.play_when_loaded == Some(playlist_episodes.playlist_id);
+                if play {
+                    self.podcasts_model.play_when_loaded = None;
+                    self.play_all(playlist_episodes.episodes.clone());
+                }
+                if self.podcasts_model.current_playlist == Some(playlist_episodes.playlist_id) {
+                    self.podcasts_model.episodes = Some(playlist_episodes.episodes);
+                    self.podcasts_model.episode_states = playlist_episodes.episode_states;
+                }
+            }
+            Err(e) => {
+                self.podcasts_model.play_when_loaded = None;
+                self.podcasts_model.episodes = None;
+                self.error = e;
+                self.show_error = true;
+            }
+        }
+    }
+}
+
+fn optional_minutes(ui: &mut egui::Ui, minutes: &mut Option<u32>) {
+    ui.horizontal(|ui| {
+        let mut enabled = minutes.is_some();
+        if ui.checkbox(&mut enabled, "").changed() {
+            *minutes = enabled.then_some(30);
+        }
+        if let Some(minutes) = minutes {
+            ui.add(egui::DragValue::new(minutes).clamp_range(1..=600).suffix(" min"));
+        }
+    });
+}
+
+fn toggle(ui: &mut egui::Ui, ids: &mut Vec<i32>, id: i32, label: &str) {
+    let mut selected = ids.contains(&id);
+    if ui.checkbox(&mut selected, label).changed() {
+        if selected {
+            ids.push(id);
+        } else {
+            ids.retain(|other| *other != id);
+        }
+    }
+}
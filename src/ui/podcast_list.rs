@@ -0,0 +1,412 @@
+use eframe::egui;
+use log::error;
+use crate::entity::podcast;
+use crate::playlist::Playlist;
+use crate::podcasts_model::{self, TagDialog};
+use crate::protocol::{PodcastGroups, TagEpisodes, TagGroup};
+use super::thumbnail;
+use crate::{AsyncAction, MyEguiApp, RequestKind};
+
+const LIST_ARTWORK_SIZE: f32 = 20.0;
+
+/// Drag-and-drop payload of a podcast being dragged onto a tag
+struct PodcastDrag(i32);
+
+/// Something picked in the side panel, applied once the podcast list is no longer borrowed
+enum ListAction {
+    OpenPodcast(podcast::Model),
+    OpenTag(i32),
+    Edit(podcast::Model),
+    Move(usize, usize),
+    Unsubscribe(i32, String),
+    Tag(i32, i32),
+    Untag(i32, i32),
+    NewTag(Option<i32>),
+    RenameTag(i32, String),
+    DeleteTag(i32),
+    OpenPlaylist(i32),
+    PlayPlaylist(i32),
+    EditPlaylist(Option<Playlist>),
+    DeletePlaylist(i32),
+    OpenInbox,
+}
+
+impl MyEguiApp {
+    /// Inbox, playlists, smart groups, tag folders and the list of all podcasts
+    pub(crate) fn show_podcast_list(&mut self, ui: &mut egui::Ui) {
+        self.request_podcast_artwork();
+        let Some(podcasts) = &self.podcasts_model.podcasts else {
+            if self.async_action_tx.in_flight(RequestKind::Podcasts) {
+                ui.spinner();
+            } else if !self.podcasts_model.podcasts_requested || ui.button("Load podcasts again").clicked() {
+                self.podcasts_model.podcasts_requested = true;
+                self.async_action_tx.send_tracked(RequestKind::Podcasts, AsyncAction::GetPodcasts);
+            }
+            return;
+        };
+        let groups = &self.podcasts_model.groups;
+        let mut action = None;
+
+        let unread = self.podcasts_model.inbox.len();
+        let inbox = if unread > 0 {
+            egui::RichText::new(format!("📬 Inbox ({})", unread)).strong()
+        } else {
+            egui::RichText::new("📭 Inbox")
+        };
+        if ui.add(egui::Link::new(inbox)).clicked() {
+            action = Some(ListAction::OpenInbox);
+        }
+
+        egui::CollapsingHeader::new("Playlists")
+            .default_open(true)
+            .show(ui, |ui| {
+                for playlist in &self.podcasts_model.playlists {
+                    ui.horizontal(|ui| {
+                        if ui.small_button("▶").on_hover_text("Play all").clicked() {
+                            action = Some(ListAction::PlayPlaylist(playlist.id));
+                        }
+                        let response = ui.add(egui::Link::new(&playlist.name));
+                        if response.clicked() {
+                            action = Some(ListAction::OpenPlaylist(playlist.id));
+                        }
+                        response.context_menu(|ui| {
+                            if ui.button("Play all").clicked() {
+                                action = Some(ListAction::PlayPlaylist(playlist.id));
+                                ui.close_menu();
+                            }
+                            if ui.button("Edit…").clicked() {
+                                action = Some(ListAction::EditPlaylist(Some(playlist.clone())));
+                                ui.close_menu();
+                            }
+                            if ui.button("Delete").clicked() {
+                                action = Some(ListAction::DeletePlaylist(playlist.id));
+                                ui.close_menu();
+                            }
+                        });
+                    });
+                }
+                if ui.add(egui::Button::new("➕ New playlist…")).clicked() {
+                    action = Some(ListAction::EditPlaylist(None));
+                }
+            });
+
+        egui::CollapsingHeader::new("Smart groups")
+            .default_open(false)
+            .show(ui, |ui| {
+                for (name, podcast_ids) in [
+                    ("Has new episodes", &groups.new_episodes),
+                    ("In progress", &groups.in_progress),
+                    ("Downloaded", &groups.downloaded),
+                ] {
+                    egui::CollapsingHeader::new(format!("{} ({})", name, podcast_ids.len()))
+                        .id_source(name)
+                        .show(ui, |ui| {
+                            for p in podcasts.iter().filter(|p| podcast_ids.contains(&p.id)) {
+                                podcast_link(ui, p, self.artwork(p.image_url.as_deref()), None, &groups.tags, None, &mut action);
+                            }
+                        });
+                }
+            });
+
+        for group in &groups.tags {
+            let tag_id = group.tag.id;
+            // Podcasts unsubscribed with their history kept still have their tags
+            let count = podcasts.iter().filter(|p| group.podcast_ids.contains(&p.id)).count();
+            let collapsing = egui::CollapsingHeader::new(format!("🏷 {} ({})", group.tag.name, count))
+                .id_source(("tag", tag_id))
+                .show(ui, |ui| {
+                    if ui.add(egui::Link::new("All episodes")).clicked() {
+                        action = Some(ListAction::OpenTag(tag_id));
+                    }
+                    for p in podcasts.iter().filter(|p| group.podcast_ids.contains(&p.id)) {
+                        podcast_link(ui, p, self.artwork(p.image_url.as_deref()), None, &groups.tags, Some(tag_id), &mut action);
+                    }
+                });
+
+            let header = collapsing.header_response;
+            if header.dnd_hover_payload::<PodcastDrag>().is_some() {
+                ui.painter().rect_stroke(header.rect, 2.0, ui.visuals().selection.stroke);
+            }
+            if let Some(drag) = header.dnd_release_payload::<PodcastDrag>() {
+                action = Some(ListAction::Tag(drag.0, tag_id));
+            }
+            header.context_menu(|ui| {
+                if ui.button("Show episodes").clicked() {
+                    action = Some(ListAction::OpenTag(tag_id));
+                    ui.close_menu();
+                }
+                if ui.button("Rename…").clicked() {
+                    action = Some(ListAction::RenameTag(tag_id, group.tag.name.clone()));
+                    ui.close_menu();
+                }
+                if ui.button("Delete").on_hover_text("Its podcasts stay subscribed").clicked() {
+                    action = Some(ListAction::DeleteTag(tag_id));
+                    ui.close_menu();
+                }
+            });
+        }
+
+        if ui.add(egui::Button::new("➕ New tag…")).on_hover_text("Drag podcasts onto a tag to add them").clicked() {
+            action = Some(ListAction::NewTag(None));
+        }
+        ui.separator();
+
+        for (index, p) in podcasts.iter().enumerate() {
+            podcast_link(ui, p, self.artwork(p.image_url.as_deref()), Some((index, podcasts.len())), &groups.tags, None, &mut action);
+        }
+
+        if let Some(action) = action {
+            self.apply_list_action(action);
+        }
+    }
+
+    fn apply_list_action(&mut self, action: ListAction) {
+        match action {
+            ListAction::OpenPodcast(p) => self.open_podcast(&p),
+            ListAction::OpenTag(tag_id) => self.open_tag(tag_id),
+            ListAction::Edit(p) => {
+                self.podcasts_model.edit_dialog = Some(podcasts_model::EditPodcastDialog::new(p));
+            }
+            ListAction::Move(from, to) => self.move_podcast(from, to),
+            ListAction::Unsubscribe(podcast_id, title) => {
+                self.podcasts_model.unsubscribe_dialog = Some(podcasts_model::UnsubscribeDialog {
+                    podcast_id,
+                    title,
+                    keep_history: true,
+                    busy: false,
+                });
+            }
+            ListAction::Tag(podcast_id, tag_id) => {
+                self.async_action_tx.send(AsyncAction::TagPodcast(podcast_id, tag_id));
+            }
+            ListAction::Untag(podcast_id, tag_id) => {
+                self.async_action_tx.send(AsyncAction::UntagPodcast(podcast_id, tag_id));
+            }
+            ListAction::NewTag(podcast_id) => {
+                self.podcasts_model.tag_dialog = Some(TagDialog { podcast_id, ..Default::default() });
+            }
+            ListAction::RenameTag(tag_id, name) => {
+                self.podcasts_model.tag_dialog = Some(TagDialog { tag_id: Some(tag_id), name, ..Default::default() });
+            }
+            ListAction::DeleteTag(tag_id) => {
+                self.async_action_tx.send(AsyncAction::DeleteTag(tag_id));
+            }
+            ListAction::OpenPlaylist(playlist_id) => self.open_playlist(playlist_id),
+            ListAction::PlayPlaylist(playlist_id) => self.play_playlist(playlist_id),
+            ListAction::EditPlaylist(playlist) => self.open_playlist_window(playlist),
+            ListAction::OpenInbox => self.open_inbox(),
+            ListAction::DeletePlaylist(playlist_id) => {
+                self.async_action_tx.send(AsyncAction::DeletePlaylist(playlist_id));
+            }
+        }
+    }
+
+    fn open_podcast(&mut self, p: &podcast::Model) {
+        let (Some(title), Some(link), Some(description)) = (p.display_title(), &p.link, &p.description) else {
+            error!("Podcast data is incomplete: id={}, title={:?}, link={:?}", p.id, p.title, p.link);
+            self.error = "This podcast has incomplete data and cannot be loaded.".to_string();
+            self.show_error = true;
+            return;
+        };
+
+        self.podcasts_model.current_tag = None;
+        self.podcasts_model.current_playlist = None;
+        self.podcasts_model.inbox_open = false;
+        self.podcasts_model.current_podcast = podcasts_model::Podcast {
+            id: Some(p.id),
+            title: title.to_string(),
+            link: link.clone(),
+            description: description.clone()
+        };
+
+        self.async_action_tx.send_tracked(
+            RequestKind::Episodes,
+            AsyncAction::GetEpisodes(link.clone(), p.id),
+        );
+        self.async_action_tx.send_tracked(
+            RequestKind::EpisodeStates,
+            AsyncAction::GetAllEpisodeStates(p.id),
+        );
+    }
+
+    /// Shows the episodes of every podcast with the tag
+    fn open_tag(&mut self, tag_id: i32) {
+        self.podcasts_model.current_tag = Some(tag_id);
+        self.podcasts_model.current_playlist = None;
+        self.podcasts_model.inbox_open = false;
+        self.podcasts_model.current_podcast = Default::default();
+        self.async_action_tx.send_tracked(RequestKind::Episodes, AsyncAction::GetTagEpisodes(tag_id));
+    }
+
+    /// What the episode list shows, when it's not a single podcast
+    pub(crate) fn episodes_heading(&self) -> String {
+        if let Some(name) = self.current_tag_name() {
+            return format!("Episodes tagged {}", name);
+        }
+        self.current_playlist_name().unwrap_or("Episodes").to_string()
+    }
+
+    /// Name of the tag whose episodes are shown, if any
+    fn current_tag_name(&self) -> Option<&str> {
+        let tag_id = self.podcasts_model.current_tag?;
+        self.podcasts_model.groups.tags.iter()
+            .find(|group| group.tag.id == tag_id)
+            .map(|group| group.tag.name.as_str())
+    }
+
+    pub(crate) fn show_tag_window(&mut self, ctx: &egui::Context) {
+        let Some(dialog) = &mut self.podcasts_model.tag_dialog else {
+            return;
+        };
+        let mut open = true;
+        let title = if dialog.tag_id.is_some() { "Rename tag" } else { "New tag" };
+
+        egui::Window::new(title)
+            .collapsible(false)
+            .resizable(false)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                let response = ui.add(egui::TextEdit::singleline(&mut dialog.name).hint_text("Name"));
+                let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+                    if dialog.busy {
+                        ui.spinner();
+                    } else if ui.add(egui::Button::new("Save")).clicked() || submitted {
+                        dialog.busy = true;
+                        self.async_action_tx.send(match dialog.tag_id {
+                            Some(tag_id) => AsyncAction::RenameTag(tag_id, dialog.name.clone()),
+                            None => AsyncAction::CreateTag(dialog.name.clone(), dialog.podcast_id),
+                        });
+                    }
+                });
+            });
+
+        if !open {
+            self.podcasts_model.tag_dialog = None;
+        }
+    }
+
+    pub(crate) fn groups_updated(&mut self, result: Result<PodcastGroups, String>) {
+        match result {
+            Ok(groups) => {
+                if self.podcasts_model.tag_dialog.as_ref().is_some_and(|dialog| dialog.busy) {
+                    self.podcasts_model.tag_dialog = None;
+                }
+                let current_tag = self.podcasts_model.current_tag;
+                self.podcasts_model.groups = groups;
+
+                match current_tag {
+                    // The tag was deleted while its episodes were shown
+                    Some(_) if self.current_tag_name().is_none() => {
+                        self.podcasts_model.current_tag = None;
+                        self.podcasts_model.episodes = None;
+                        self.podcasts_model.episode_states.clear();
+                    }
+                    // Podcasts may have been added to or removed from it
+                    Some(tag_id) => {
+                        self.async_action_tx.send_tracked(RequestKind::Episodes, AsyncAction::GetTagEpisodes(tag_id));
+                    }
+                    None => {}
+                }
+            }
+            Err(e) => {
+                if let Some(dialog) = &mut self.podcasts_model.tag_dialog {
+                    dialog.busy = false;
+                }
+                self.error = e;
+                self.show_error = true;
+            }
+        }
+    }
+
+    pub(crate) fn tag_episodes_updated(&mut self, result: Result<TagEpisodes, String>) {
+        match result {
+            Ok(tag_episodes) => {
+                if self.podcasts_model.current_tag == Some(tag_episodes.tag_id) {
+                    self.podcasts_model.episodes = Some(tag_episodes.episodes);
+                    self.podcasts_model.episode_states = tag_episodes.episode_states;
+                }
+            }
+            Err(e) => {
+                self.podcasts_model.episodes = None;
+                self.error = e;
+                self.show_error = true;
+            }
+        }
+    }
+}
+
+/// A podcast in the side panel. `position` is its place in the full list, which is where it
+/// can be moved; `in_tag` is the tag folder it's listed in.
+fn podcast_link(
+    ui: &mut egui::Ui,
+    p: &podcast::Model,
+    artwork: Option<egui::Image<'static>>,
+    position: Option<(usize, usize)>,
+    tags: &[TagGroup],
+    in_tag: Option<i32>,
+    action: &mut Option<ListAction>,
+) {
+    let Some(title) = p.display_title() else {
+        return;
+    };
+
+    let response = ui.horizontal(|ui| {
+        if let Some(artwork) = artwork {
+            thumbnail(ui, artwork, LIST_ARTWORK_SIZE);
+        }
+        ui.add(egui::Link::new(title)).interact(egui::Sense::drag())
+    }).inner;
+    response.dnd_set_drag_payload(PodcastDrag(p.id));
+    response.context_menu(|ui| {
+        if ui.button("Edit…").clicked() {
+            *action = Some(ListAction::Edit(p.clone()));
+            ui.close_menu();
+        }
+        if let Some((index, len)) = position {
+            if ui.add_enabled(index > 0, egui::Button::new("Move up")).clicked() {
+                *action = Some(ListAction::Move(index, index - 1));
+                ui.close_menu();
+            }
+            if ui.add_enabled(index + 1 < len, egui::Button::new("Move down")).clicked() {
+                *action = Some(ListAction::Move(index, index + 1));
+                ui.close_menu();
+            }
+        }
+        ui.menu_button("Tags", |ui| {
+            for group in tags {
+                let mut tagged = group.podcast_ids.contains(&p.id);
+                if ui.checkbox(&mut tagged, &group.tag.name).changed() {
+                    *action = Some(if tagged {
+                        ListAction::Tag(p.id, group.tag.id)
+                    } else {
+                        ListAction::Untag(p.id, group.tag.id)
+                    });
+                }
+            }
+            if !tags.is_empty() {
+                ui.separator();
+            }
+            if ui.button("New tag…").clicked() {
+                *action = Some(ListAction::NewTag(Some(p.id)));
+                ui.close_menu();
+            }
+        });
+        if let Some(tag_id) = in_tag {
+            if ui.button("Remove from tag").clicked() {
+                *action = Some(ListAction::Untag(p.id, tag_id));
+                ui.close_menu();
+            }
+        }
+        ui.separator();
+        if ui.button("Unsubscribe…").clicked() {
+            *action = Some(ListAction::Unsubscribe(p.id, title.to_string()));
+            ui.close_menu();
+        }
+    });
+
+    if response.clicked() {
+        *action = Some(ListAction::OpenPodcast(p.clone()));
+    }
+}
@@ -0,0 +1,77 @@
+use std::path::PathBuf;
+use eframe::egui;
+use log::{error, info};
+use crate::{AsyncAction, MyEguiApp};
+
+impl MyEguiApp {
+    /// Shown instead of the rest of the UI while the worker has no database to work with
+    pub(crate) fn show_recovery_screen(&mut self, ctx: &egui::Context) {
+        let Some(failure) = self.database_failure.clone() else {
+            return;
+        };
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.heading("The database could not be opened");
+            ui.add_space(5.0);
+            ui.label(&failure.error);
+            ui.add_space(5.0);
+            ui.weak(format!("Database file: {}", failure.file.display()));
+            ui.separator();
+
+            if self.recovering {
+                ui.spinner();
+                return;
+            }
+
+            if ui.add(egui::Button::new("Try again")).clicked() {
+                self.recovering = true;
+                self.async_action_tx.send(AsyncAction::RetryDatabase);
+            }
+
+            ui.add_space(10.0);
+            ui.label("Use another database file; it's created if it doesn't exist yet:");
+            ui.horizontal(|ui| {
+                ui.text_edit_singleline(&mut self.recovery_path);
+                if ui.add(egui::Button::new("Open")).clicked() && !self.recovery_path.trim().is_empty() {
+                    self.recovering = true;
+                    self.async_action_tx.send(AsyncAction::OpenDatabase(PathBuf::from(self.recovery_path.trim())));
+                }
+            });
+
+            ui.add_space(10.0);
+            match &failure.latest_backup {
+                Some(backup) => {
+                    ui.label(format!("Latest automatic backup: {}", backup.display()));
+                    if ui.add(egui::Button::new("Restore backup"))
+                        .on_hover_text("The current file is kept next to it with a .broken suffix")
+                        .clicked()
+                    {
+                        self.recovering = true;
+                        self.async_action_tx.send(AsyncAction::RestoreDatabaseBackup);
+                    }
+                }
+                None => {
+                    ui.weak("There is no automatic backup to restore.");
+                }
+            }
+        });
+    }
+
+    /// Remembers a database picked on the recovery screen for the next start
+    pub(crate) fn database_recovered(&mut self, file: PathBuf) {
+        self.database_failure = None;
+        self.recovering = false;
+        self.podcasts_model.podcasts = None;
+
+        if file == self.config.database_path() {
+            return;
+        }
+        let mut config = self.config.clone();
+        config.database_file = Some(file);
+        match config.save(&self.config_path) {
+            Ok(()) => info!("Now using database {}", config.database_path().display()),
+            Err(e) => error!("Failed to remember the database file: {}", e),
+        }
+        self.config = config;
+    }
+}
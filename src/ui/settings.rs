@@ -0,0 +1,241 @@
+use std::path::PathBuf;
+use eframe::egui;
+use log::{error, info};
+use crate::config::{self, Config, Theme};
+use crate::directory;
+use crate::loudness;
+use crate::silence;
+use crate::{AsyncAction, MyEguiApp};
+
+/// Settings being edited; only written to `MyEguiApp::config` once they pass validation
+pub(crate) struct SettingsDialog {
+    draft: Config,
+    data_dir: String,
+    download_dir: String,
+    podcast_index_key: String,
+    podcast_index_secret: String,
+    error: Option<String>,
+}
+
+impl SettingsDialog {
+    fn new(config: &Config) -> Self {
+        SettingsDialog {
+            draft: config.clone(),
+            data_dir: config.data_dir.display().to_string(),
+            download_dir: config.download_dir.display().to_string(),
+            podcast_index_key: config.podcast_index_key.clone().unwrap_or_default(),
+            podcast_index_secret: config.podcast_index_secret.clone().unwrap_or_default(),
+            error: None,
+        }
+    }
+
+    fn config(&self) -> Config {
+        Config {
+            data_dir: PathBuf::from(self.data_dir.trim()),
+            download_dir: PathBuf::from(self.download_dir.trim()),
+            user_agent: self.draft.user_agent.trim().to_string(),
+            itunes_base_url: self.draft.itunes_base_url.trim().to_string(),
+            podcast_index_base_url: self.draft.podcast_index_base_url.trim().to_string(),
+            podcast_index_key: non_empty(&self.podcast_index_key),
+            podcast_index_secret: non_empty(&self.podcast_index_secret),
+            ..self.draft.clone()
+        }
+    }
+}
+
+impl MyEguiApp {
+    pub(crate) fn open_settings_window(&mut self) {
+        self.settings_dialog = Some(SettingsDialog::new(&self.config));
+    }
+
+    pub(crate) fn show_settings_window(&mut self, ctx: &egui::Context, frame: &eframe::Frame) {
+        let supports_speed = self.player_wrapper.inner_player.supports_speed();
+        let Some(dialog) = &mut self.settings_dialog else {
+            return;
+        };
+        let mut open = true;
+        let mut save = false;
+
+        egui::Window::new("Settings")
+            .collapsible(false)
+            .resizable(false)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                egui::Grid::new("settings_grid")
+                    .num_columns(2)
+                    .spacing([12.0, 6.0])
+                    .show(ui, |ui| {
+                        ui.label("Data directory");
+                        ui.text_edit_singleline(&mut dialog.data_dir)
+                            .on_hover_text("Database and credentials; changes apply after a restart");
+                        ui.end_row();
+
+                        ui.label("Download directory");
+                        ui.text_edit_singleline(&mut dialog.download_dir);
+                        ui.end_row();
+
+                        ui.label("Refresh feeds every");
+                        ui.add(egui::DragValue::new(&mut dialog.draft.refresh_interval_minutes)
+                            .clamp_range(0..=24 * 60)
+                            .suffix(" min"))
+                            .on_hover_text("0 turns automatic refreshes off");
+                        ui.end_row();
+
+                        ui.label("Skip back");
+                        ui.add(egui::DragValue::new(&mut dialog.draft.skip_back_seconds)
+                            .clamp_range(1..=600)
+                            .suffix(" s"));
+                        ui.end_row();
+
+                        ui.label("Skip forward");
+                        ui.add(egui::DragValue::new(&mut dialog.draft.skip_forward_seconds)
+                            .clamp_range(1..=600)
+                            .suffix(" s"));
+                        ui.end_row();
+
+                        ui.label("Default speed");
+                        ui.add_enabled(supports_speed,
+                            egui::Slider::new(&mut dialog.draft.default_speed, config::MIN_SPEED..=config::MAX_SPEED)
+                                .step_by(0.05)
+                                .suffix("×"))
+                            .on_hover_text("Episodes start at this speed")
+                            .on_disabled_hover_text("The audio engine can't change the speed yet, so episodes play at normal speed");
+                        ui.end_row();
+
+                        ui.label("Theme");
+                        ui.horizontal(|ui| {
+                            ui.radio_value(&mut dialog.draft.theme, Theme::System, "System");
+                            ui.radio_value(&mut dialog.draft.theme, Theme::Light, "Light");
+                            ui.radio_value(&mut dialog.draft.theme, Theme::Dark, "Dark");
+                        });
+                        ui.end_row();
+
+                        ui.label("Artwork");
+                        ui.checkbox(&mut dialog.draft.show_artwork, "Show artwork")
+                            .on_hover_text("Turn off to save bandwidth");
+                        ui.end_row();
+
+                        ui.label("Loudness");
+                        ui.horizontal(|ui| {
+                            ui.checkbox(&mut dialog.draft.normalize_loudness, "Normalize to")
+                                .on_hover_text("Evens out episodes mastered louder or quieter, while playing and in exported clips");
+                            ui.add_enabled(dialog.draft.normalize_loudness,
+                                egui::DragValue::new(&mut dialog.draft.loudness_target)
+                                    .clamp_range(loudness::MIN_TARGET_LUFS..=loudness::MAX_TARGET_LUFS)
+                                    .speed(0.5)
+                                    .suffix(" LUFS"));
+                        });
+                        ui.end_row();
+
+                        ui.label("Silence");
+                        ui.horizontal(|ui| {
+                            ui.checkbox(&mut dialog.draft.trim_silence, "Shorten pauses longer than")
+                                .on_hover_text("Finding the pauses needs the whole episode, which is fetched unless it's downloaded");
+                            ui.add_enabled(dialog.draft.trim_silence,
+                                egui::DragValue::new(&mut dialog.draft.min_silence_seconds)
+                                    .clamp_range(silence::MIN_SILENCE_SECONDS..=silence::MAX_SILENCE_SECONDS)
+                                    .speed(0.1)
+                                    .suffix(" s"));
+                        });
+                        ui.end_row();
+
+                        ui.label("HTTP timeout");
+                        ui.add(egui::DragValue::new(&mut dialog.draft.http_timeout_seconds)
+                            .clamp_range(1..=600)
+                            .suffix(" s"));
+                        ui.end_row();
+
+                        ui.label("User agent");
+                        ui.horizontal(|ui| {
+                            ui.text_edit_singleline(&mut dialog.draft.user_agent);
+                            if ui.small_button("Reset").clicked() {
+                                dialog.draft.user_agent = config::default_user_agent();
+                            }
+                        });
+                        ui.end_row();
+
+                        ui.label("iTunes directory");
+                        ui.horizontal(|ui| {
+                            ui.text_edit_singleline(&mut dialog.draft.itunes_base_url);
+                            if ui.small_button("Reset").clicked() {
+                                dialog.draft.itunes_base_url = directory::DEFAULT_ITUNES_BASE_URL.to_string();
+                            }
+                        });
+                        ui.end_row();
+
+                        ui.label("Podcast Index");
+                        ui.horizontal(|ui| {
+                            ui.text_edit_singleline(&mut dialog.draft.podcast_index_base_url);
+                            if ui.small_button("Reset").clicked() {
+                                dialog.draft.podcast_index_base_url = directory::DEFAULT_PODCAST_INDEX_BASE_URL.to_string();
+                            }
+                        });
+                        ui.end_row();
+
+                        ui.label("Podcast Index key");
+                        ui.text_edit_singleline(&mut dialog.podcast_index_key)
+                            .on_hover_text("Podcast Index is only searched with an API key and secret from api.podcastindex.org");
+                        ui.end_row();
+
+                        ui.label("Podcast Index secret");
+                        ui.add(egui::TextEdit::singleline(&mut dialog.podcast_index_secret).password(true));
+                        ui.end_row();
+                    });
+
+                if let Some(error) = &dialog.error {
+                    ui.colored_label(egui::Color32::RED, error);
+                }
+
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+                    save = ui.add(egui::Button::new("Save")).clicked();
+                });
+            });
+
+        if save {
+            let config = dialog.config();
+            match config.save(&self.config_path) {
+                Ok(()) => {
+                    info!("Saved settings to {}", self.config_path.display());
+                    self.apply_config(config, ctx, frame);
+                    open = false;
+                }
+                Err(e) => {
+                    error!("Failed to save settings: {}", e);
+                    dialog.error = Some(e.user_friendly_message());
+                }
+            }
+        }
+
+        if !open {
+            self.settings_dialog = None;
+        }
+    }
+
+    fn apply_config(&mut self, config: Config, ctx: &egui::Context, frame: &eframe::Frame) {
+        if config.theme != self.config.theme {
+            ctx.set_visuals(theme_visuals(config.theme, frame.info().system_theme));
+        }
+        self.async_action_tx.send(AsyncAction::ApplyConfig(config.clone()));
+        if config.data_dir != self.config.data_dir {
+            self.notice = Some(format!(
+                "Rustcast will use {} after a restart. Move the files from {} there to keep your podcasts.",
+                config.data_dir.display(),
+                self.config.data_dir.display()
+            ));
+        }
+        self.config = config;
+        self.apply_loudness();
+    }
+}
+
+fn non_empty(value: &str) -> Option<String> {
+    Some(value.trim().to_string()).filter(|v| !v.is_empty())
+}
+
+pub(crate) fn theme_visuals(theme: Theme, system_theme: Option<eframe::Theme>) -> egui::Visuals {
+    match theme {
+        Theme::Light => egui::Visuals::light(),
+        Theme::Dark => egui::Visuals::dark(),
+        Theme::System => system_theme.unwrap_or(eframe::Theme::Dark).egui_visuals(),
+    }
+}
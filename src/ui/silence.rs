@@ -0,0 +1,66 @@
+use eframe::egui;
+use log::warn;
+use crate::silence::Silence;
+use crate::{AsyncAction, MyEguiApp, PlayerState, Progress, RequestKind};
+
+impl MyEguiApp {
+    /// Looks for the pauses of an episode that was just opened, if they're to be trimmed
+    pub(crate) fn detect_silences(&mut self, podcast_id: i32, link: String) {
+        self.player_wrapper.trimmer.reset();
+        if self.config.trim_silence {
+            self.async_action_tx.send_tracked(
+                RequestKind::Silence,
+                AsyncAction::DetectSilences(podcast_id, link, self.config.min_silence_seconds),
+            );
+        }
+    }
+
+    /// Skips the rest of a pause once playback enters it
+    pub(crate) fn trim_silence(&mut self) {
+        if !self.config.trim_silence
+            || self.player_wrapper.player_state != PlayerState::Playing
+            || self.async_action_tx.in_flight(RequestKind::PlaybackPosition)
+        {
+            return;
+        }
+        let Some(link) = self.podcasts_model.current_episode.as_ref().and_then(|episode| episode.link.as_deref()) else {
+            return;
+        };
+
+        // Runs before the listening session sees the new position, so the skip isn't taken
+        // for a seek of the listener's own
+        let position = self.player_wrapper.inner_player.current_position();
+        if let Some((from, to)) = self.player_wrapper.trimmer.landed(position) {
+            self.player_wrapper.sessions.trimmed(from, to);
+        }
+        if let Some(target) = self.player_wrapper.trimmer.skip(link, position) {
+            self.player_wrapper.inner_player.seek(target);
+        }
+    }
+
+    /// The search for pauses, which has to fetch and decode the whole episode before any
+    /// are trimmed, and a way to give up on it
+    pub(crate) fn show_silence_detection(&self, ui: &mut egui::Ui) {
+        let Some(progress) = self.async_action_tx.progress(RequestKind::Silence) else {
+            return;
+        };
+        ui.spinner();
+        ui.weak(match progress {
+            Progress::Downloading { received, total: Some(total) } if total > 0 =>
+                format!("· Fetching the episode to find pauses, {}%", received * 100 / total),
+            Progress::Downloading { .. } => "· Fetching the episode to find pauses".to_string(),
+            _ => "· Finding pauses".to_string(),
+        }).on_hover_text("Pauses are trimmed once the whole episode has been fetched and decoded");
+        if ui.small_button("✖").on_hover_text("Play without trimming pauses").clicked() {
+            self.async_action_tx.cancel_kind(RequestKind::Silence);
+        }
+    }
+
+    pub(crate) fn silences_detected(&mut self, link: String, result: Result<Vec<Silence>, String>) {
+        match result {
+            Ok(silences) => self.player_wrapper.trimmer.set_silences(link, silences),
+            // Playback goes on untrimmed, which isn't worth interrupting it for
+            Err(e) => warn!("Silences of {} are unknown: {}", link, e),
+        }
+    }
+}
@@ -0,0 +1,169 @@
+use eframe::egui;
+use crate::enclosure::{self, Quality};
+use crate::podcasts_model::{self, PodcastDialog};
+use crate::loudness::MAX_PODCAST_GAIN_DB;
+use crate::protocol::PodcastEdit;
+use crate::{AsyncAction, MyEguiApp, PlayerState};
+
+impl MyEguiApp {
+    /// Moves a podcast in the side panel right away and stores the new order
+    pub(crate) fn move_podcast(&mut self, from: usize, to: usize) {
+        let Some(podcasts) = &mut self.podcasts_model.podcasts else {
+            return;
+        };
+        if from >= podcasts.len() || to >= podcasts.len() {
+            return;
+        }
+
+        let podcast = podcasts.remove(from);
+        podcasts.insert(to, podcast);
+        let order = podcasts.iter().map(|p| p.id).collect();
+        self.async_action_tx.send(AsyncAction::ReorderPodcasts(order));
+    }
+
+    pub(crate) fn show_edit_podcast_window(&mut self, ctx: &egui::Context) {
+        let Some(edit) = &mut self.podcasts_model.edit_dialog else {
+            return;
+        };
+        let mut open = true;
+
+        egui::Window::new("Edit podcast")
+            .collapsible(false)
+            .resizable(true)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.add(egui::TextEdit::singleline(&mut edit.dialog.title).hint_text(&edit.feed_title))
+                    .on_hover_text("Leave empty to use the title from the feed");
+                ui.add(egui::TextEdit::singleline(&mut edit.dialog.link).hint_text("Feed URL"));
+                ui.add(egui::TextEdit::multiline(&mut edit.dialog.description).hint_text("Description"));
+                ui.horizontal(|ui| {
+                    ui.label("Volume offset");
+                    ui.add(egui::Slider::new(&mut edit.gain_db, -MAX_PODCAST_GAIN_DB..=MAX_PODCAST_GAIN_DB).suffix(" dB"))
+                        .on_hover_text("For shows mastered louder or quieter than the rest");
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Episode version").on_hover_text(
+                        "For feeds that offer episodes in more than one quality or format. Video plays as audio unless MP4 video is picked."
+                    );
+                    let preference = &mut edit.enclosure_preference;
+                    egui::ComboBox::from_id_source("preferred_quality")
+                        .selected_text(preference.quality.label())
+                        .show_ui(ui, |ui| {
+                            for quality in Quality::ALL {
+                                ui.selectable_value(&mut preference.quality, quality, quality.label());
+                            }
+                        });
+                    let format = preference.format.as_deref().map_or("Any format", |format| {
+                        enclosure::format_name(format).unwrap_or(format)
+                    });
+                    egui::ComboBox::from_id_source("preferred_format")
+                        .selected_text(format)
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut preference.format, None, "Any format");
+                            for (mime_type, name) in enclosure::FORMATS {
+                                ui.selectable_value(&mut preference.format, Some(mime_type.to_string()), name);
+                            }
+                        });
+                });
+
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+                    if edit.busy {
+                        ui.spinner();
+                    } else if ui.add(egui::Button::new("Save")).clicked() {
+                        edit.busy = true;
+                        self.async_action_tx.send(AsyncAction::UpdatePodcast(edit.podcast_id, PodcastEdit {
+                            title_override: edit.title_override(),
+                            link: edit.dialog.link.trim().to_string(),
+                            description: edit.dialog.description.clone(),
+                            gain_db: edit.gain_db,
+                            enclosure_preference: edit.enclosure_preference.clone(),
+                        }));
+                    }
+                });
+            });
+
+        if !open {
+            self.podcasts_model.edit_dialog = None;
+        }
+    }
+
+    pub(crate) fn podcast_saved(&mut self, result: Result<(), String>) {
+        let edit = self.podcasts_model.edit_dialog.take();
+
+        match result {
+            Ok(()) => {
+                let current = &mut self.podcasts_model.current_podcast;
+                if let Some(edit) = edit.filter(|edit| current.id == Some(edit.podcast_id)) {
+                    let PodcastDialog { title, link, description, .. } = edit.dialog;
+                    current.title = if title.trim().is_empty() { edit.feed_title } else { title.trim().to_string() };
+                    current.link = link.trim().to_string();
+                    current.description = description;
+                }
+                self.async_action_tx.send(AsyncAction::GetPodcasts);
+            }
+            Err(e) => {
+                // Keep what was typed so it can be corrected
+                self.podcasts_model.edit_dialog = edit.map(|edit| podcasts_model::EditPodcastDialog { busy: false, ..edit });
+                self.error = e;
+                self.show_error = true;
+            }
+        }
+    }
+
+    pub(crate) fn show_unsubscribe_window(&mut self, ctx: &egui::Context) {
+        let Some(dialog) = &mut self.podcasts_model.unsubscribe_dialog else {
+            return;
+        };
+        let mut close = false;
+
+        egui::Window::new("Unsubscribe")
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(format!("Unsubscribe from \"{}\"?", dialog.title));
+                ui.label("Its episodes and downloaded files will be removed.");
+                ui.checkbox(&mut dialog.keep_history, "Keep listening history")
+                    .on_hover_text("Playback positions are restored if you subscribe to this feed again");
+
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::TOP), |ui| {
+                    if dialog.busy {
+                        ui.spinner();
+                        return;
+                    }
+                    if ui.add(egui::Button::new("Unsubscribe")).clicked() {
+                        dialog.busy = true;
+                        self.async_action_tx.send(AsyncAction::Unsubscribe(dialog.podcast_id, dialog.keep_history));
+                    }
+                    if ui.add(egui::Button::new("Cancel")).clicked() {
+                        close = true;
+                    }
+                });
+            });
+
+        if close {
+            self.podcasts_model.unsubscribe_dialog = None;
+        }
+    }
+
+    pub(crate) fn unsubscribed(&mut self, podcast_id: i32, result: Result<(), String>) {
+        self.podcasts_model.unsubscribe_dialog = None;
+
+        // Clear the view either way, the reloaded podcast list shows what actually happened
+        if self.podcasts_model.current_podcast.id == Some(podcast_id) {
+            self.podcasts_model.current_podcast = Default::default();
+            self.podcasts_model.episodes = None;
+            self.podcasts_model.episode_states.clear();
+        }
+        if self.podcasts_model.current_episode.as_ref().is_some_and(|e| e.podcast_id == podcast_id) {
+            self.player_wrapper.inner_player.pause();
+            self.player_wrapper.player_state = PlayerState::Paused;
+            self.podcasts_model.current_episode = None;
+        }
+        self.async_action_tx.send(AsyncAction::GetPodcasts);
+
+        if let Err(e) = result {
+            self.error = e;
+            self.show_error = true;
+        }
+    }
+}
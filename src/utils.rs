@@ -21,28 +21,6 @@ pub fn validate_podcast_url(url: &str) -> RustcastResult<()> {
     }
 }
 
-pub fn safe_network_request(url: &str) -> RustcastResult<ureq::Response> {
-    validate_podcast_url(url)?;
-
-    match ureq::get(url)
-        .timeout(std::time::Duration::from_secs(30))
-        .call()
-    {
-        Ok(response) => Ok(response),
-        Err(ureq::Error::Status(code, _)) if code == 404 => {
-            Err(RustcastError::Network(NetworkError::RequestFailed(
-                "Podcast feed not found (404)".to_string()
-            )))
-        },
-        Err(ureq::Error::Status(code, _)) if code >= 500 => {
-            Err(RustcastError::Network(NetworkError::RequestFailed(
-                "Server error - please try again later".to_string()
-            )))
-        },
-        Err(e) => Err(RustcastError::from(e))
-    }
-}
-
 pub fn safe_rss_parse(content: &str) -> RustcastResult<rss::Channel> {
     if content.trim().is_empty() {
         return Err(RustcastError::Rss(crate::error::RssError::InvalidFeed(
@@ -80,4 +58,91 @@ pub fn validate_podcast_data(title: &str, link: &str, description: &str) -> Rust
     }
 
     Ok(())
-}
\ No newline at end of file
+}
+
+pub fn unix_timestamp() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+/// Feed URLs are compared without scheme differences or a trailing slash
+pub fn same_feed_url(a: &str, b: &str) -> bool {
+    fn normalize(url: &str) -> &str {
+        url.trim()
+            .trim_start_matches("https://")
+            .trim_start_matches("http://")
+            .trim_end_matches('/')
+    }
+
+    normalize(a).eq_ignore_ascii_case(normalize(b))
+}
+
+/// Whether moving a feed from `from` to `to` changes its stored URL. Unlike `same_feed_url`,
+/// switching between http and https counts, as it decides where credentials may go.
+pub fn feed_url_changed(from: &str, to: &str) -> bool {
+    let is_https = |url: &str| url.trim().get(..8).is_some_and(|scheme| scheme.eq_ignore_ascii_case("https://"));
+    !same_feed_url(from, to) || is_https(from) != is_https(to)
+}
+
+/// Whether the feed says `<podcast:locked>yes</podcast:locked>`: its owner doesn't want it
+/// taken over by another host
+pub fn channel_locked(channel: &rss::Channel) -> bool {
+    channel.extensions().get("podcast")
+        .and_then(|podcast| podcast.get("locked"))
+        .and_then(|locked| locked.first())
+        .and_then(|locked| locked.value())
+        .is_some_and(|value| value.trim().eq_ignore_ascii_case("yes"))
+}
+
+/// Artwork of a feed, preferring `itunes:image` as it's usually the larger, square one
+pub fn channel_image(channel: &rss::Channel) -> Option<String> {
+    channel.itunes_ext()
+        .and_then(|itunes| itunes.image())
+        .or(channel.image().map(|image| image.url()))
+        .map(|url| url.trim().to_string())
+        .filter(|url| !url.is_empty())
+}
+
+/// Unix timestamp of an RSS `pubDate`, which feeds are supposed to write as RFC 2822
+pub fn pub_date_timestamp(date: &str) -> Option<i64> {
+    chrono::DateTime::parse_from_rfc2822(date.trim())
+        .ok()
+        .map(|d| d.timestamp())
+}
+
+/// Seconds in an `itunes:duration`, which is either plain seconds or `[HH:]MM:SS`
+pub fn parse_duration(duration: &str) -> Option<i32> {
+    let mut seconds: i32 = 0;
+    let parts: Vec<&str> = duration.trim().split(':').collect();
+    if parts.len() > 3 {
+        return None;
+    }
+
+    for part in parts {
+        // Some feeds add fractions of a second, which don't matter here
+        let whole = part.split('.').next()?;
+        let value: i32 = whole.trim().parse().ok()?;
+        seconds = seconds.checked_mul(60)?.checked_add(value)?;
+    }
+    (seconds > 0).then_some(seconds)
+}
+
+/// Position in an episode as `M:SS`, or `H:MM:SS` past the first hour
+pub fn format_position(seconds: f64) -> String {
+    let total = seconds.max(0.0) as i64;
+    let (hours, minutes, secs) = (total / 3600, total % 3600 / 60, total % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, secs)
+    } else {
+        format!("{}:{:02}", minutes, secs)
+    }
+}
+
+/// Unix timestamp as local date and time, for display
+pub fn format_timestamp(timestamp: i64) -> String {
+    chrono::DateTime::from_timestamp(timestamp, 0)
+        .map(|d| d.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_default()
+}